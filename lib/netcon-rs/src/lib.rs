@@ -60,6 +60,7 @@ use std::{
     os::windows::ffi::OsStringExt,
     ptr::NonNull,
 };
+use uuid::Uuid;
 use winapi::{
     shared::{
         guiddef::{
@@ -312,6 +313,24 @@ impl NetConProperties {
     pub fn clsid_ui_object(&self) -> &CLSID {
         unsafe { &self.0.as_ref().clsidUiObject }
     }
+
+    /// Make an owned [`NetConPropertiesSnapshot`] of these properties.
+    pub fn snapshot(&self) -> NetConPropertiesSnapshot {
+        let guid = self.guid();
+
+        NetConPropertiesSnapshot {
+            guid: guid_to_uuid(guid),
+            name: String::from_utf16_lossy(self.raw_name()),
+            device_name: String::from_utf16_lossy(self.raw_device_name()),
+            status: self.status().ok(),
+            raw_status: self.raw_status(),
+            media_type: self.media_type().ok(),
+            raw_media_type: self.raw_media_type(),
+            characteristics: NetConCharacteristicFlags::from_bits_truncate(
+                self.raw_characteristics(),
+            ),
+        }
+    }
 }
 
 impl std::fmt::Debug for NetConProperties {
@@ -339,8 +358,49 @@ impl Drop for NetConProperties {
     }
 }
 
+/// An owned copy of [`NetConProperties`].
+///
+/// Unlike [`NetConProperties`], this does not borrow COM-allocated memory, so it can be sent to other threads.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct NetConPropertiesSnapshot {
+    /// The GUID
+    pub guid: Uuid,
+
+    /// The name
+    pub name: String,
+
+    /// The device name
+    pub device_name: String,
+
+    /// The connection status, or `None` if it is unknown
+    pub status: Option<NetConStatus>,
+
+    /// The raw connection status
+    pub raw_status: u32,
+
+    /// The media type, or `None` if it is unknown
+    pub media_type: Option<MediaType>,
+
+    /// The raw media type
+    pub raw_media_type: u32,
+
+    /// The connection characteristics
+    #[serde(serialize_with = "serialize_characteristics")]
+    pub characteristics: NetConCharacteristicFlags,
+}
+
+fn serialize_characteristics<S>(
+    characteristics: &NetConCharacteristicFlags,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_u32(characteristics.bits())
+}
+
 /// The connection status
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, serde::Serialize)]
 pub enum NetConStatus {
     Disconnected,
     Connecting,
@@ -380,7 +440,7 @@ impl TryFrom<u32> for NetConStatus {
 }
 
 /// The Connection Type
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, serde::Serialize)]
 pub enum MediaType {
     None,
     Direct,
@@ -436,3 +496,17 @@ bitflags! {
         const LAN_MASK = NCCF_LAN_MASK;
     }
 }
+
+/// Convert a GUID to a uuid.
+pub fn guid_to_uuid(guid: &GUID) -> Uuid {
+    // This should be infallible
+    Uuid::from_fields(guid.Data1, guid.Data2, guid.Data3, &guid.Data4)
+        .expect("a guid was not a valid uuid")
+}
+
+/// Parse an adapter name, which has the form "{<guid>}", as a uuid.
+///
+/// Returns `None` if the name is not a guid.
+pub fn adapter_name_to_uuid(name: &str) -> Option<Uuid> {
+    Uuid::parse_str(name.trim_start_matches('{').trim_end_matches('}')).ok()
+}
@@ -0,0 +1,442 @@
+use crate::sockaddr::socket_address_to_ip_addr;
+use std::{
+    convert::{
+        TryFrom,
+        TryInto,
+    },
+    ffi::CStr,
+    fmt::Display,
+    net::{
+        IpAddr,
+        Ipv4Addr,
+    },
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+use winapi::{
+    shared::{
+        ifdef::{
+            IfOperStatusDormant,
+            IfOperStatusDown,
+            IfOperStatusLowerLayerDown,
+            IfOperStatusNotPresent,
+            IfOperStatusTesting,
+            IfOperStatusUp,
+        },
+        ipifcons::{
+            IF_TYPE_ETHERNET_CSMACD,
+            IF_TYPE_IEEE1394,
+            IF_TYPE_IEEE80211,
+            IF_TYPE_OTHER,
+            IF_TYPE_PPP,
+            IF_TYPE_SOFTWARE_LOOPBACK,
+            IF_TYPE_TUNNEL,
+            IF_TYPE_WWANPP,
+        },
+        winerror::{
+            ERROR_BUFFER_OVERFLOW,
+            ERROR_NO_DATA,
+            ERROR_SUCCESS,
+        },
+        ws2def::AF_UNSPEC,
+    },
+    um::{
+        iphlpapi::{
+            GetAdaptersAddresses,
+            GetAdaptersInfo,
+        },
+        iptypes::{
+            GAA_FLAG_INCLUDE_GATEWAYS,
+            IP_ADAPTER_ADDRESSES,
+            IP_ADAPTER_INFO,
+        },
+        winbase::lstrlenW,
+    },
+};
+
+/// Microsoft recommends starting with a 15KB buffer.
+const INITIAL_BUFFER_SIZE: usize = 15 * 1024;
+
+/// The adapter list can grow between calls, so we retry a few times before giving up.
+const MAX_TRIES: usize = 3;
+
+/// An owned copy of an adapter's info from `GetAdaptersAddresses`.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AdapterAddresses {
+    /// The name of the adapter. This is a guid.
+    pub name: String,
+    pub friendly_name: String,
+    pub description: String,
+    pub dns_suffix: String,
+
+    pub if_index: u32,
+    pub ipv6_if_index: u32,
+    pub luid: u64,
+
+    pub physical_address: Vec<u8>,
+    pub mtu: u32,
+    pub if_type: u32,
+    pub oper_status: OperStatus,
+
+    /// The transmit link speed in bits per second
+    pub transmit_link_speed: u64,
+
+    /// The receive link speed in bits per second
+    pub receive_link_speed: u64,
+
+    pub dhcp_v4_enabled: bool,
+    pub ipv4_enabled: bool,
+    pub ipv6_enabled: bool,
+
+    pub unicast_addresses: Vec<UnicastAddress>,
+    pub anycast_addresses: Vec<IpAddr>,
+    pub multicast_addresses: Vec<IpAddr>,
+    pub dns_servers: Vec<IpAddr>,
+    pub gateway_addresses: Vec<IpAddr>,
+}
+
+impl AdapterAddresses {
+    /// Copy the data out of a raw adapter.
+    ///
+    /// # Safety
+    /// `adapter` must be a valid adapter from `GetAdaptersAddresses`.
+    unsafe fn from_raw(adapter: &IP_ADAPTER_ADDRESSES) -> Self {
+        let mut unicast_addresses = Vec::new();
+        let mut ptr = adapter.FirstUnicastAddress;
+        while let Some(address) = ptr.as_ref() {
+            if let Some(ip) = socket_address_to_ip_addr(&address.Address) {
+                unicast_addresses.push(UnicastAddress {
+                    address: ip,
+                    prefix_length: address.OnLinkPrefixLength,
+                    valid_lifetime: address.ValidLifetime,
+                    lease_lifetime: address.LeaseLifetime,
+                });
+            }
+            ptr = address.Next;
+        }
+
+        let mut anycast_addresses = Vec::new();
+        let mut ptr = adapter.FirstAnycastAddress;
+        while let Some(address) = ptr.as_ref() {
+            anycast_addresses.extend(socket_address_to_ip_addr(&address.Address));
+            ptr = address.Next;
+        }
+
+        let mut multicast_addresses = Vec::new();
+        let mut ptr = adapter.FirstMulticastAddress;
+        while let Some(address) = ptr.as_ref() {
+            multicast_addresses.extend(socket_address_to_ip_addr(&address.Address));
+            ptr = address.Next;
+        }
+
+        let mut dns_servers = Vec::new();
+        let mut ptr = adapter.FirstDnsServerAddress;
+        while let Some(address) = ptr.as_ref() {
+            dns_servers.extend(socket_address_to_ip_addr(&address.Address));
+            ptr = address.Next;
+        }
+
+        let mut gateway_addresses = Vec::new();
+        let mut ptr = adapter.FirstGatewayAddress;
+        while let Some(address) = ptr.as_ref() {
+            gateway_addresses.extend(socket_address_to_ip_addr(&address.Address));
+            ptr = address.Next;
+        }
+
+        let physical_address_len =
+            (adapter.PhysicalAddressLength as usize).min(adapter.PhysicalAddress.len());
+
+        AdapterAddresses {
+            name: if adapter.AdapterName.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(adapter.AdapterName)
+                    .to_string_lossy()
+                    .into_owned()
+            },
+            friendly_name: wide_ptr_to_string(adapter.FriendlyName),
+            description: wide_ptr_to_string(adapter.Description),
+            dns_suffix: wide_ptr_to_string(adapter.DnsSuffix),
+
+            if_index: adapter.u.s().IfIndex,
+            ipv6_if_index: adapter.Ipv6IfIndex,
+            luid: adapter.Luid.Value,
+
+            physical_address: adapter.PhysicalAddress[..physical_address_len].to_vec(),
+            mtu: adapter.Mtu,
+            if_type: adapter.IfType,
+            oper_status: OperStatus::from(adapter.OperStatus),
+
+            transmit_link_speed: adapter.TransmitLinkSpeed,
+            receive_link_speed: adapter.ReceiveLinkSpeed,
+
+            dhcp_v4_enabled: adapter.Dhcpv4Enabled() != 0,
+            ipv4_enabled: adapter.Ipv4Enabled() != 0,
+            ipv6_enabled: adapter.Ipv6Enabled() != 0,
+
+            unicast_addresses,
+            anycast_addresses,
+            multicast_addresses,
+            dns_servers,
+            gateway_addresses,
+        }
+    }
+
+    /// Get a human readable name for the interface type.
+    pub fn if_type_name(&self) -> &'static str {
+        match self.if_type {
+            IF_TYPE_OTHER => "Other",
+            IF_TYPE_ETHERNET_CSMACD => "Ethernet",
+            IF_TYPE_PPP => "PPP",
+            IF_TYPE_SOFTWARE_LOOPBACK => "Loopback",
+            IF_TYPE_IEEE80211 => "Wireless",
+            IF_TYPE_TUNNEL => "Tunnel",
+            IF_TYPE_IEEE1394 => "Firewire",
+            IF_TYPE_WWANPP => "Mobile Broadband",
+            _ => "Unknown",
+        }
+    }
+}
+
+/// A unicast address of an adapter
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UnicastAddress {
+    pub address: IpAddr,
+    pub prefix_length: u8,
+
+    /// The remaining valid lifetime in seconds
+    pub valid_lifetime: u32,
+
+    /// The remaining DHCP lease lifetime in seconds
+    pub lease_lifetime: u32,
+}
+
+/// The operational status of an interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum OperStatus {
+    Up,
+    Down,
+    Testing,
+    Unknown,
+    Dormant,
+    NotPresent,
+    LowerLayerDown,
+}
+
+impl From<u32> for OperStatus {
+    fn from(status: u32) -> Self {
+        match status {
+            IfOperStatusUp => OperStatus::Up,
+            IfOperStatusDown => OperStatus::Down,
+            IfOperStatusTesting => OperStatus::Testing,
+            IfOperStatusDormant => OperStatus::Dormant,
+            IfOperStatusNotPresent => OperStatus::NotPresent,
+            IfOperStatusLowerLayerDown => OperStatus::LowerLayerDown,
+            _ => OperStatus::Unknown,
+        }
+    }
+}
+
+impl Display for OperStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            OperStatus::Up => "Up",
+            OperStatus::Down => "Down",
+            OperStatus::Testing => "Testing",
+            OperStatus::Unknown => "Unknown",
+            OperStatus::Dormant => "Dormant",
+            OperStatus::NotPresent => "Not Present",
+            OperStatus::LowerLayerDown => "Lower Layer Down",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Get the ipv4 and ipv6 info of all adapters.
+pub fn get_adapter_addresses() -> std::io::Result<Vec<AdapterAddresses>> {
+    // Use a u64 buffer so that the structs are properly aligned.
+    let mut buffer: Vec<u64> = vec![0; INITIAL_BUFFER_SIZE / std::mem::size_of::<u64>()];
+
+    for _ in 0..MAX_TRIES {
+        let mut size: u32 = (buffer.len() * std::mem::size_of::<u64>())
+            .try_into()
+            .expect("buffer size cannot fit in a u32");
+        let code = unsafe {
+            GetAdaptersAddresses(
+                AF_UNSPEC as u32,
+                GAA_FLAG_INCLUDE_GATEWAYS,
+                std::ptr::null_mut(),
+                buffer.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+
+        match code {
+            ERROR_SUCCESS => {
+                let mut adapters = Vec::new();
+                let mut ptr = buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES;
+                while let Some(adapter) = unsafe { ptr.as_ref() } {
+                    adapters.push(unsafe { AdapterAddresses::from_raw(adapter) });
+                    ptr = adapter.Next;
+                }
+                return Ok(adapters);
+            }
+            ERROR_BUFFER_OVERFLOW => {
+                let len =
+                    (size as usize + std::mem::size_of::<u64>() - 1) / std::mem::size_of::<u64>();
+                buffer.resize(len, 0);
+            }
+            ERROR_NO_DATA => return Ok(Vec::new()),
+            code => return Err(std::io::Error::from_raw_os_error(code as i32)),
+        }
+    }
+
+    Err(std::io::Error::from_raw_os_error(
+        ERROR_BUFFER_OVERFLOW as i32,
+    ))
+}
+
+/// Info that is only available from the legacy `GetAdaptersInfo` api.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LegacyAdapterInfo {
+    /// The name of the adapter. This is a guid.
+    pub name: String,
+    pub combo_index: u32,
+
+    pub dhcp_enabled: bool,
+    pub dhcp_server: Option<Ipv4Addr>,
+    pub lease_obtained: Option<SystemTime>,
+    pub lease_expires: Option<SystemTime>,
+}
+
+impl LegacyAdapterInfo {
+    fn from_raw(adapter: &IP_ADAPTER_INFO) -> Self {
+        let dhcp_enabled = adapter.DhcpEnabled != 0;
+
+        // These fields are garbage if dhcp is not enabled.
+        let (dhcp_server, lease_obtained, lease_expires) = if dhcp_enabled {
+            (
+                char_array_to_string(&adapter.DhcpServer.IpAddress.String)
+                    .parse::<Ipv4Addr>()
+                    .ok()
+                    .filter(|ip| !ip.is_unspecified()),
+                time_t_to_system_time(adapter.LeaseObtained),
+                time_t_to_system_time(adapter.LeaseExpires),
+            )
+        } else {
+            (None, None, None)
+        };
+
+        LegacyAdapterInfo {
+            name: char_array_to_string(&adapter.AdapterName),
+            combo_index: adapter.ComboIndex,
+
+            dhcp_enabled,
+            dhcp_server,
+            lease_obtained,
+            lease_expires,
+        }
+    }
+}
+
+/// Get the info of all adapters from the legacy `GetAdaptersInfo` api.
+///
+/// This only includes adapters with ipv4 enabled.
+pub fn get_legacy_adapters_info() -> std::io::Result<Vec<LegacyAdapterInfo>> {
+    // Use a u64 buffer so that the structs are properly aligned.
+    let mut buffer: Vec<u64> = vec![0; INITIAL_BUFFER_SIZE / std::mem::size_of::<u64>()];
+
+    for _ in 0..MAX_TRIES {
+        let mut size: u32 = (buffer.len() * std::mem::size_of::<u64>())
+            .try_into()
+            .expect("buffer size cannot fit in a u32");
+        let code = unsafe { GetAdaptersInfo(buffer.as_mut_ptr().cast(), &mut size) };
+
+        match code {
+            ERROR_SUCCESS => {
+                let mut adapters = Vec::new();
+                let mut ptr = buffer.as_ptr() as *const IP_ADAPTER_INFO;
+                while let Some(adapter) = unsafe { ptr.as_ref() } {
+                    adapters.push(LegacyAdapterInfo::from_raw(adapter));
+                    ptr = adapter.Next;
+                }
+                return Ok(adapters);
+            }
+            ERROR_BUFFER_OVERFLOW => {
+                let len =
+                    (size as usize + std::mem::size_of::<u64>() - 1) / std::mem::size_of::<u64>();
+                buffer.resize(len, 0);
+            }
+            ERROR_NO_DATA => return Ok(Vec::new()),
+            code => return Err(std::io::Error::from_raw_os_error(code as i32)),
+        }
+    }
+
+    Err(std::io::Error::from_raw_os_error(
+        ERROR_BUFFER_OVERFLOW as i32,
+    ))
+}
+
+/// An adapter that can be picked from a list, shown by its friendly name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceOption {
+    pub luid: u64,
+    pub index: u32,
+    pub name: String,
+}
+
+impl InterfaceOption {
+    pub fn new(adapter: &AdapterAddresses) -> Self {
+        // Adapters without ipv4 have no ipv4 index, but the indexes are the same otherwise.
+        let index = if adapter.if_index != 0 {
+            adapter.if_index
+        } else {
+            adapter.ipv6_if_index
+        };
+
+        Self {
+            luid: adapter.luid,
+            index,
+            name: adapter.friendly_name.clone(),
+        }
+    }
+}
+
+impl Display for InterfaceOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.name.fmt(f)
+    }
+}
+
+/// Convert a `time_t` into a [`SystemTime`].
+///
+/// Returns `None` for times at or before the unix epoch, which windows uses for unset times.
+fn time_t_to_system_time(time: i64) -> Option<SystemTime> {
+    let secs = u64::try_from(time).ok().filter(|secs| *secs != 0)?;
+    Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Copy a nul-terminated char array into a [`String`], replacing invalid data.
+fn char_array_to_string(chars: &[i8]) -> String {
+    let bytes: Vec<u8> = chars
+        .iter()
+        .take_while(|c| **c != 0)
+        .map(|c| *c as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// Copy a nul-terminated wide string into a [`String`], replacing invalid data.
+///
+/// # Safety
+/// `ptr` must be null or a valid nul-terminated wide string.
+pub unsafe fn wide_ptr_to_string(ptr: *const u16) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+
+    let len = lstrlenW(ptr).try_into().expect("len cannot fit in a usize");
+    String::from_utf16_lossy(std::slice::from_raw_parts(ptr, len))
+}
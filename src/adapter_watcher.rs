@@ -0,0 +1,348 @@
+use crate::registry_adapter::RegistryAdapter;
+use anyhow::Context;
+use iced::futures::{
+    stream::BoxStream,
+    StreamExt,
+};
+use log::{
+    debug,
+    error,
+    info,
+};
+use std::{
+    hash::Hash,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::mpsc::UnboundedSender;
+use winapi::{
+    shared::{
+        minwindef::{
+            FALSE,
+            TRUE,
+        },
+        netioapi::{
+            CancelMibChangeNotify2,
+            NotifyIpInterfaceChange,
+            NotifyUnicastIpAddressChange,
+            MIB_NOTIFICATION_TYPE,
+            PMIB_IPINTERFACE_ROW,
+            PMIB_UNICASTIPADDRESS_ROW,
+        },
+        ntdef::{
+            HANDLE,
+            PVOID,
+        },
+        winerror::{
+            ERROR_SUCCESS,
+            NO_ERROR,
+        },
+        ws2def::AF_UNSPEC,
+    },
+    um::{
+        handleapi::CloseHandle,
+        synchapi::{
+            CreateEventW,
+            SetEvent,
+            WaitForMultipleObjects,
+        },
+        winbase::{
+            INFINITE,
+            WAIT_OBJECT_0,
+        },
+        winnt::{
+            REG_NOTIFY_CHANGE_LAST_SET,
+            REG_NOTIFY_CHANGE_NAME,
+        },
+        winreg::RegNotifyChangeKeyValue,
+    },
+};
+use winreg::{
+    enums::{
+        HKEY_LOCAL_MACHINE,
+        KEY_NOTIFY,
+    },
+    RegKey,
+};
+
+/// How long to wait for a burst of change notifications to settle before reporting it.
+const DEBOUNCE_DURATION: Duration = Duration::from_millis(500);
+
+/// Get a subscription that fires whenever an adapter is added, removed, changes state, gets an address, or has its registry settings changed.
+///
+/// Bursts of changes are debounced into a single event.
+pub fn adapter_changes() -> iced::Subscription<()> {
+    iced::Subscription::from_recipe(AdapterChanges)
+}
+
+struct AdapterChanges;
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for AdapterChanges
+where
+    H: std::hash::Hasher,
+{
+    type Output = ();
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let watcher = match AdapterWatcher::new(tx).context("failed to create adapter watcher") {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                error!("{:?}", e);
+                return iced::futures::stream::empty().boxed();
+            }
+        };
+
+        iced::futures::stream::unfold((watcher, rx), |(watcher, mut rx)| async move {
+            rx.recv().await?;
+
+            // Swallow events until the burst settles.
+            let mut num_events = 1;
+            loop {
+                match tokio::time::timeout(DEBOUNCE_DURATION, rx.recv()).await {
+                    Ok(Some(())) => {
+                        num_events += 1;
+                    }
+                    Ok(None) => return None,
+                    Err(_elapsed) => break,
+                }
+            }
+            debug!("Debounced {} adapter change event(s)", num_events);
+
+            Some(((), (watcher, rx)))
+        })
+        .boxed()
+    }
+}
+
+/// Watches for adapter changes, sending a message for each one.
+///
+/// All notifications are cancelled when this is dropped.
+struct AdapterWatcher {
+    _interface_notification: MibChangeNotification,
+    _address_notification: MibChangeNotification,
+    _registry_watcher: RegistryWatcher,
+
+    // This must be dropped after the notifications are cancelled, as the callbacks reference it.
+    _sender: Box<UnboundedSender<()>>,
+}
+
+impl AdapterWatcher {
+    fn new(sender: UnboundedSender<()>) -> anyhow::Result<Self> {
+        let registry_watcher =
+            RegistryWatcher::new(sender.clone()).context("failed to watch registry adapters")?;
+
+        let sender = Box::new(sender);
+        let caller_context = &*sender as *const UnboundedSender<()> as PVOID;
+
+        let mut interface_handle = std::ptr::null_mut();
+        let code = unsafe {
+            NotifyIpInterfaceChange(
+                AF_UNSPEC as u16,
+                Some(ip_interface_change_callback),
+                caller_context,
+                FALSE as u8,
+                &mut interface_handle,
+            )
+        };
+        if code != NO_ERROR {
+            return Err(std::io::Error::from_raw_os_error(code as i32))
+                .context("failed to register for interface change notifications");
+        }
+        let interface_notification = MibChangeNotification(interface_handle);
+
+        let mut address_handle = std::ptr::null_mut();
+        let code = unsafe {
+            NotifyUnicastIpAddressChange(
+                AF_UNSPEC as u16,
+                Some(unicast_ip_address_change_callback),
+                caller_context,
+                FALSE as u8,
+                &mut address_handle,
+            )
+        };
+        if code != NO_ERROR {
+            return Err(std::io::Error::from_raw_os_error(code as i32))
+                .context("failed to register for address change notifications");
+        }
+        let address_notification = MibChangeNotification(address_handle);
+
+        info!("Watching for adapter changes");
+
+        Ok(Self {
+            _interface_notification: interface_notification,
+            _address_notification: address_notification,
+            _registry_watcher: registry_watcher,
+
+            _sender: sender,
+        })
+    }
+}
+
+// Safety: The notification handles may be cancelled from any thread,
+// and the sender is only accessed through a shared reference.
+unsafe impl Send for AdapterWatcher {}
+
+unsafe extern "system" fn ip_interface_change_callback(
+    caller_context: PVOID,
+    _row: PMIB_IPINTERFACE_ROW,
+    _notification_type: MIB_NOTIFICATION_TYPE,
+) {
+    let sender = &*(caller_context as *const UnboundedSender<()>);
+    let _ = sender.send(()).is_ok();
+}
+
+unsafe extern "system" fn unicast_ip_address_change_callback(
+    caller_context: PVOID,
+    _row: PMIB_UNICASTIPADDRESS_ROW,
+    _notification_type: MIB_NOTIFICATION_TYPE,
+) {
+    let sender = &*(caller_context as *const UnboundedSender<()>);
+    let _ = sender.send(()).is_ok();
+}
+
+/// A handle from one of the `Notify*Change` functions.
+///
+/// The notification is cancelled on drop.
+/// This blocks until any running callbacks complete.
+struct MibChangeNotification(HANDLE);
+
+impl Drop for MibChangeNotification {
+    fn drop(&mut self) {
+        let code = unsafe { CancelMibChangeNotify2(self.0) };
+        if code != NO_ERROR {
+            error!(
+                "failed to cancel change notification: {}",
+                std::io::Error::from_raw_os_error(code as i32)
+            );
+        }
+    }
+}
+
+/// A win32 event.
+struct Event(HANDLE);
+
+impl Event {
+    /// Make a new unnamed, unsignaled, auto-reset event.
+    fn new() -> std::io::Result<Self> {
+        let handle = unsafe { CreateEventW(std::ptr::null_mut(), FALSE, FALSE, std::ptr::null()) };
+        if handle.is_null() {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Self(handle))
+    }
+
+    /// Signal this event.
+    fn set(&self) -> std::io::Result<()> {
+        if unsafe { SetEvent(self.0) } == FALSE {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        unsafe {
+            CloseHandle(self.0);
+        }
+    }
+}
+
+// Safety: Events may be used from any thread.
+unsafe impl Send for Event {}
+unsafe impl Sync for Event {}
+
+/// Watches the adapter class registry key on a background thread.
+///
+/// The thread is stopped when this is dropped.
+struct RegistryWatcher {
+    stop_event: Arc<Event>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl RegistryWatcher {
+    fn new(sender: UnboundedSender<()>) -> anyhow::Result<Self> {
+        let key = RegKey::predef(HKEY_LOCAL_MACHINE)
+            .open_subkey_with_flags(RegistryAdapter::REGISTRY_ADAPTER_KEY_STR, KEY_NOTIFY)
+            .context("failed to open registry adapter key")?;
+        let stop_event = Arc::new(Event::new().context("failed to create stop event")?);
+        let change_event = Event::new().context("failed to create change event")?;
+
+        let thread_stop_event = stop_event.clone();
+        let handle = std::thread::Builder::new()
+            .name("registry-watcher".into())
+            .spawn(move || {
+                if let Err(e) = watch_registry(&key, &thread_stop_event, &change_event, &sender) {
+                    error!("{:?}", e);
+                }
+            })
+            .context("failed to spawn registry watcher thread")?;
+
+        Ok(Self {
+            stop_event,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for RegistryWatcher {
+    fn drop(&mut self) {
+        if let Err(e) = self.stop_event.set() {
+            // The thread will not be able to exit, so don't wait for it.
+            error!("failed to stop registry watcher: {}", e);
+            return;
+        }
+
+        if let Some(handle) = self.handle.take() {
+            if handle.join().is_err() {
+                error!("registry watcher thread panicked");
+            }
+        }
+    }
+}
+
+fn watch_registry(
+    key: &RegKey,
+    stop_event: &Event,
+    change_event: &Event,
+    sender: &UnboundedSender<()>,
+) -> anyhow::Result<()> {
+    let handles = [stop_event.0, change_event.0];
+    loop {
+        // Notifications are one-shot, so this needs to be re-registered after every change.
+        let code = unsafe {
+            RegNotifyChangeKeyValue(
+                key.raw_handle(),
+                TRUE,
+                REG_NOTIFY_CHANGE_NAME | REG_NOTIFY_CHANGE_LAST_SET,
+                change_event.0,
+                TRUE,
+            )
+        };
+        if code != ERROR_SUCCESS as i32 {
+            return Err(std::io::Error::from_raw_os_error(code))
+                .context("failed to register for registry change notifications");
+        }
+
+        let ret = unsafe {
+            WaitForMultipleObjects(handles.len() as u32, handles.as_ptr(), FALSE, INFINITE)
+        };
+        match ret {
+            WAIT_OBJECT_0 => return Ok(()),
+            ret if ret == WAIT_OBJECT_0 + 1 => {
+                if sender.send(()).is_err() {
+                    // Nobody is listening anymore.
+                    return Ok(());
+                }
+            }
+            _ => {
+                return Err(std::io::Error::last_os_error()).context("failed to wait for events");
+            }
+        }
+    }
+}
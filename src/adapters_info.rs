@@ -1,46 +1,174 @@
 use crate::{
+    adapter_addresses::{
+        AdapterAddresses,
+        LegacyAdapterInfo,
+    },
+    conflict_check::{
+        check_conflicts,
+        ConflictCheck,
+        ConflictReport,
+        NeighborProber,
+    },
+    dhcp::{
+        format_relative_time,
+        DhcpAction,
+        DhcpActionState,
+    },
+    dns_config::{
+        DnsEditor,
+        DnsMessage,
+        DnsPreset,
+        DnsSettings,
+    },
+    export::{
+        save_export,
+        AdapterRecord,
+        ExportFormat,
+    },
     format_mac_address_to_string,
+    if_stats::{
+        format_bytes_per_sec,
+        InterfaceStatistics,
+        SampleInterval,
+    },
+    ip_config::{
+        AddressConfig,
+        ConfigChange,
+        IpConfig,
+        IpConfigEditor,
+        IpConfigMessage,
+    },
+    snapshot_diff::{
+        AdapterChange,
+        DiffHistory,
+        SnapshotDiff,
+    },
     style::{
         ForegroundGreenButtonStyle,
         ForegroundGreyContainerStyle,
         GreyStyle,
         GreyStyleCopyTextHack,
+        HIGHLIGHT_TEXT_COLOR,
+    },
+    subnet::{
+        mask_from_prefix_length,
+        SubnetInfo,
     },
 };
+use anyhow::Context;
 use iced::{
     Align,
     Button,
+    Canvas,
     Clipboard,
     Column,
     Command,
     Container,
     Element,
     Length,
+    PickList,
     Row,
     Scrollable,
     Space,
+    Subscription,
     Text,
     TextInput,
 };
-use iphlpapi::{
-    IpAdapterInfo,
-    IpAddrString,
+use log::{
+    error,
+    info,
+    warn,
+};
+use std::{
+    collections::HashMap,
+    net::{
+        IpAddr,
+        Ipv4Addr,
+    },
+    path::PathBuf,
+    sync::Arc,
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+    },
 };
-use log::info;
-use std::time::Instant;
+
+/// How often lease countdowns are updated
+const LEASE_TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The height of a throughput graph
+const GRAPH_HEIGHT: u16 = 80;
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Refresh,
+    ToggleSection(usize, Section),
+
+    Dhcp(usize, DhcpAction),
+    DhcpComplete(String, DhcpAction, Arc<anyhow::Result<()>>),
+    Tick,
+    SampleStatistics,
+
+    ExportFormatSelected(ExportFormat),
+    CopyExport,
+    SaveExport,
+    SaveExportComplete(Arc<anyhow::Result<PathBuf>>),
+
+    IpConfig(usize, IpConfigMessage),
+    IpConfigComplete(String, Arc<anyhow::Result<()>>),
+
+    Dns(usize, DnsMessage),
+    DnsComplete(String, Arc<anyhow::Result<()>>),
+
+    OlderDiff,
+    NewerDiff,
+    ClearDiffs,
+
+    /// Handled by the app, which pings the address in the Ping tab
+    PingGateway(String),
+
+    CheckConflicts(usize),
+    ConflictCheckComplete(String, Arc<Vec<ConflictReport>>),
 
     Nop,
 }
 
+/// A collapsible section of an adapter's info
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Section {
+    General,
+    Addresses,
+    Dns,
+    Dhcp,
+    Link,
+    Statistics,
+    IpConfig,
+}
+
 pub struct AdaptersInfo {
     adapters_info: std::io::Result<Vec<AdapterState>>,
 
+    // Keyed by luid, so that history survives refreshes.
+    statistics: HashMap<u64, InterfaceStatistics>,
+
+    dns_presets: Vec<DnsPreset>,
+
+    export_format: ExportFormat,
+    export_status: String,
+
+    /// The records from the last successful refresh, to diff the next one against
+    last_snapshot: Option<Vec<AdapterRecord>>,
+    diff_history: DiffHistory,
+
     scroll_state: iced::scrollable::State,
     button_state: iced::button::State,
+    export_format_state: iced::pick_list::State<ExportFormat>,
+    copy_export_button_state: iced::button::State,
+    save_export_button_state: iced::button::State,
+    older_diff_button_state: iced::button::State,
+    newer_diff_button_state: iced::button::State,
+    clear_diffs_button_state: iced::button::State,
 }
 
 impl AdaptersInfo {
@@ -48,8 +176,24 @@ impl AdaptersInfo {
         let mut ret = AdaptersInfo {
             adapters_info: Err(std::io::Error::from_raw_os_error(0)),
 
+            statistics: HashMap::new(),
+
+            dns_presets: Vec::new(),
+
+            export_format: ExportFormat::default(),
+            export_status: String::new(),
+
+            last_snapshot: None,
+            diff_history: DiffHistory::default(),
+
             scroll_state: iced::scrollable::State::new(),
             button_state: iced::button::State::new(),
+            export_format_state: iced::pick_list::State::default(),
+            copy_export_button_state: iced::button::State::new(),
+            save_export_button_state: iced::button::State::new(),
+            older_diff_button_state: iced::button::State::new(),
+            newer_diff_button_state: iced::button::State::new(),
+            clear_diffs_button_state: iced::button::State::new(),
         };
         ret.refresh_adapters_info();
         ret
@@ -57,19 +201,459 @@ impl AdaptersInfo {
 
     pub fn refresh_adapters_info(&mut self) {
         let start = Instant::now();
-        let adapters_info = iphlpapi::get_adapters_info();
+        let adapter_addresses = crate::adapter_addresses::get_adapter_addresses();
+        info!("Got adapter addresses in {:?}", start.elapsed());
+
+        // The combo index and dhcp lease times are only available from the legacy api.
+        let start = Instant::now();
+        let mut legacy_adapters_info: HashMap<String, LegacyAdapterInfo> =
+            match crate::adapter_addresses::get_legacy_adapters_info() {
+                Ok(adapters_info) => adapters_info
+                    .into_iter()
+                    .map(|adapter| (adapter.name.clone(), adapter))
+                    .collect(),
+                Err(e) => {
+                    warn!("Failed to get adapters info: {}", e);
+                    HashMap::new()
+                }
+            };
         info!("Got adapters info in {:?}", start.elapsed());
 
-        self.adapters_info = adapters_info
-            .map(|adapters_info| adapters_info.iter().map(AdapterState::new).collect());
+        // Keep the view state of adapters that still exist.
+        let mut old_states: HashMap<String, AdapterState> =
+            std::mem::replace(&mut self.adapters_info, Ok(Vec::new()))
+                .map(|adapters| {
+                    adapters
+                        .into_iter()
+                        .map(|adapter| (adapter.adapter_name.clone(), adapter))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+        self.adapters_info = adapter_addresses.map(|adapter_addresses| {
+            adapter_addresses
+                .iter()
+                .map(|adapter| {
+                    let mut state =
+                        AdapterState::new(adapter, legacy_adapters_info.remove(&adapter.name));
+                    if let Some(old_state) = old_states.remove(&adapter.name) {
+                        state.keep_view_state(old_state);
+                    }
+                    state
+                })
+                .collect()
+        });
+
+        // Drop the statistics of adapters that are gone.
+        match self.adapters_info.as_ref() {
+            Ok(adapters) => {
+                self.statistics
+                    .retain(|luid, _| adapters.iter().any(|adapter| adapter.luid == *luid));
+            }
+            Err(_) => self.statistics.clear(),
+        }
+
+        // Failed refreshes keep the old snapshot, so the next successful one shows everything that changed.
+        if let Ok(adapters) = self.adapters_info.as_ref() {
+            let records: Vec<AdapterRecord> = adapters
+                .iter()
+                .map(|adapter| adapter.record.clone())
+                .collect();
+            if let Some(last_snapshot) = self.last_snapshot.as_ref() {
+                self.diff_history.push(SnapshotDiff::new(
+                    last_snapshot,
+                    &records,
+                    SystemTime::now(),
+                ));
+            }
+            self.last_snapshot = Some(records);
+        }
+    }
+
+    /// Set the dns presets that can be picked for an adapter.
+    pub fn set_dns_presets(&mut self, dns_presets: Vec<DnsPreset>) {
+        self.dns_presets = dns_presets;
+    }
+
+    /// Export the current adapters in the selected format.
+    fn export(&self) -> anyhow::Result<String> {
+        let records: Vec<AdapterRecord> = match self.adapters_info.as_ref() {
+            Ok(adapters) => adapters
+                .iter()
+                .map(|adapter| adapter.record.clone())
+                .collect(),
+            Err(e) => anyhow::bail!("no adapters to export: {}", e),
+        };
+
+        self.export_format.export(&records)
+    }
+
+    /// Get the subscriptions of this tab.
+    ///
+    /// Interface statistics are sampled every `sample_interval`.
+    pub fn subscription(&self, sample_interval: SampleInterval) -> Subscription<Message> {
+        let adapters = match self.adapters_info.as_ref() {
+            Ok(adapters) if !adapters.is_empty() => adapters,
+            _ => return Subscription::none(),
+        };
+
+        let mut subscriptions = vec![
+            iced::time::every(sample_interval.as_duration()).map(|_| Message::SampleStatistics)
+        ];
+
+        let has_lease = adapters
+            .iter()
+            .any(|adapter| adapter.lease_expires_time.is_some());
+        if has_lease {
+            subscriptions.push(iced::time::every(LEASE_TICK_INTERVAL).map(|_| Message::Tick));
+        }
+
+        Subscription::batch(subscriptions)
     }
 
-    pub fn update(&mut self, message: Message, _clipboard: &mut Clipboard) -> Command<Message> {
+    pub fn update(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
         match message {
             Message::Refresh => {
                 self.refresh_adapters_info();
                 Command::none()
             }
+            Message::ToggleSection(i, section) => {
+                if let Some(adapter_state) = self
+                    .adapters_info
+                    .as_mut()
+                    .ok()
+                    .and_then(|adapters| adapters.get_mut(i))
+                {
+                    adapter_state.expanded.toggle(section);
+                }
+                Command::none()
+            }
+            Message::Dhcp(i, action) => {
+                let adapter_state = match self
+                    .adapters_info
+                    .as_mut()
+                    .ok()
+                    .and_then(|adapters| adapters.get_mut(i))
+                {
+                    Some(adapter_state) => adapter_state,
+                    None => {
+                        error!(
+                            "Cannot run DHCP {:?} for adapter {} as it does not exist",
+                            action, i
+                        );
+                        return Command::none();
+                    }
+                };
+
+                if adapter_state.dhcp_state.is_running() {
+                    return Command::none();
+                }
+                adapter_state.dhcp_state = DhcpActionState::Running(action);
+
+                let adapter_name = adapter_state.adapter_name.clone();
+                Command::perform(
+                    {
+                        let adapter_name = adapter_name.clone();
+                        async move { action.run(adapter_name).await }
+                    },
+                    move |result| Message::DhcpComplete(adapter_name, action, Arc::new(result)),
+                )
+            }
+            Message::DhcpComplete(adapter_name, action, result) => {
+                let result = match result.as_ref() {
+                    Ok(()) => Ok(()),
+                    Err(e) => {
+                        error!("{:?}", e);
+                        Err(format!("{:#}", e))
+                    }
+                };
+
+                // The adapter list may have been refreshed while the action ran, so look it up by name.
+                if let Some(adapter_state) = self.adapters_info.as_mut().ok().and_then(|adapters| {
+                    adapters
+                        .iter_mut()
+                        .find(|adapter| adapter.adapter_name == adapter_name)
+                }) {
+                    adapter_state.dhcp_state = DhcpActionState::Done(action, result);
+                }
+
+                // Show the new lease
+                self.refresh_adapters_info();
+
+                Command::none()
+            }
+            Message::Tick => {
+                let now = SystemTime::now();
+                if let Ok(adapters) = self.adapters_info.as_mut() {
+                    for adapter in adapters.iter_mut() {
+                        adapter.update_lease_text(now);
+                    }
+                }
+                Command::none()
+            }
+            Message::SampleStatistics => {
+                let now = Instant::now();
+                if let Ok(adapters) = self.adapters_info.as_ref() {
+                    for adapter in adapters.iter() {
+                        self.statistics
+                            .entry(adapter.luid)
+                            .or_default()
+                            .sample(adapter.luid, now);
+                    }
+                }
+                Command::none()
+            }
+            Message::ExportFormatSelected(export_format) => {
+                self.export_format = export_format;
+                Command::none()
+            }
+            Message::CopyExport => {
+                match self.export() {
+                    Ok(data) => {
+                        clipboard.write(data);
+                        self.export_status = format!("Copied {} to clipboard", self.export_format);
+                    }
+                    Err(e) => {
+                        error!("{:?}", e);
+                        self.export_status = format!("Failed to export: {:#}", e);
+                    }
+                }
+                Command::none()
+            }
+            Message::SaveExport => {
+                let data = match self.export() {
+                    Ok(data) => data,
+                    Err(e) => {
+                        error!("{:?}", e);
+                        self.export_status = format!("Failed to export: {:#}", e);
+                        return Command::none();
+                    }
+                };
+                let extension = self.export_format.extension();
+                self.export_status = "Saving...".to_string();
+
+                Command::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            save_export("adapters", &data, extension)
+                        })
+                        .await
+                        .context("tokio task panicked")?
+                    },
+                    |result| Message::SaveExportComplete(Arc::new(result)),
+                )
+            }
+            Message::SaveExportComplete(result) => {
+                match result.as_ref() {
+                    Ok(path) => {
+                        info!("Saved export to '{}'", path.display());
+                        self.export_status = format!("Saved to {}", path.display());
+                    }
+                    Err(e) => {
+                        error!("{:?}", e);
+                        self.export_status = format!("Failed to save export: {:#}", e);
+                    }
+                }
+                Command::none()
+            }
+            Message::IpConfig(i, message) => {
+                let adapters = match self.adapters_info.as_mut() {
+                    Ok(adapters) => adapters,
+                    Err(_) => return Command::none(),
+                };
+
+                // New addresses may not overlap the subnets of other interfaces.
+                let other_interfaces: Vec<AddressConfig> = adapters
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .flat_map(|(_, adapter)| adapter.ip_config.current().addresses.iter().copied())
+                    .collect();
+
+                let adapter_state = match adapters.get_mut(i) {
+                    Some(adapter_state) => adapter_state,
+                    None => {
+                        error!("Cannot configure adapter {} as it does not exist", i);
+                        return Command::none();
+                    }
+                };
+
+                if let IpConfigMessage::Apply = message {
+                    let changes = match adapter_state.ip_config.start_apply() {
+                        Some(changes) => changes,
+                        None => return Command::none(),
+                    };
+
+                    // Probe new static addresses first, so one in use by another host is never assigned.
+                    // Addresses the interface already has, like a lease kept as static, are ours already.
+                    let current_addresses = &adapter_state.ip_config.current().addresses;
+                    let checks: Vec<ConflictCheck> = changes
+                        .iter()
+                        .filter_map(|change| match change {
+                            ConfigChange::AddAddress(
+                                address_config @ AddressConfig {
+                                    address: IpAddr::V4(address),
+                                    ..
+                                },
+                            ) if !current_addresses.contains(address_config) => {
+                                Some(adapter_state.conflict_check(*address))
+                            }
+                            _ => None,
+                        })
+                        .collect();
+
+                    let luid = adapter_state.luid;
+                    let adapter_name = adapter_state.adapter_name.clone();
+                    return Command::perform(
+                        check_and_apply_changes(luid, adapter_name.clone(), checks, changes),
+                        move |result| Message::IpConfigComplete(adapter_name, Arc::new(result)),
+                    );
+                }
+
+                adapter_state.ip_config.update(message, &other_interfaces);
+                Command::none()
+            }
+            Message::IpConfigComplete(adapter_name, result) => {
+                if let Err(e) = result.as_ref() {
+                    error!("{:?}", e);
+                }
+
+                if let Some(adapter_state) = self.adapters_info.as_mut().ok().and_then(|adapters| {
+                    adapters
+                        .iter_mut()
+                        .find(|adapter| adapter.adapter_name == adapter_name)
+                }) {
+                    adapter_state.ip_config.finish_apply(result.as_ref());
+                }
+
+                self.refresh_adapters_info();
+
+                Command::none()
+            }
+            Message::Dns(i, message) => {
+                let adapter_state = match self
+                    .adapters_info
+                    .as_mut()
+                    .ok()
+                    .and_then(|adapters| adapters.get_mut(i))
+                {
+                    Some(adapter_state) => adapter_state,
+                    None => {
+                        error!(
+                            "Cannot configure dns for adapter {} as it does not exist",
+                            i
+                        );
+                        return Command::none();
+                    }
+                };
+
+                let revert_to_dhcp = match message {
+                    DnsMessage::Apply => false,
+                    DnsMessage::RevertToDhcp => true,
+                    message => {
+                        adapter_state.dns.update(message);
+                        return Command::none();
+                    }
+                };
+
+                let change = match adapter_state.dns.start_apply(revert_to_dhcp) {
+                    Some(change) => change,
+                    None => return Command::none(),
+                };
+                let adapter_name = adapter_state.adapter_name.clone();
+                Command::perform(
+                    crate::dns_config::apply_dns_change(adapter_name.clone(), change),
+                    move |result| Message::DnsComplete(adapter_name, Arc::new(result)),
+                )
+            }
+            Message::DnsComplete(adapter_name, result) => {
+                if let Err(e) = result.as_ref() {
+                    error!("{:?}", e);
+                }
+
+                if let Some(adapter_state) = self.adapters_info.as_mut().ok().and_then(|adapters| {
+                    adapters
+                        .iter_mut()
+                        .find(|adapter| adapter.adapter_name == adapter_name)
+                }) {
+                    adapter_state.dns.finish_apply(result.as_ref());
+                }
+
+                self.refresh_adapters_info();
+
+                Command::none()
+            }
+            Message::OlderDiff => {
+                self.diff_history.select_older();
+                Command::none()
+            }
+            Message::NewerDiff => {
+                self.diff_history.select_newer();
+                Command::none()
+            }
+            Message::ClearDiffs => {
+                self.diff_history.clear();
+                Command::none()
+            }
+            Message::PingGateway(_) => Command::none(),
+            Message::CheckConflicts(i) => {
+                let adapter_state = match self
+                    .adapters_info
+                    .as_mut()
+                    .ok()
+                    .and_then(|adapters| adapters.get_mut(i))
+                {
+                    Some(adapter_state) => adapter_state,
+                    None => {
+                        error!(
+                            "Cannot check adapter {} for conflicts as it does not exist",
+                            i
+                        );
+                        return Command::none();
+                    }
+                };
+
+                if adapter_state.conflict_state.is_running() {
+                    return Command::none();
+                }
+
+                let checks: Vec<ConflictCheck> = adapter_state
+                    .ipv4_addresses()
+                    .map(|address| adapter_state.conflict_check(address))
+                    .collect();
+                if checks.is_empty() {
+                    return Command::none();
+                }
+
+                adapter_state.conflict_state = ConflictCheckState::Running;
+                let adapter_name = adapter_state.adapter_name.clone();
+                Command::perform(
+                    check_conflicts(Arc::new(NeighborProber), checks),
+                    move |reports| Message::ConflictCheckComplete(adapter_name, Arc::new(reports)),
+                )
+            }
+            Message::ConflictCheckComplete(adapter_name, reports) => {
+                for report in reports.iter() {
+                    if report.is_conflict() {
+                        warn!(
+                            "{} on '{}' is also used by {}",
+                            report.address,
+                            adapter_name,
+                            format_hardware_addresses(&report.conflicts)
+                        );
+                    }
+                }
+
+                if let Some(adapter_state) = self.adapters_info.as_mut().ok().and_then(|adapters| {
+                    adapters
+                        .iter_mut()
+                        .find(|adapter| adapter.adapter_name == adapter_name)
+                }) {
+                    adapter_state.conflict_state = ConflictCheckState::Done(reports);
+                }
+
+                Command::none()
+            }
             Message::Nop => Command::none(),
         }
     }
@@ -78,13 +662,28 @@ impl AdaptersInfo {
         let title = Text::new("Adapter Info").size(36);
         let mut column = Column::new().spacing(10).push(title);
 
+        // Only the selected diff is highlighted, so stepping through history shows what each one changed.
+        let selected_diff = self.diff_history.selected();
+        if let Some(diff_view) = diff_view(
+            &self.diff_history,
+            &mut self.older_diff_button_state,
+            &mut self.newer_diff_button_state,
+            &mut self.clear_diffs_button_state,
+        ) {
+            column = column.push(diff_view);
+        }
+
         match self.adapters_info.as_mut() {
             Ok(adapters) => {
                 for (i, adapter_state) in adapters.iter_mut().enumerate() {
+                    let statistics = self.statistics.get(&adapter_state.luid);
+                    let dns_presets = &self.dns_presets;
+                    let change =
+                        selected_diff.and_then(|diff| diff.get(&adapter_state.adapter_name));
                     column = column.push(
                         Row::new()
                             .push(Space::new(Length::Units(20), Length::Shrink))
-                            .push(adapter_state.view(i)),
+                            .push(adapter_state.view(i, statistics, dns_presets, change)),
                     );
                 }
             }
@@ -104,9 +703,31 @@ impl AdaptersInfo {
                 )
                 .push(
                     Container::new(
-                        Button::new(&mut self.button_state, Text::new("Refresh"))
-                            .style(ForegroundGreenButtonStyle)
-                            .on_press(Message::Refresh),
+                        Row::new()
+                            .spacing(10)
+                            .align_items(Align::Center)
+                            .push(
+                                Button::new(&mut self.button_state, Text::new("Refresh"))
+                                    .style(ForegroundGreenButtonStyle)
+                                    .on_press(Message::Refresh),
+                            )
+                            .push(PickList::new(
+                                &mut self.export_format_state,
+                                ExportFormat::ALL,
+                                Some(self.export_format),
+                                Message::ExportFormatSelected,
+                            ))
+                            .push(
+                                Button::new(&mut self.copy_export_button_state, Text::new("Copy"))
+                                    .style(ForegroundGreenButtonStyle)
+                                    .on_press(Message::CopyExport),
+                            )
+                            .push(
+                                Button::new(&mut self.save_export_button_state, Text::new("Save"))
+                                    .style(ForegroundGreenButtonStyle)
+                                    .on_press(Message::SaveExport),
+                            )
+                            .push(Text::new(&self.export_status).size(15)),
                     )
                     .padding(10)
                     .width(Length::Fill)
@@ -126,23 +747,74 @@ impl Default for AdaptersInfo {
     }
 }
 
-fn ip_address_list_view(ip_address_state_vec: &mut [IpAddress]) -> iced::Element<Message> {
-    let mut column = Column::new();
+/// The changes of the selected diff, with controls to step through the history.
+fn diff_view<'a>(
+    diff_history: &'a DiffHistory,
+    older_diff_button_state: &'a mut iced::button::State,
+    newer_diff_button_state: &'a mut iced::button::State,
+    clear_diffs_button_state: &'a mut iced::button::State,
+) -> Option<Element<'a, Message>> {
+    let diff = diff_history.selected()?;
+    let (position, len) = diff_history.position();
 
-    for state in ip_address_state_vec.iter_mut() {
+    let mut column = Column::new().spacing(5).push(
+        Row::new()
+            .spacing(10)
+            .align_items(Align::Center)
+            .push(Text::new(format!(
+                "Changes {} ({} of {})",
+                format_relative_time(diff.time, SystemTime::now()),
+                position,
+                len
+            )))
+            .push(
+                Button::new(older_diff_button_state, Text::new("Older").size(15))
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::OlderDiff),
+            )
+            .push(
+                Button::new(newer_diff_button_state, Text::new("Newer").size(15))
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::NewerDiff),
+            )
+            .push(
+                Button::new(clear_diffs_button_state, Text::new("Clear").size(15))
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::ClearDiffs),
+            ),
+    );
+    for change in diff.changes.iter() {
         column = column.push(
-            TextInput::new(&mut state.ip_address_state, "", &state.ip_address, |_| {
-                Message::Nop
-            })
-            .style(GreyStyleCopyTextHack)
-            .size(15),
+            Text::new(change.to_string())
+                .size(15)
+                .color(HIGHLIGHT_TEXT_COLOR),
         );
+        for field in change.fields() {
+            column = column.push(
+                Row::new()
+                    .push(Space::new(Length::Units(20), Length::Shrink))
+                    .push(Text::new(field.to_string()).size(15)),
+            );
+        }
+    }
 
-        column = column.push(
-            TextInput::new(&mut state.mask_state, "", &state.mask, |_| Message::Nop)
-                .style(GreyStyleCopyTextHack)
-                .size(15),
-        );
+    Some(
+        Container::new(column)
+            .padding(10)
+            .width(Length::Fill)
+            .style(ForegroundGreyContainerStyle)
+            .into(),
+    )
+}
+
+fn ip_address_list_view(ip_address_state_vec: &mut [IpAddress]) -> iced::Element<Message> {
+    let mut column = Column::new();
+
+    for state in ip_address_state_vec.iter_mut() {
+        column = column.push(state.ip_address.view());
+        for line in state.subnet.iter_mut() {
+            column = column.push(line.view());
+        }
     }
 
     Row::new()
@@ -151,122 +823,783 @@ fn ip_address_list_view(ip_address_state_vec: &mut [IpAddress]) -> iced::Element
         .into()
 }
 
+fn copyable_text_list_view(list: &mut [CopyableText]) -> iced::Element<Message> {
+    let column = list
+        .iter_mut()
+        .fold(Column::new(), |column, text| column.push(text.view()));
+
+    Row::new()
+        .push(Space::new(Length::Units(20), Length::Shrink))
+        .push(column)
+        .into()
+}
+
+/// Which sections of an adapter are expanded
+#[derive(Debug, Clone)]
+struct ExpandedSections {
+    general: bool,
+    addresses: bool,
+    dns: bool,
+    dhcp: bool,
+    link: bool,
+    statistics: bool,
+    ip_config: bool,
+}
+
+impl ExpandedSections {
+    fn get(&self, section: Section) -> bool {
+        match section {
+            Section::General => self.general,
+            Section::Addresses => self.addresses,
+            Section::Dns => self.dns,
+            Section::Dhcp => self.dhcp,
+            Section::Link => self.link,
+            Section::Statistics => self.statistics,
+            Section::IpConfig => self.ip_config,
+        }
+    }
+
+    fn toggle(&mut self, section: Section) {
+        let expanded = match section {
+            Section::General => &mut self.general,
+            Section::Addresses => &mut self.addresses,
+            Section::Dns => &mut self.dns,
+            Section::Dhcp => &mut self.dhcp,
+            Section::Link => &mut self.link,
+            Section::Statistics => &mut self.statistics,
+            Section::IpConfig => &mut self.ip_config,
+        };
+        *expanded = !*expanded;
+    }
+}
+
+impl Default for ExpandedSections {
+    fn default() -> Self {
+        Self {
+            general: true,
+            addresses: true,
+            dns: false,
+            dhcp: false,
+            link: false,
+            statistics: false,
+            ip_config: false,
+        }
+    }
+}
+
+/// A collapsible section header.
+fn section_header<'a>(
+    button_state: &'a mut iced::button::State,
+    title: &str,
+    i: usize,
+    section: Section,
+    expanded: bool,
+) -> Element<'a, Message> {
+    let marker = if expanded { "[-]" } else { "[+]" };
+    Button::new(
+        button_state,
+        Text::new(format!("{} {}", marker, title)).size(15),
+    )
+    .style(ForegroundGreenButtonStyle)
+    .on_press(Message::ToggleSection(i, section))
+    .into()
+}
+
 #[derive(Clone)]
 struct AdapterState {
-    name: String,
-    name_state: iced::text_input::State,
+    adapter_name: String,
+    friendly_name: String,
+    luid: u64,
+    record: AdapterRecord,
+    expanded: ExpandedSections,
 
-    description: String,
-    description_state: iced::text_input::State,
+    general_button_state: iced::button::State,
+    name: CopyableText,
+    description: CopyableText,
+    combo_index: CopyableText,
+    if_index: CopyableText,
+    luid_text: CopyableText,
+    hardware_address: CopyableText,
 
-    combo_index: String,
-    combo_index_state: iced::text_input::State,
+    addresses_button_state: iced::button::State,
+    ip_address_list: Vec<IpAddress>,
+    anycast_address_list: Vec<CopyableText>,
+    multicast_address_list: Vec<CopyableText>,
+    gateway_address_list: Vec<CopyableText>,
+    /// The gateway to ping, with a zone index if it is link-local
+    ping_gateway: Option<String>,
+    ping_gateway_button_state: iced::button::State,
+    interface_index: u32,
+    physical_address: Vec<u8>,
+    conflict_check_button_state: iced::button::State,
+    conflict_state: ConflictCheckState,
 
-    hardware_address: String,
-    hardware_address_state: iced::text_input::State,
+    dns_button_state: iced::button::State,
+    dns_suffix: CopyableText,
+    dns_server_list: Vec<CopyableText>,
+    dns: DnsEditor,
 
-    ip_address_list: Vec<IpAddress>,
-    gateway_address_list: Vec<IpAddress>,
+    dhcp_button_state: iced::button::State,
+    dhcp_enabled: CopyableText,
+    dhcp_server: CopyableText,
+    lease_obtained_time: Option<SystemTime>,
+    lease_obtained: CopyableText,
+    lease_expires_time: Option<SystemTime>,
+    lease_expires: CopyableText,
+    release_button_state: iced::button::State,
+    renew_button_state: iced::button::State,
+    dhcp_state: DhcpActionState,
+
+    link_button_state: iced::button::State,
+    oper_status: CopyableText,
+    if_type: CopyableText,
+    mtu: CopyableText,
+    transmit_link_speed: CopyableText,
+    receive_link_speed: CopyableText,
+
+    statistics_button_state: iced::button::State,
+
+    ip_config_button_state: iced::button::State,
+    ip_config: IpConfigEditor,
 }
 
 impl AdapterState {
-    pub fn new(adapter: &IpAdapterInfo) -> Self {
-        AdapterState {
-            name: format!("Name: {}", adapter.get_name().to_string_lossy()),
-            name_state: iced::text_input::State::new(),
-
-            description: format!(
-                "Description: {}",
-                adapter.get_description().to_string_lossy()
-            ),
-            description_state: iced::text_input::State::new(),
+    pub fn new(adapter: &AdapterAddresses, legacy_info: Option<LegacyAdapterInfo>) -> Self {
+        let combo_index = legacy_info
+            .as_ref()
+            .map(|legacy_info| legacy_info.combo_index.to_string());
+        let dhcp_server = legacy_info
+            .as_ref()
+            .and_then(|legacy_info| legacy_info.dhcp_server)
+            .map_or_else(|| "None".to_string(), |dhcp_server| dhcp_server.to_string());
 
-            combo_index: format!("Combo Index: {}", adapter.get_combo_index()),
-            combo_index_state: iced::text_input::State::new(),
+        let mut ret = AdapterState {
+            adapter_name: adapter.name.clone(),
+            friendly_name: adapter.friendly_name.clone(),
+            luid: adapter.luid,
+            record: AdapterRecord::new(adapter, legacy_info.as_ref()),
+            expanded: ExpandedSections::default(),
 
-            hardware_address: format!(
+            general_button_state: iced::button::State::new(),
+            name: CopyableText::new(format!("Name: {}", adapter.name)),
+            description: CopyableText::new(format!("Description: {}", adapter.description)),
+            combo_index: CopyableText::new(format!(
+                "Combo Index: {}",
+                combo_index.as_deref().unwrap_or("Unknown")
+            )),
+            if_index: CopyableText::new(format!(
+                "Interface Index: {} (IPv6: {})",
+                adapter.if_index, adapter.ipv6_if_index
+            )),
+            luid_text: CopyableText::new(format!("LUID: {:#018X}", adapter.luid)),
+            hardware_address: CopyableText::new(format!(
                 "Hardware Address: {}",
-                format_mac_address_to_string(adapter.get_address())
-            ),
-            hardware_address_state: iced::text_input::State::new(),
+                format_mac_address_to_string(&adapter.physical_address)
+            )),
 
+            addresses_button_state: iced::button::State::new(),
             ip_address_list: adapter
-                .get_ip_address_list()
+                .unicast_addresses
                 .iter()
-                .map(IpAddress::new)
+                .map(|address| IpAddress::new(address.address, address.prefix_length))
                 .collect(),
-            gateway_address_list: adapter
-                .get_gateway_list()
-                .iter()
-                .map(IpAddress::new)
-                .collect(),
-        }
+            anycast_address_list: address_list(&adapter.anycast_addresses),
+            multicast_address_list: address_list(&adapter.multicast_addresses),
+            gateway_address_list: address_list(&adapter.gateway_addresses),
+            ping_gateway: default_gateway(adapter),
+            ping_gateway_button_state: iced::button::State::new(),
+            interface_index: adapter.if_index,
+            physical_address: adapter.physical_address.clone(),
+            conflict_check_button_state: iced::button::State::new(),
+            conflict_state: ConflictCheckState::Idle,
+
+            dns_button_state: iced::button::State::new(),
+            dns_suffix: CopyableText::new(format!("DNS Suffix: {}", adapter.dns_suffix)),
+            dns_server_list: address_list(&adapter.dns_servers),
+            dns: DnsEditor::new(DnsSettings::load(&adapter.name)),
+
+            dhcp_button_state: iced::button::State::new(),
+            dhcp_enabled: CopyableText::new(format!("DHCP Enabled: {}", adapter.dhcp_v4_enabled)),
+            dhcp_server: CopyableText::new(format!("DHCP Server: {}", dhcp_server)),
+            lease_obtained_time: legacy_info
+                .as_ref()
+                .and_then(|legacy_info| legacy_info.lease_obtained),
+            lease_obtained: CopyableText::new(String::new()),
+            lease_expires_time: legacy_info
+                .as_ref()
+                .and_then(|legacy_info| legacy_info.lease_expires),
+            lease_expires: CopyableText::new(String::new()),
+            release_button_state: iced::button::State::new(),
+            renew_button_state: iced::button::State::new(),
+            dhcp_state: DhcpActionState::Idle,
+
+            link_button_state: iced::button::State::new(),
+            oper_status: CopyableText::new(format!("Operational Status: {}", adapter.oper_status)),
+            if_type: CopyableText::new(format!(
+                "Interface Type: {} ({})",
+                adapter.if_type_name(),
+                adapter.if_type
+            )),
+            mtu: CopyableText::new(format!("MTU: {}", adapter.mtu)),
+            transmit_link_speed: CopyableText::new(format!(
+                "Transmit Link Speed: {}",
+                format_link_speed(adapter.transmit_link_speed)
+            )),
+            receive_link_speed: CopyableText::new(format!(
+                "Receive Link Speed: {}",
+                format_link_speed(adapter.receive_link_speed)
+            )),
+
+            statistics_button_state: iced::button::State::new(),
+
+            ip_config_button_state: iced::button::State::new(),
+            ip_config: IpConfigEditor::new(IpConfig::new(adapter)),
+        };
+        ret.update_lease_text(SystemTime::now());
+        ret
+    }
+
+    /// Take the view state of the state this one replaces on refresh.
+    fn keep_view_state(&mut self, old_state: AdapterState) {
+        self.expanded = old_state.expanded;
+        self.dhcp_state = old_state.dhcp_state;
+        self.conflict_state = old_state.conflict_state;
+
+        self.dns.keep_view_state(old_state.dns);
+
+        let current = self.ip_config.current().clone();
+        self.ip_config = old_state.ip_config;
+        self.ip_config.set_current(current);
+    }
+
+    /// Get the ipv4 addresses currently assigned to this adapter.
+    fn ipv4_addresses(&self) -> impl Iterator<Item = Ipv4Addr> + '_ {
+        self.ip_config
+            .current()
+            .addresses
+            .iter()
+            .filter_map(|address| match address.address {
+                IpAddr::V4(address) => Some(address),
+                IpAddr::V6(_) => None,
+            })
+    }
+
+    /// Get a check for conflicts with an address on this adapter.
+    fn conflict_check(&self, address: Ipv4Addr) -> ConflictCheck {
+        ConflictCheck::new(address, self.interface_index, self.physical_address.clone())
     }
 
-    fn view(&mut self, i: usize) -> iced::Element<Message> {
+    /// Update the lease times relative to `now`.
+    fn update_lease_text(&mut self, now: SystemTime) {
+        self.lease_obtained.value = format!(
+            "Lease Obtained: {}",
+            self.lease_obtained_time.map_or_else(
+                || "None".to_string(),
+                |time| format_relative_time(time, now)
+            )
+        );
+        self.lease_expires.value = format!(
+            "Lease Expires: {}",
+            self.lease_expires_time.map_or_else(
+                || "None".to_string(),
+                |time| format_relative_time(time, now)
+            )
+        );
+    }
+
+    fn view<'a>(
+        &'a mut self,
+        i: usize,
+        statistics: Option<&'a InterfaceStatistics>,
+        dns_presets: &'a [DnsPreset],
+        change: Option<&'a AdapterChange>,
+    ) -> iced::Element<'a, Message> {
+        let has_ipv4_address = self.ipv4_addresses().next().is_some();
+        let expanded = &self.expanded;
+
+        let mut general = Column::new().push(section_header(
+            &mut self.general_button_state,
+            "General",
+            i,
+            Section::General,
+            expanded.get(Section::General),
+        ));
+        if expanded.get(Section::General) {
+            general = general
+                .push(self.name.view())
+                .push(self.description.view())
+                .push(self.combo_index.view())
+                .push(self.if_index.view())
+                .push(self.luid_text.view())
+                .push(self.hardware_address.view());
+        }
+
+        let mut addresses = Column::new().push(section_header(
+            &mut self.addresses_button_state,
+            "Addresses",
+            i,
+            Section::Addresses,
+            expanded.get(Section::Addresses),
+        ));
+        if expanded.get(Section::Addresses) {
+            addresses = addresses
+                .push(Text::new("IP Address List").size(15))
+                .push(ip_address_list_view(&mut self.ip_address_list))
+                .push(Text::new("Gateway List").size(15))
+                .push(copyable_text_list_view(&mut self.gateway_address_list));
+            if let Some(gateway) = self.ping_gateway.as_ref() {
+                addresses = addresses.push(
+                    Button::new(
+                        &mut self.ping_gateway_button_state,
+                        Text::new("Ping Gateway").size(15),
+                    )
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::PingGateway(gateway.clone())),
+                );
+            }
+
+            let mut conflict_check_button = Button::new(
+                &mut self.conflict_check_button_state,
+                Text::new("Check for Conflicts").size(15),
+            )
+            .style(ForegroundGreenButtonStyle);
+            if has_ipv4_address && !self.conflict_state.is_running() {
+                conflict_check_button = conflict_check_button.on_press(Message::CheckConflicts(i));
+            }
+            addresses = addresses.push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(conflict_check_button)
+                    .push(Text::new(self.conflict_state.status()).size(15)),
+            );
+            if let ConflictCheckState::Done(reports) = &self.conflict_state {
+                for report in reports.iter() {
+                    let mut text = Text::new(format_conflict_report(report)).size(15);
+                    if report.is_conflict() {
+                        text = text.color(HIGHLIGHT_TEXT_COLOR);
+                    }
+                    addresses = addresses.push(text);
+                }
+            }
+            addresses = addresses
+                .push(Text::new("Anycast Address List").size(15))
+                .push(copyable_text_list_view(&mut self.anycast_address_list))
+                .push(Text::new("Multicast Address List").size(15))
+                .push(copyable_text_list_view(&mut self.multicast_address_list));
+        }
+
+        let mut dns = Column::new().push(section_header(
+            &mut self.dns_button_state,
+            "DNS",
+            i,
+            Section::Dns,
+            expanded.get(Section::Dns),
+        ));
+        if expanded.get(Section::Dns) {
+            dns = dns
+                .push(self.dns_suffix.view())
+                .push(Text::new("DNS Server List").size(15))
+                .push(copyable_text_list_view(&mut self.dns_server_list))
+                .push(
+                    self.dns
+                        .view(dns_presets)
+                        .map(move |message| Message::Dns(i, message)),
+                );
+        }
+
+        let mut dhcp = Column::new().push(section_header(
+            &mut self.dhcp_button_state,
+            "DHCP",
+            i,
+            Section::Dhcp,
+            expanded.get(Section::Dhcp),
+        ));
+        if expanded.get(Section::Dhcp) {
+            let mut release_button = Button::new(
+                &mut self.release_button_state,
+                Text::new("Release").size(15),
+            )
+            .style(ForegroundGreenButtonStyle);
+            let mut renew_button =
+                Button::new(&mut self.renew_button_state, Text::new("Renew").size(15))
+                    .style(ForegroundGreenButtonStyle);
+
+            // Leave the buttons disabled while an action is running.
+            if !self.dhcp_state.is_running() {
+                release_button = release_button.on_press(Message::Dhcp(i, DhcpAction::Release));
+                renew_button = renew_button.on_press(Message::Dhcp(i, DhcpAction::Renew));
+            }
+
+            dhcp = dhcp
+                .push(self.dhcp_enabled.view())
+                .push(self.dhcp_server.view())
+                .push(self.lease_obtained.view())
+                .push(self.lease_expires.view())
+                .push(
+                    Row::new()
+                        .spacing(10)
+                        .align_items(Align::Center)
+                        .push(release_button)
+                        .push(renew_button)
+                        .push(Text::new(self.dhcp_state.status()).size(15)),
+                );
+        }
+
+        let mut link = Column::new().push(section_header(
+            &mut self.link_button_state,
+            "Link",
+            i,
+            Section::Link,
+            expanded.get(Section::Link),
+        ));
+        if expanded.get(Section::Link) {
+            link = link
+                .push(self.oper_status.view())
+                .push(self.if_type.view())
+                .push(self.mtu.view())
+                .push(self.transmit_link_speed.view())
+                .push(self.receive_link_speed.view());
+        }
+
+        let mut statistics_column = Column::new().push(section_header(
+            &mut self.statistics_button_state,
+            "Statistics",
+            i,
+            Section::Statistics,
+            expanded.get(Section::Statistics),
+        ));
+        if expanded.get(Section::Statistics) {
+            statistics_column = statistics_column.push(statistics_view(statistics));
+        }
+
+        let mut ip_config = Column::new().push(section_header(
+            &mut self.ip_config_button_state,
+            "IP Configuration",
+            i,
+            Section::IpConfig,
+            expanded.get(Section::IpConfig),
+        ));
+        if expanded.get(Section::IpConfig) {
+            ip_config = ip_config.push(
+                self.ip_config
+                    .view()
+                    .map(move |message| Message::IpConfig(i, message)),
+            );
+        }
+
         let info_list_view = Row::new()
             .push(Space::new(Length::Units(20), Length::Shrink))
             .push(
                 Column::new()
-                    .push(
-                        TextInput::new(&mut self.name_state, "", &self.name, |_| Message::Nop)
-                            .style(GreyStyleCopyTextHack)
-                            .size(15),
-                    )
-                    .push(
-                        TextInput::new(&mut self.description_state, "", &self.description, |_| {
-                            Message::Nop
-                        })
-                        .style(GreyStyleCopyTextHack)
-                        .size(15),
-                    )
-                    .push(
-                        TextInput::new(&mut self.combo_index_state, "", &self.combo_index, |_| {
-                            Message::Nop
-                        })
-                        .style(GreyStyleCopyTextHack)
-                        .size(15),
-                    )
-                    .push(
-                        TextInput::new(
-                            &mut self.hardware_address_state,
-                            "",
-                            &self.hardware_address,
-                            |_| Message::Nop,
-                        )
-                        .style(GreyStyleCopyTextHack)
-                        .size(15),
-                    )
-                    .push(Text::new("IP Address List").size(15))
-                    .push(ip_address_list_view(&mut self.ip_address_list))
-                    .push(Text::new("Gateway List").size(15))
-                    .push(ip_address_list_view(&mut self.gateway_address_list)),
+                    .spacing(5)
+                    .push(general)
+                    .push(addresses)
+                    .push(dns)
+                    .push(dhcp)
+                    .push(link)
+                    .push(statistics_column)
+                    .push(ip_config),
             );
 
-        Column::new()
-            .push(Text::new(format!("Adapter {}", i)))
-            .push(info_list_view)
+        let mut title = Text::new(format!("Adapter {}: {}", i, self.friendly_name));
+        if change.is_some() {
+            title = title.color(HIGHLIGHT_TEXT_COLOR);
+        }
+        let mut column = Column::new().push(title);
+        for field in change.into_iter().flat_map(AdapterChange::fields) {
+            column = column.push(
+                Text::new(field.to_string())
+                    .size(15)
+                    .color(HIGHLIGHT_TEXT_COLOR),
+            );
+        }
+
+        column.push(info_list_view).into()
+    }
+}
+
+/// The counters and throughput graph of an adapter.
+fn statistics_view(statistics: Option<&InterfaceStatistics>) -> Element<Message> {
+    let statistics = match statistics {
+        Some(statistics) => statistics,
+        None => return Text::new("Waiting for first sample...").size(15).into(),
+    };
+
+    let mut column = Column::new();
+    if let Some(e) = statistics.last_error() {
+        column = column.push(Text::new(format!("Failed to get statistics: {}", e)).size(15));
+    }
+
+    if let Some(stats) = statistics.latest() {
+        let (rx, tx) = statistics
+            .latest_throughput()
+            .map_or((0.0, 0.0), |throughput| (throughput.rx, throughput.tx));
+
+        column = column
+            .push(
+                Text::new(format!(
+                    "Received: {} bytes ({})",
+                    stats.in_octets,
+                    format_bytes_per_sec(rx)
+                ))
+                .size(15),
+            )
+            .push(
+                Text::new(format!(
+                    "Sent: {} bytes ({})",
+                    stats.out_octets,
+                    format_bytes_per_sec(tx)
+                ))
+                .size(15),
+            )
+            .push(
+                Text::new(format!(
+                    "Packets In: {} unicast, {} non-unicast",
+                    stats.in_ucast_pkts, stats.in_nucast_pkts
+                ))
+                .size(15),
+            )
+            .push(
+                Text::new(format!(
+                    "Packets Out: {} unicast, {} non-unicast",
+                    stats.out_ucast_pkts, stats.out_nucast_pkts
+                ))
+                .size(15),
+            )
+            .push(
+                Text::new(format!(
+                    "Errors: {} in, {} out",
+                    stats.in_errors, stats.out_errors
+                ))
+                .size(15),
+            )
+            .push(
+                Text::new(format!(
+                    "Discards: {} in, {} out",
+                    stats.in_discards, stats.out_discards
+                ))
+                .size(15),
+            );
+    }
+
+    column
+        .push(Text::new("Throughput (green: received, orange: sent)").size(15))
+        .push(
+            Canvas::new(statistics.graph())
+                .width(Length::Fill)
+                .height(Length::Units(GRAPH_HEIGHT)),
+        )
+        .into()
+}
+
+/// Text that can be selected and copied, but not edited.
+#[derive(Clone)]
+struct CopyableText {
+    value: String,
+    state: iced::text_input::State,
+}
+
+impl CopyableText {
+    fn new(value: String) -> Self {
+        Self {
+            value,
+            state: iced::text_input::State::new(),
+        }
+    }
+
+    fn view(&mut self) -> Element<Message> {
+        TextInput::new(&mut self.state, "", &self.value, |_| Message::Nop)
+            .style(GreyStyleCopyTextHack)
+            .size(15)
             .into()
     }
 }
 
+fn address_list(addresses: &[IpAddr]) -> Vec<CopyableText> {
+    addresses
+        .iter()
+        .map(|address| CopyableText::new(format!("IP Address: {}", address)))
+        .collect()
+}
+
+/// Get the gateway to ping for an adapter, preferring ipv4.
+///
+/// Link-local ipv6 gateways get the zone index of the adapter, like "fe80::1%12".
+fn default_gateway(adapter: &AdapterAddresses) -> Option<String> {
+    let gateway = adapter
+        .gateway_addresses
+        .iter()
+        .find(|address| address.is_ipv4())
+        .or_else(|| adapter.gateway_addresses.first())?;
+
+    match gateway {
+        IpAddr::V6(address) if address.segments()[0] & 0xffc0 == 0xfe80 => {
+            Some(format!("{}%{}", address, adapter.ipv6_if_index))
+        }
+        address => Some(address.to_string()),
+    }
+}
+
+/// The state of an adapter's conflict check
+#[derive(Debug, Clone, Default)]
+enum ConflictCheckState {
+    #[default]
+    Idle,
+    Running,
+    Done(Arc<Vec<ConflictReport>>),
+}
+
+impl ConflictCheckState {
+    fn is_running(&self) -> bool {
+        matches!(self, Self::Running)
+    }
+
+    fn status(&self) -> String {
+        match self {
+            Self::Idle => String::new(),
+            Self::Running => "Probing...".to_string(),
+            Self::Done(reports) => {
+                let num_conflicts = reports.iter().filter(|report| report.is_conflict()).count();
+                match num_conflicts {
+                    0 => "No conflicts".to_string(),
+                    1 => "1 conflict".to_string(),
+                    n => format!("{} conflicts", n),
+                }
+            }
+        }
+    }
+}
+
+/// Format hardware addresses with their vendors, like "AA-BB-CC-DD-EE-FF (Vendor)".
+fn format_hardware_addresses(addresses: &[Vec<u8>]) -> String {
+    addresses
+        .iter()
+        .map(|address| {
+            let formatted = format_mac_address_to_string(address).trim_end().to_string();
+            match crate::oui::vendor_name(address) {
+                Some(vendor) => format!("{} ({})", formatted, vendor),
+                None => formatted,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn format_conflict_report(report: &ConflictReport) -> String {
+    if report.is_conflict() {
+        format!(
+            "{}: in use by {}",
+            report.address,
+            format_hardware_addresses(&report.conflicts)
+        )
+    } else if let Some(error) = report.errors.first() {
+        format!("{}: {}", report.address, error)
+    } else {
+        format!("{}: no other host answered", report.address)
+    }
+}
+
+/// Probe new addresses for conflicts, then apply the changes if none are found.
+///
+/// Nothing is applied if any address could not be probed.
+async fn check_and_apply_changes(
+    luid: u64,
+    adapter_name: String,
+    checks: Vec<ConflictCheck>,
+    changes: Vec<ConfigChange>,
+) -> anyhow::Result<()> {
+    let reports = check_conflicts(Arc::new(NeighborProber), checks).await;
+    for report in reports.iter() {
+        for error in report.errors.iter() {
+            warn!("Failed to probe {}: {}", report.address, error);
+        }
+    }
+
+    let conflicts: Vec<String> = reports
+        .iter()
+        .filter(|report| report.is_conflict())
+        .map(format_conflict_report)
+        .collect();
+    if !conflicts.is_empty() {
+        anyhow::bail!("address conflict, {}", conflicts.join("; "));
+    }
+
+    // An address that could not be probed at all might be in use, so it is not assigned.
+    let unchecked: Vec<String> = reports
+        .iter()
+        .filter(|report| report.is_unchecked())
+        .map(format_conflict_report)
+        .collect();
+    if !unchecked.is_empty() {
+        anyhow::bail!(
+            "could not check for address conflicts, {}",
+            unchecked.join("; ")
+        );
+    }
+
+    crate::ip_config::apply_changes(luid, adapter_name, changes).await
+}
+
+/// Format a link speed in bits per second.
+fn format_link_speed(speed: u64) -> String {
+    const UNITS: &[(u64, &str)] = &[
+        (1_000_000_000_000, "Tbps"),
+        (1_000_000_000, "Gbps"),
+        (1_000_000, "Mbps"),
+        (1_000, "Kbps"),
+    ];
+
+    // An unknown speed is reported as the max value.
+    if speed == u64::MAX {
+        return "Unknown".to_string();
+    }
+
+    for (scale, unit) in UNITS.iter() {
+        if speed >= *scale {
+            return format!("{:.1} {}", speed as f64 / *scale as f64, unit);
+        }
+    }
+
+    format!("{} bps", speed)
+}
+
 #[derive(Clone)]
 pub struct IpAddress {
-    ip_address: String,
-    ip_address_state: iced::text_input::State,
+    ip_address: CopyableText,
 
-    mask: String,
-    mask_state: iced::text_input::State,
+    /// The mask and calculated subnet of the address
+    subnet: Vec<CopyableText>,
 }
 
 impl IpAddress {
-    pub fn new(ip_address: &IpAddrString) -> Self {
-        IpAddress {
-            ip_address: format!("IP Address: {}", ip_address.get_address().to_string_lossy()),
-            ip_address_state: iced::text_input::State::new(),
+    pub fn new(ip_address: IpAddr, prefix_length: u8) -> Self {
+        let mut subnet = Vec::new();
+        match (ip_address, mask_from_prefix_length(prefix_length)) {
+            (IpAddr::V4(_), Some(mask)) => {
+                subnet.push(format!("Mask: {} (/{})", mask, prefix_length));
+            }
+            _ => subnet.push(format!("Prefix Length: {}", prefix_length)),
+        }
+        if let Some(info) = SubnetInfo::new(ip_address, prefix_length) {
+            subnet.push(format!("Network: {}/{}", info.network, prefix_length));
+            if let Some(broadcast) = info.broadcast {
+                subnet.push(format!("Broadcast: {}", broadcast));
+            }
+            subnet.push(format!(
+                "Hosts: {} - {} ({})",
+                info.first_host,
+                info.last_host,
+                info.format_host_count()
+            ));
+            if ip_address.is_ipv4() {
+                subnet.push(format!("Wildcard: {}", info.wildcard));
+            }
+        }
 
-            mask: format!("Mask: {}", ip_address.get_mask().to_string_lossy()),
-            mask_state: iced::text_input::State::new(),
+        IpAddress {
+            ip_address: CopyableText::new(format!("IP Address: {}", ip_address)),
+            subnet: subnet.into_iter().map(CopyableText::new).collect(),
         }
     }
 }
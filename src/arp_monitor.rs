@@ -0,0 +1,314 @@
+use crate::{
+    dhcp::format_relative_time,
+    neighbor_table::get_neighbors,
+    spoof_detector::{
+        detect,
+        parse_known_neighbors,
+        Alert,
+        Detector,
+        KnownNeighbor,
+        Observation,
+    },
+    style::{
+        ForegroundGreenButtonStyle,
+        HIGHLIGHT_TEXT_COLOR,
+    },
+    GreyStyle,
+};
+use iced::{
+    futures::{
+        stream::BoxStream,
+        StreamExt,
+    },
+    Align,
+    Button,
+    Checkbox,
+    Clipboard,
+    Column,
+    Command,
+    Container,
+    Element,
+    Length,
+    Row,
+    Scrollable,
+    Space,
+    Subscription,
+    Text,
+};
+use log::warn;
+use std::{
+    collections::VecDeque,
+    hash::Hash,
+    net::IpAddr,
+    sync::Arc,
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+/// How often the neighbor cache is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The most alerts that are kept
+const MAX_ALERTS: usize = 100;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    EnabledToggled(bool),
+    Alert(Alert),
+    ClearAlerts,
+}
+
+/// What the monitor checks against
+///
+/// Changing this restarts the monitor, which forgets what it has seen so far.
+#[derive(Debug, Hash)]
+struct MonitorConfig {
+    known: Vec<(IpAddr, Vec<u8>)>,
+    gateways: Vec<IpAddr>,
+}
+
+pub struct ArpMonitor {
+    enabled: bool,
+    known_neighbors: Vec<KnownNeighbor>,
+    known_neighbor_errors: Vec<String>,
+    gateways: Vec<IpAddr>,
+    config: Arc<MonitorConfig>,
+
+    alerts: VecDeque<(SystemTime, Alert)>,
+
+    scroll_state: iced::scrollable::State,
+    clear_button_state: iced::button::State,
+}
+
+impl ArpMonitor {
+    pub fn new() -> Self {
+        let mut ret = Self {
+            enabled: true,
+            known_neighbors: Vec::new(),
+            known_neighbor_errors: Vec::new(),
+            gateways: Vec::new(),
+            config: Arc::new(MonitorConfig {
+                known: Vec::new(),
+                gateways: Vec::new(),
+            }),
+
+            alerts: VecDeque::new(),
+
+            scroll_state: iced::scrollable::State::new(),
+            clear_button_state: iced::button::State::new(),
+        };
+        ret.refresh_gateways();
+        ret
+    }
+
+    /// Set the known-good mappings.
+    pub fn set_known_neighbors(&mut self, known_neighbors: &[KnownNeighbor]) {
+        if self.known_neighbors == known_neighbors {
+            return;
+        }
+        self.known_neighbors = known_neighbors.to_vec();
+        self.update_config();
+    }
+
+    /// Reload the default gateways of all adapters.
+    pub fn refresh_gateways(&mut self) {
+        match crate::adapter_addresses::get_adapter_addresses() {
+            Ok(adapters) => {
+                self.gateways = adapters
+                    .iter()
+                    .flat_map(|adapter| adapter.gateway_addresses.iter().copied())
+                    .collect();
+                self.gateways.sort();
+                self.gateways.dedup();
+            }
+            Err(e) => {
+                warn!("Failed to get adapter addresses: {}", e);
+            }
+        }
+        self.update_config();
+    }
+
+    fn update_config(&mut self) {
+        let (known, errors) = parse_known_neighbors(&self.known_neighbors);
+        for error in errors.iter() {
+            warn!("Invalid known neighbor: {}", error);
+        }
+        self.known_neighbor_errors = errors;
+
+        let mut known: Vec<_> = known.into_iter().collect();
+        known.sort();
+        self.config = Arc::new(MonitorConfig {
+            known,
+            gateways: self.gateways.clone(),
+        });
+    }
+
+    /// Get the number of alerts raised.
+    pub fn num_alerts(&self) -> usize {
+        self.alerts.len()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        if !self.enabled {
+            return Subscription::none();
+        }
+
+        Subscription::from_recipe(Monitor {
+            config: self.config.clone(),
+        })
+        .map(Message::Alert)
+    }
+
+    pub fn update(&mut self, message: Message, _clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::EnabledToggled(enabled) => {
+                self.enabled = enabled;
+            }
+            Message::Alert(alert) => {
+                warn!("Possible spoofing: {}", alert);
+                self.alerts.push_front((SystemTime::now(), alert));
+                self.alerts.truncate(MAX_ALERTS);
+            }
+            Message::ClearAlerts => {
+                self.alerts.clear();
+            }
+        }
+
+        Command::none()
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        let mut column = Column::new()
+            .spacing(10)
+            .push(Text::new("ARP Monitor").size(36))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Checkbox::new(
+                        self.enabled,
+                        "Watch the neighbor cache",
+                        Message::EnabledToggled,
+                    ))
+                    .push(
+                        Button::new(&mut self.clear_button_state, Text::new("Clear").size(15))
+                            .style(ForegroundGreenButtonStyle)
+                            .on_press(Message::ClearAlerts),
+                    ),
+            );
+
+        let gateways = if self.gateways.is_empty() {
+            "None".to_string()
+        } else {
+            self.gateways
+                .iter()
+                .map(|gateway| gateway.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        column = column
+            .push(Text::new(format!("Gateways: {}", gateways)).size(15))
+            .push(
+                Text::new(format!(
+                    "Known Neighbors: {} (edit in Settings)",
+                    self.config.known.len()
+                ))
+                .size(15),
+            );
+        column = self
+            .known_neighbor_errors
+            .iter()
+            .fold(column, |column, error| {
+                column.push(Text::new(format!("Error: {}", error)).size(15))
+            });
+
+        column = column.push(Text::new("Alerts").size(24));
+        if self.alerts.is_empty() {
+            column = column.push(Text::new("No alerts").size(15));
+        }
+        let now = SystemTime::now();
+        for (time, alert) in self.alerts.iter() {
+            column = column.push(
+                Row::new()
+                    .spacing(10)
+                    .push(Space::new(Length::Units(20), Length::Shrink))
+                    .push(
+                        Text::new(format_relative_time(*time, now))
+                            .size(15)
+                            .width(Length::Units(120)),
+                    )
+                    .push(
+                        Text::new(alert.to_string())
+                            .size(15)
+                            .color(HIGHLIGHT_TEXT_COLOR),
+                    ),
+            );
+        }
+
+        Container::new(
+            Scrollable::new(&mut self.scroll_state)
+                .push(Container::new(column).padding(20))
+                .width(Length::Fill),
+        )
+        .style(GreyStyle)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+/// Polls the neighbor cache and runs it through a detector
+struct Monitor {
+    config: Arc<MonitorConfig>,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for Monitor
+where
+    H: std::hash::Hasher,
+{
+    type Output = Alert;
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+        self.config.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let detector = Detector::new(
+            self.config.known.iter().cloned().collect(),
+            self.config.gateways.iter().copied().collect(),
+        );
+
+        let observations = iced::futures::stream::unfold(true, |is_first| async move {
+            if !is_first {
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+
+            let observations = match tokio::task::spawn_blocking(get_neighbors).await {
+                Ok(Ok(neighbors)) => neighbors
+                    .into_iter()
+                    .map(|neighbor| Observation {
+                        interface_index: neighbor.interface_index,
+                        address: neighbor.address,
+                        hardware_address: neighbor.physical_address,
+                    })
+                    .collect(),
+                Ok(Err(e)) => {
+                    warn!("Failed to get neighbors: {}", e);
+                    Vec::new()
+                }
+                Err(e) => {
+                    warn!("tokio task failed to join: {}", e);
+                    Vec::new()
+                }
+            };
+
+            Some((iced::futures::stream::iter(observations), false))
+        })
+        .flatten();
+
+        detect(detector, observations).boxed()
+    }
+}
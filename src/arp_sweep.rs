@@ -0,0 +1,436 @@
+use crate::subnet::SubnetInfo;
+use anyhow::Context;
+use iced::futures::{
+    stream::BoxStream,
+    Stream,
+    StreamExt,
+};
+use std::{
+    convert::TryInto,
+    hash::Hash,
+    net::{
+        IpAddr,
+        Ipv4Addr,
+    },
+    sync::Arc,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use tokio::sync::Semaphore;
+
+/// The most hosts a single sweep may cover, a /16
+pub const MAX_TARGETS: usize = 1 << 16;
+
+/// Resolves the hardware address of an ipv4 address
+pub trait ArpResolver: Send + Sync + 'static {
+    /// Resolve `target`, blocking the current thread.
+    ///
+    /// If `source` is `None`, the source address is picked from the route to the target.
+    fn resolve(&self, target: Ipv4Addr, source: Option<Ipv4Addr>) -> anyhow::Result<Vec<u8>>;
+}
+
+/// Resolves addresses with `SendARP`
+pub struct SendArpResolver;
+
+impl ArpResolver for SendArpResolver {
+    fn resolve(&self, target: Ipv4Addr, source: Option<Ipv4Addr>) -> anyhow::Result<Vec<u8>> {
+        let (mac, mac_len) = iphlpapi::send_arp(target, source).context("failed to resolve arp")?;
+        let mac_len: usize = mac_len
+            .try_into()
+            .context("mac_len cannot fit in a usize")?;
+
+        Ok(mac.to_ne_bytes()[..mac_len.min(8)].to_vec())
+    }
+}
+
+/// Parse the hosts to sweep.
+///
+/// Accepts a CIDR like "192.168.1.0/24", a range like "192.168.1.10-192.168.1.50" or "192.168.1.10-50", or a single address.
+/// The network and broadcast addresses of a CIDR are skipped.
+pub fn parse_targets(input: &str) -> anyhow::Result<Vec<Ipv4Addr>> {
+    let input = input.trim();
+
+    let (start, end) = if input.contains('/') {
+        let info = SubnetInfo::parse(input)?;
+        match (info.first_host, info.last_host) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => (start, end),
+            _ => anyhow::bail!("only ipv4 can be swept"),
+        }
+    } else if let Some((start, end)) = input.split_once('-') {
+        let start: Ipv4Addr = start.trim().parse().context("invalid start address")?;
+        let end = end.trim();
+
+        // Allow abbreviating the end to its last octet.
+        let end = match end.parse::<u8>() {
+            Ok(last_octet) => {
+                let [a, b, c, _] = start.octets();
+                Ipv4Addr::new(a, b, c, last_octet)
+            }
+            Err(_) => end.parse().context("invalid end address")?,
+        };
+
+        if end < start {
+            anyhow::bail!("the end of the range is before the start");
+        }
+        (start, end)
+    } else {
+        let address: Ipv4Addr = input.parse().context("invalid address")?;
+        (address, address)
+    };
+
+    let start = u32::from(start);
+    let end = u32::from(end);
+    let len = u64::from(end - start) + 1;
+    if len > MAX_TARGETS as u64 {
+        anyhow::bail!("cannot sweep more than {} hosts at once", MAX_TARGETS);
+    }
+
+    Ok((start..=end).map(Ipv4Addr::from).collect())
+}
+
+/// The settings of a sweep
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepConfig {
+    pub targets: Vec<Ipv4Addr>,
+    pub source: Option<Ipv4Addr>,
+
+    /// The most hosts that are resolved at once
+    pub concurrency: usize,
+    /// How long to wait for each host
+    pub timeout: Duration,
+}
+
+/// The outcome of resolving a single host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepResult {
+    pub address: Ipv4Addr,
+
+    /// The hardware address, or why it could not be resolved
+    pub result: Result<Vec<u8>, String>,
+    pub rtt: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SweepEvent {
+    Result(SweepResult),
+    Finished,
+}
+
+/// Resolve every target, yielding results as they arrive and then [`SweepEvent::Finished`].
+///
+/// Dropping the stream cancels the sweep.
+/// Resolutions that are already running still finish in the background, but their results are discarded.
+///
+/// A resolution that times out keeps its slot until it returns, so no more than `concurrency` ever run at once.
+pub fn sweep<R: ArpResolver>(
+    resolver: Arc<R>,
+    config: SweepConfig,
+) -> impl Stream<Item = SweepEvent> {
+    let SweepConfig {
+        targets,
+        source,
+        concurrency,
+        timeout,
+    } = config;
+
+    let concurrency = concurrency.max(1);
+    let slots = Arc::new(Semaphore::new(concurrency));
+
+    iced::futures::stream::iter(targets)
+        .map(move |target| resolve_one(resolver.clone(), slots.clone(), target, source, timeout))
+        .buffer_unordered(concurrency)
+        .map(SweepEvent::Result)
+        .chain(iced::futures::stream::once(async { SweepEvent::Finished }))
+}
+
+async fn resolve_one<R: ArpResolver>(
+    resolver: Arc<R>,
+    slots: Arc<Semaphore>,
+    target: Ipv4Addr,
+    source: Option<Ipv4Addr>,
+    timeout: Duration,
+) -> SweepResult {
+    // The blocking call cannot be cancelled, so the slot is held until it returns rather than until it times out.
+    let slot = slots
+        .acquire_owned()
+        .await
+        .expect("the sweep semaphore is never closed");
+
+    let start = Instant::now();
+    let task = tokio::task::spawn_blocking(move || {
+        let result = resolver.resolve(target, source);
+        drop(slot);
+        result
+    });
+    let result = match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(Ok(mac))) => Ok(mac),
+        Ok(Ok(Err(e))) => Err(format!("{:#}", e)),
+        Ok(Err(e)) => Err(format!("tokio task failed to join: {}", e)),
+        Err(_elapsed) => Err("timed out".to_string()),
+    };
+
+    SweepResult {
+        address: target,
+        result,
+        rtt: start.elapsed(),
+    }
+}
+
+/// Get a subscription that runs a sweep with `SendARP`, tagging events with `id`.
+///
+/// Changing `id` starts a new sweep, and dropping the subscription cancels it.
+pub fn sweep_subscription(
+    id: u64,
+    config: Arc<SweepConfig>,
+) -> iced::Subscription<(u64, SweepEvent)> {
+    iced::Subscription::from_recipe(Sweep { id, config })
+}
+
+struct Sweep {
+    id: u64,
+    config: Arc<SweepConfig>,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for Sweep
+where
+    H: std::hash::Hasher,
+{
+    type Output = (u64, SweepEvent);
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+        self.id.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let id = self.id;
+        sweep(Arc::new(SendArpResolver), (*self.config).clone())
+            .map(move |event| (id, event))
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        sync::atomic::{
+            AtomicUsize,
+            Ordering,
+        },
+    };
+
+    /// A resolver that answers from a fixed table, taking `delay` per host
+    struct FakeResolver {
+        hosts: HashMap<Ipv4Addr, Vec<u8>>,
+        delay: Duration,
+
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+        resolved: AtomicUsize,
+    }
+
+    impl FakeResolver {
+        fn new(hosts: &[(Ipv4Addr, [u8; 6])], delay: Duration) -> Self {
+            Self {
+                hosts: hosts
+                    .iter()
+                    .map(|(address, mac)| (*address, mac.to_vec()))
+                    .collect(),
+                delay,
+
+                running: AtomicUsize::new(0),
+                max_running: AtomicUsize::new(0),
+                resolved: AtomicUsize::new(0),
+            }
+        }
+    }
+
+    impl ArpResolver for FakeResolver {
+        fn resolve(&self, target: Ipv4Addr, _source: Option<Ipv4Addr>) -> anyhow::Result<Vec<u8>> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            std::thread::sleep(self.delay);
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            self.resolved.fetch_add(1, Ordering::SeqCst);
+
+            self.hosts
+                .get(&target)
+                .cloned()
+                .context("host did not respond")
+        }
+    }
+
+    fn config(targets: &str, concurrency: usize, timeout: Duration) -> SweepConfig {
+        SweepConfig {
+            targets: parse_targets(targets).unwrap(),
+            source: None,
+            concurrency,
+            timeout,
+        }
+    }
+
+    #[test]
+    fn parse_cidr() {
+        let targets = parse_targets("192.168.1.0/24").unwrap();
+        assert_eq!(targets.len(), 254);
+        assert_eq!(targets[0], Ipv4Addr::new(192, 168, 1, 1));
+        assert_eq!(targets[253], Ipv4Addr::new(192, 168, 1, 254));
+
+        let targets = parse_targets("10.0.0.7/30").unwrap();
+        assert_eq!(
+            targets,
+            vec![Ipv4Addr::new(10, 0, 0, 5), Ipv4Addr::new(10, 0, 0, 6)]
+        );
+
+        assert_eq!(
+            parse_targets("10.0.0.7/32").unwrap(),
+            vec![Ipv4Addr::new(10, 0, 0, 7)]
+        );
+        assert_eq!(parse_targets("10.0.0.0/16").unwrap().len(), 65534);
+    }
+
+    #[test]
+    fn parse_range() {
+        let targets = parse_targets("192.168.1.10-192.168.1.12").unwrap();
+        assert_eq!(
+            targets,
+            vec![
+                Ipv4Addr::new(192, 168, 1, 10),
+                Ipv4Addr::new(192, 168, 1, 11),
+                Ipv4Addr::new(192, 168, 1, 12),
+            ]
+        );
+        assert_eq!(parse_targets("192.168.1.10 - 12").unwrap(), targets);
+
+        // Ranges may cross octets.
+        assert_eq!(
+            parse_targets("10.0.0.255-10.0.1.0").unwrap(),
+            vec![Ipv4Addr::new(10, 0, 0, 255), Ipv4Addr::new(10, 0, 1, 0)]
+        );
+    }
+
+    #[test]
+    fn parse_single() {
+        assert_eq!(
+            parse_targets(" 192.168.1.1 ").unwrap(),
+            vec![Ipv4Addr::new(192, 168, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse_targets("").is_err());
+        assert!(parse_targets("192.168.1").is_err());
+        assert!(parse_targets("192.168.1.20-10").is_err());
+        assert!(parse_targets("192.168.1.1-256").is_err());
+        assert!(parse_targets("10.0.0.0/8").is_err());
+        assert!(parse_targets("fe80::/64").is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sweep_reports_every_host() {
+        let mac = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55];
+        let resolver = Arc::new(FakeResolver::new(
+            &[
+                (Ipv4Addr::new(10, 0, 0, 2), mac),
+                (Ipv4Addr::new(10, 0, 0, 9), mac),
+            ],
+            Duration::from_millis(1),
+        ));
+
+        let events: Vec<SweepEvent> =
+            sweep(resolver, config("10.0.0.1-10", 4, Duration::from_secs(5)))
+                .collect()
+                .await;
+
+        assert_eq!(events.len(), 11);
+        assert_eq!(events.last(), Some(&SweepEvent::Finished));
+
+        let mut found: Vec<Ipv4Addr> = events
+            .iter()
+            .filter_map(|event| match event {
+                SweepEvent::Result(result) => result.result.as_ref().ok().map(|_| result.address),
+                SweepEvent::Finished => None,
+            })
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![Ipv4Addr::new(10, 0, 0, 2), Ipv4Addr::new(10, 0, 0, 9)]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sweep_limits_concurrency() {
+        let resolver = Arc::new(FakeResolver::new(&[], Duration::from_millis(20)));
+
+        let events: Vec<SweepEvent> = sweep(
+            resolver.clone(),
+            config("10.0.0.1-20", 3, Duration::from_secs(5)),
+        )
+        .collect()
+        .await;
+
+        assert_eq!(events.len(), 21);
+        assert!(resolver.max_running.load(Ordering::SeqCst) <= 3);
+        assert_eq!(resolver.resolved.load(Ordering::SeqCst), 20);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn sweep_times_out_slow_hosts() {
+        let resolver = Arc::new(FakeResolver::new(
+            &[(Ipv4Addr::new(10, 0, 0, 1), [0; 6])],
+            Duration::from_millis(500),
+        ));
+
+        let events: Vec<SweepEvent> =
+            sweep(resolver, config("10.0.0.1", 1, Duration::from_millis(10)))
+                .collect()
+                .await;
+
+        match &events[0] {
+            SweepEvent::Result(result) => {
+                assert_eq!(result.result, Err("timed out".to_string()));
+                assert!(result.rtt < Duration::from_millis(500));
+            }
+            SweepEvent::Finished => panic!("expected a result"),
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn timed_out_hosts_keep_their_slot() {
+        let resolver = Arc::new(FakeResolver::new(&[], Duration::from_millis(50)));
+
+        let events: Vec<SweepEvent> = sweep(
+            resolver.clone(),
+            config("10.0.0.1-10", 2, Duration::from_millis(5)),
+        )
+        .collect()
+        .await;
+
+        assert_eq!(events.len(), 11);
+        assert!(resolver.max_running.load(Ordering::SeqCst) <= 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn dropping_sweep_cancels_it() {
+        let resolver = Arc::new(FakeResolver::new(&[], Duration::from_millis(10)));
+
+        let events: Vec<SweepEvent> = sweep(
+            resolver.clone(),
+            config("10.0.0.1-200", 2, Duration::from_secs(5)),
+        )
+        .take(4)
+        .collect()
+        .await;
+        assert_eq!(events.len(), 4);
+
+        // Let any in flight resolutions finish.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(resolver.resolved.load(Ordering::SeqCst) <= 6);
+    }
+}
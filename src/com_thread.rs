@@ -5,7 +5,10 @@ use log::{
     warn,
 };
 use netcon::{
+    adapter_name_to_uuid,
+    guid_to_uuid,
     NetConProperties,
+    NetConPropertiesSnapshot,
     NetConnection,
     NetConnectionManager,
 };
@@ -17,7 +20,6 @@ use std::{
     time::Instant,
 };
 use uuid::Uuid;
-use winapi::shared::guiddef::GUID;
 
 const MAX_BUFFERED_COMMANDS: usize = 32;
 
@@ -29,7 +31,10 @@ enum ComCommand {
     ResetNetworkConnection {
         adapter_name: Uuid,
         adapter_description: OsString,
-        responder: ComThreadResultSender<anyhow::Result<()>>,
+        responder: ComThreadResultSender<anyhow::Result<NetConPropertiesSnapshot>>,
+    },
+    GetNetworkConnections {
+        responder: ComThreadResultSender<anyhow::Result<Vec<NetConPropertiesSnapshot>>>,
     },
 }
 
@@ -82,19 +87,18 @@ impl ComThread {
     }
 
     /// Reset the network adapter.
+    /// Returns the properties of the connection after it was restarted.
     /// Returns an error if the adpater could not be located or restarted.
     pub async fn reset_network_connection(
         &self,
         adapter_name: &str,
         adapter_description: OsString,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<NetConPropertiesSnapshot> {
         let start = Instant::now();
         let (responder, rx) = tokio::sync::oneshot::channel();
 
-        // Adapter names have the form {<guid>}.
         let adapter_name =
-            Uuid::parse_str(adapter_name.trim_start_matches('{').trim_end_matches('}'))
-                .context("adapter name is not a guid")?;
+            adapter_name_to_uuid(adapter_name).context("adapter name is not a guid")?;
 
         self.command_tx
             .send(ComCommand::ResetNetworkConnection {
@@ -114,6 +118,21 @@ impl ComThread {
 
         result
     }
+
+    /// Get snapshots of the properties of all network connections.
+    pub async fn get_network_connections(&self) -> anyhow::Result<Vec<NetConPropertiesSnapshot>> {
+        let start = Instant::now();
+        let (responder, rx) = tokio::sync::oneshot::channel();
+
+        self.command_tx
+            .send(ComCommand::GetNetworkConnections { responder })
+            .await
+            .context("failed to send request")?;
+        let result = rx.await.context("failed to receive result")?;
+        info!("Got network connections in {:?}", start.elapsed());
+
+        result
+    }
 }
 
 fn process_command(connection_manager: &NetConnectionManager, command: ComCommand) {
@@ -127,6 +146,10 @@ fn process_command(connection_manager: &NetConnectionManager, command: ComComman
                 reset_network_connection(connection_manager, adapter_name, &adapter_description);
             let _ = responder.send(result).is_ok();
         }
+        ComCommand::GetNetworkConnections { responder } => {
+            let result = get_network_connections(connection_manager);
+            let _ = responder.send(result).is_ok();
+        }
     }
 }
 
@@ -134,7 +157,7 @@ fn reset_network_connection(
     connection_manager: &NetConnectionManager,
     adapter_name: Uuid,
     adapter_description: &OsStr,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<NetConPropertiesSnapshot> {
     let (connection, _properties) =
         find_network_connection(connection_manager, adapter_name, adapter_description)
             .context("failed to get network connection")?
@@ -143,7 +166,30 @@ fn reset_network_connection(
     connection.disconnect()?;
     connection.connect()?;
 
-    Ok(())
+    // Re-fetch the properties, as the status has changed.
+    let properties = connection
+        .get_properties()
+        .context("failed to get network connection properties")?;
+
+    Ok(properties.snapshot())
+}
+
+fn get_network_connections(
+    connection_manager: &NetConnectionManager,
+) -> anyhow::Result<Vec<NetConPropertiesSnapshot>> {
+    let mut snapshots = Vec::with_capacity(32);
+    for connection_result in connection_manager
+        .iter()
+        .context("failed to iterate network connections")?
+    {
+        let connection = connection_result.context("failed to get network connection")?;
+        let properties = connection
+            .get_properties()
+            .context("failed to get network connection properties")?;
+        snapshots.push(properties.snapshot());
+    }
+
+    Ok(snapshots)
 }
 
 fn find_network_connection(
@@ -163,7 +209,7 @@ fn find_network_connection(
         debug!(
             "Located '{}' | '{}' | '{}'",
             properties.name().to_string_lossy(),
-            guid_to_uuid(*properties.guid()),
+            guid_to_uuid(properties.guid()),
             properties.device_name().to_string_lossy(),
         );
 
@@ -173,7 +219,7 @@ fn find_network_connection(
     // 1st pass guid compare
     for i in 0..connections.len() {
         let (_connection, properties) = &connections[i];
-        if guid_to_uuid(*properties.guid()) == adapter_name {
+        if guid_to_uuid(properties.guid()) == adapter_name {
             return Ok(Some(connections.swap_remove(i)));
         }
     }
@@ -193,9 +239,3 @@ fn find_network_connection(
 
     Ok(None)
 }
-
-pub fn guid_to_uuid(guid: GUID) -> Uuid {
-    // This should be infallible
-    Uuid::from_fields(guid.Data1, guid.Data2, guid.Data3, &guid.Data4)
-        .expect("a guid was not a valid uuid")
-}
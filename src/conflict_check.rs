@@ -0,0 +1,279 @@
+use anyhow::Context;
+use std::{
+    net::Ipv4Addr,
+    sync::Arc,
+    time::Duration,
+};
+
+/// How many probes are sent for each address, from RFC 5227
+pub const PROBE_NUM: u32 = 3;
+
+/// How long to wait between probes
+pub const PROBE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Sends arp probes
+pub trait ArpProber: Send + Sync + 'static {
+    /// Probe `target` on an interface, blocking the current thread.
+    ///
+    /// Returns the hardware address that answered, or `None` if nobody did.
+    fn probe(&self, target: Ipv4Addr, interface_index: u32) -> anyhow::Result<Option<Vec<u8>>>;
+}
+
+/// Probes addresses with `ResolveIpNetEntry2`
+pub struct NeighborProber;
+
+impl ArpProber for NeighborProber {
+    fn probe(&self, target: Ipv4Addr, interface_index: u32) -> anyhow::Result<Option<Vec<u8>>> {
+        crate::neighbor_table::probe_neighbor(target, interface_index)
+            .context("failed to send arp probe")
+    }
+}
+
+/// An address to check for conflicts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictCheck {
+    pub address: Ipv4Addr,
+    pub interface_index: u32,
+
+    /// The hardware address of the interface itself, which is never a conflict
+    pub own_hardware_address: Vec<u8>,
+
+    pub probes: u32,
+    pub interval: Duration,
+}
+
+impl ConflictCheck {
+    pub fn new(address: Ipv4Addr, interface_index: u32, own_hardware_address: Vec<u8>) -> Self {
+        Self {
+            address,
+            interface_index,
+            own_hardware_address,
+
+            probes: PROBE_NUM,
+            interval: PROBE_INTERVAL,
+        }
+    }
+}
+
+/// The outcome of a conflict check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictReport {
+    pub address: Ipv4Addr,
+
+    /// Every other hardware address that answered, in the order they were seen
+    pub conflicts: Vec<Vec<u8>>,
+
+    /// How many probes were sent without an error
+    pub sent: u32,
+
+    /// Probes that failed to send
+    pub errors: Vec<String>,
+}
+
+impl ConflictReport {
+    /// Whether another host answered for the address.
+    pub fn is_conflict(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+
+    /// Whether every probe failed, so nothing is known about the address.
+    pub fn is_unchecked(&self) -> bool {
+        self.sent == 0
+    }
+}
+
+/// Probe an address `check.probes` times, collecting every other host that answers.
+pub async fn check_conflict<P: ArpProber>(prober: Arc<P>, check: ConflictCheck) -> ConflictReport {
+    let mut report = ConflictReport {
+        address: check.address,
+        conflicts: Vec::new(),
+        sent: 0,
+        errors: Vec::new(),
+    };
+
+    for i in 0..check.probes {
+        if i != 0 {
+            tokio::time::sleep(check.interval).await;
+        }
+
+        let prober = prober.clone();
+        let address = check.address;
+        let interface_index = check.interface_index;
+        let result =
+            tokio::task::spawn_blocking(move || prober.probe(address, interface_index)).await;
+        match result {
+            Ok(Ok(Some(hardware_address))) => {
+                report.sent += 1;
+                if hardware_address != check.own_hardware_address
+                    && !report.conflicts.contains(&hardware_address)
+                {
+                    report.conflicts.push(hardware_address);
+                }
+            }
+            Ok(Ok(None)) => report.sent += 1,
+            Ok(Err(e)) => report.errors.push(format!("{:#}", e)),
+            Err(e) => report
+                .errors
+                .push(format!("tokio task failed to join: {}", e)),
+        }
+    }
+
+    report
+}
+
+/// Check several addresses at once.
+pub async fn check_conflicts<P: ArpProber>(
+    prober: Arc<P>,
+    checks: Vec<ConflictCheck>,
+) -> Vec<ConflictReport> {
+    iced::futures::future::join_all(
+        checks
+            .into_iter()
+            .map(|check| check_conflict(prober.clone(), check)),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        sync::Mutex,
+    };
+
+    const OWN: [u8; 6] = [0x02, 0, 0, 0, 0, 0x01];
+    const OTHER: [u8; 6] = [0x02, 0, 0, 0, 0, 0x02];
+    const THIRD: [u8; 6] = [0x02, 0, 0, 0, 0, 0x03];
+
+    type Answer = anyhow::Result<Option<Vec<u8>>>;
+
+    /// A prober that plays back a list of answers per address
+    struct FakeProber {
+        answers: Mutex<HashMap<Ipv4Addr, Vec<Answer>>>,
+    }
+
+    impl FakeProber {
+        fn new(answers: Vec<(Ipv4Addr, Vec<Answer>)>) -> Arc<Self> {
+            Arc::new(Self {
+                answers: Mutex::new(answers.into_iter().collect()),
+            })
+        }
+    }
+
+    impl ArpProber for FakeProber {
+        fn probe(&self, target: Ipv4Addr, _interface_index: u32) -> Answer {
+            let mut answers = self.answers.lock().unwrap();
+            let answers = answers.get_mut(&target).context("unexpected probe")?;
+            if answers.is_empty() {
+                return Ok(None);
+            }
+            answers.remove(0)
+        }
+    }
+
+    fn check(address: Ipv4Addr) -> ConflictCheck {
+        ConflictCheck {
+            interval: Duration::from_millis(0),
+            ..ConflictCheck::new(address, 1, OWN.to_vec())
+        }
+    }
+
+    fn address(last: u8) -> Ipv4Addr {
+        Ipv4Addr::new(192, 168, 1, last)
+    }
+
+    #[tokio::test]
+    async fn no_answer_is_free() {
+        let prober = FakeProber::new(vec![(address(5), vec![])]);
+        let report = check_conflict(prober, check(address(5))).await;
+
+        assert!(!report.is_conflict());
+        assert!(!report.is_unchecked());
+        assert!(report.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn own_answer_is_not_a_conflict() {
+        let prober = FakeProber::new(vec![(
+            address(5),
+            vec![Ok(Some(OWN.to_vec())), Ok(Some(OWN.to_vec())), Ok(None)],
+        )]);
+        let report = check_conflict(prober, check(address(5))).await;
+
+        assert!(!report.is_conflict());
+    }
+
+    #[tokio::test]
+    async fn other_answers_are_collected_once() {
+        let prober = FakeProber::new(vec![(
+            address(5),
+            vec![
+                Ok(None),
+                Ok(Some(OTHER.to_vec())),
+                Ok(Some(THIRD.to_vec())),
+                Ok(Some(OTHER.to_vec())),
+            ],
+        )]);
+        let report = check_conflict(
+            prober,
+            ConflictCheck {
+                probes: 4,
+                ..check(address(5))
+            },
+        )
+        .await;
+
+        assert!(report.is_conflict());
+        assert_eq!(report.conflicts, vec![OTHER.to_vec(), THIRD.to_vec()]);
+    }
+
+    #[tokio::test]
+    async fn errors_do_not_stop_probing() {
+        let prober = FakeProber::new(vec![(
+            address(5),
+            vec![
+                Err(anyhow::anyhow!("no route")),
+                Ok(Some(OTHER.to_vec())),
+                Ok(None),
+            ],
+        )]);
+        let report = check_conflict(prober, check(address(5))).await;
+
+        assert_eq!(report.conflicts, vec![OTHER.to_vec()]);
+        assert_eq!(report.sent, 2);
+        assert_eq!(report.errors, vec!["no route".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn all_errors_is_unchecked() {
+        let prober = FakeProber::new(vec![(
+            address(5),
+            vec![
+                Err(anyhow::anyhow!("no route")),
+                Err(anyhow::anyhow!("no route")),
+                Err(anyhow::anyhow!("no route")),
+            ],
+        )]);
+        let report = check_conflict(prober, check(address(5))).await;
+
+        assert!(!report.is_conflict());
+        assert!(report.is_unchecked());
+        assert_eq!(report.errors.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn several_addresses_keep_their_order() {
+        let prober = FakeProber::new(vec![
+            (address(5), vec![]),
+            (address(6), vec![Ok(Some(OTHER.to_vec()))]),
+        ]);
+        let reports = check_conflicts(prober, vec![check(address(5)), check(address(6))]).await;
+
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].address, address(5));
+        assert!(!reports[0].is_conflict());
+        assert_eq!(reports[1].address, address(6));
+        assert!(reports[1].is_conflict());
+    }
+}
@@ -0,0 +1,157 @@
+use anyhow::Context;
+use log::info;
+use std::time::{
+    Duration,
+    Instant,
+    SystemTime,
+};
+use winapi::{
+    shared::winerror::NO_ERROR,
+    um::{
+        ipexport::{
+            IP_ADAPTER_INDEX_MAP,
+            MAX_ADAPTER_NAME,
+        },
+        iphlpapi::{
+            GetAdapterIndex,
+            IpReleaseAddress,
+            IpRenewAddress,
+        },
+    },
+};
+
+/// An action that can be performed on an adapter's DHCP lease
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DhcpAction {
+    Release,
+    Renew,
+}
+
+impl DhcpAction {
+    /// Run this action on the adapter with the given name.
+    ///
+    /// Adapter names have the form {<guid>}.
+    /// This runs on a blocking thread, as it may take several seconds to complete.
+    pub async fn run(self, adapter_name: String) -> anyhow::Result<()> {
+        tokio::task::spawn_blocking(move || self.run_blocking(&adapter_name))
+            .await
+            .context("tokio task failed to join")?
+    }
+
+    /// Run this action on the adapter with the given name, blocking the current thread.
+    pub fn run_blocking(self, adapter_name: &str) -> anyhow::Result<()> {
+        let start = Instant::now();
+        let mut adapter_index_map = get_adapter_index_map(adapter_name)?;
+        let code = match self {
+            Self::Release => unsafe { IpReleaseAddress(&mut adapter_index_map) },
+            Self::Renew => unsafe { IpRenewAddress(&mut adapter_index_map) },
+        };
+        if code != NO_ERROR {
+            return Err(std::io::Error::from_raw_os_error(code as i32))
+                .with_context(|| format!("failed to {} address", self.verb()));
+        }
+
+        info!(
+            "Executed DHCP {:?} for '{}' in {:?}",
+            self,
+            adapter_name,
+            start.elapsed()
+        );
+
+        Ok(())
+    }
+
+    /// Get the verb for this action, like "release".
+    pub fn verb(self) -> &'static str {
+        match self {
+            Self::Release => "release",
+            Self::Renew => "renew",
+        }
+    }
+}
+
+/// The state of a DHCP action on an adapter
+#[derive(Debug, Clone, Default)]
+pub enum DhcpActionState {
+    #[default]
+    Idle,
+    Running(DhcpAction),
+    Done(DhcpAction, Result<(), String>),
+}
+
+impl DhcpActionState {
+    /// Whether an action is running.
+    pub fn is_running(&self) -> bool {
+        matches!(self, Self::Running(_))
+    }
+
+    /// Get a human readable status message.
+    pub fn status(&self) -> String {
+        match self {
+            Self::Idle => String::new(),
+            Self::Running(DhcpAction::Release) => "Releasing...".to_string(),
+            Self::Running(DhcpAction::Renew) => "Renewing...".to_string(),
+            Self::Done(DhcpAction::Release, Ok(())) => "Released".to_string(),
+            Self::Done(DhcpAction::Renew, Ok(())) => "Renewed".to_string(),
+            Self::Done(action, Err(e)) => format!("Failed to {}: {}", action.verb(), e),
+        }
+    }
+}
+
+/// Get the `IP_ADAPTER_INDEX_MAP` for an adapter name.
+fn get_adapter_index_map(adapter_name: &str) -> anyhow::Result<IP_ADAPTER_INDEX_MAP> {
+    let device_name: Vec<u16> = format!("\\DEVICE\\TCPIP_{}", adapter_name)
+        .encode_utf16()
+        .collect();
+
+    // Leave room for the nul terminator
+    if device_name.len() >= MAX_ADAPTER_NAME {
+        anyhow::bail!("adapter name '{}' is too long", adapter_name);
+    }
+
+    let mut adapter_index_map = IP_ADAPTER_INDEX_MAP {
+        Index: 0,
+        Name: [0; MAX_ADAPTER_NAME],
+    };
+    adapter_index_map.Name[..device_name.len()].copy_from_slice(&device_name);
+
+    let code = unsafe {
+        GetAdapterIndex(
+            adapter_index_map.Name.as_mut_ptr(),
+            &mut adapter_index_map.Index,
+        )
+    };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32))
+            .context("failed to get adapter index");
+    }
+
+    Ok(adapter_index_map)
+}
+
+/// Format a duration like "1d 2h 3m 4s", skipping leading zero units.
+pub fn format_duration(duration: Duration) -> String {
+    let total_secs = duration.as_secs();
+    let days = total_secs / (60 * 60 * 24);
+    let hours = (total_secs / (60 * 60)) % 24;
+    let minutes = (total_secs / 60) % 60;
+    let secs = total_secs % 60;
+
+    if days > 0 {
+        format!("{}d {}h {}m {}s", days, hours, minutes, secs)
+    } else if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, secs)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Format a point in time relative to now, like "in 1h 2m 3s" or "1h 2m 3s ago".
+pub fn format_relative_time(time: SystemTime, now: SystemTime) -> String {
+    match time.duration_since(now) {
+        Ok(duration) => format!("in {}", format_duration(duration)),
+        Err(e) => format!("{} ago", format_duration(e.duration())),
+    }
+}
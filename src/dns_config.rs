@@ -0,0 +1,579 @@
+use crate::{
+    style::{
+        ForegroundGreenButtonStyle,
+        ForegroundGreenTextInputStyle,
+    },
+    tcpip_interface::TcpipInterface,
+};
+use anyhow::Context;
+use iced::{
+    Align,
+    Button,
+    Column,
+    Element,
+    Length,
+    PickList,
+    Row,
+    Text,
+    TextInput,
+};
+use log::info;
+use netcon::adapter_name_to_uuid;
+use once_cell::sync::Lazy;
+use std::net::IpAddr;
+use winapi::{
+    shared::{
+        guiddef::GUID,
+        minwindef::DWORD,
+        ntdef::PWSTR,
+        winerror::NO_ERROR,
+    },
+    um::libloaderapi::{
+        GetProcAddress,
+        LoadLibraryW,
+    },
+};
+
+/// The max length of a dns name
+const MAX_DOMAIN_LEN: usize = 253;
+
+/// The max length of a single label of a dns name
+const MAX_LABEL_LEN: usize = 63;
+
+/// A named set of dns settings that can be applied to any adapter
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DnsPreset {
+    pub name: String,
+    pub servers: Vec<IpAddr>,
+
+    #[serde(default)]
+    pub suffix: String,
+}
+
+impl DnsPreset {
+    /// Get the presets that are available before the user makes their own.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self {
+                name: "Cloudflare".to_string(),
+                servers: vec![[1, 1, 1, 1].into(), [1, 0, 0, 1].into()],
+                suffix: String::new(),
+            },
+            Self {
+                name: "Google".to_string(),
+                servers: vec![[8, 8, 8, 8].into(), [8, 8, 4, 4].into()],
+                suffix: String::new(),
+            },
+            Self {
+                name: "Quad9".to_string(),
+                servers: vec![[9, 9, 9, 9].into(), [149, 112, 112, 112].into()],
+                suffix: String::new(),
+            },
+        ]
+    }
+}
+
+impl std::fmt::Display for DnsPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.name.fmt(f)
+    }
+}
+
+/// The dns settings of an adapter, as stored in the registry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsSettings {
+    /// The statically configured dns servers. This is empty if they come from dhcp.
+    pub name_server: String,
+
+    /// The dns servers from dhcp
+    pub dhcp_name_server: String,
+
+    /// The statically configured connection-specific suffix
+    pub domain: String,
+}
+
+impl DnsSettings {
+    /// Load the dns settings of the adapter with the given name.
+    pub fn load(adapter_name: &str) -> std::io::Result<Self> {
+        let interface = TcpipInterface::open_read_only(adapter_name)?;
+        Ok(Self {
+            name_server: interface.get_name_server()?,
+            dhcp_name_server: interface.get_dhcp_name_server()?,
+            domain: interface.get_domain()?,
+        })
+    }
+}
+
+/// Parse a list of dns servers separated by commas or whitespace.
+///
+/// All invalid entries are returned at once so they can be shown together.
+pub fn parse_servers(input: &str) -> Result<Vec<IpAddr>, Vec<String>> {
+    let mut servers = Vec::new();
+    let mut errors = Vec::new();
+    for entry in input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|entry| !entry.is_empty())
+    {
+        match entry.parse::<IpAddr>() {
+            Ok(server) if server.is_unspecified() || server.is_multicast() => {
+                errors.push(format!("{} is not a usable dns server", server));
+            }
+            Ok(server) if servers.contains(&server) => {
+                errors.push(format!("{} is listed more than once", server));
+            }
+            Ok(server) => servers.push(server),
+            Err(e) => errors.push(format!("'{}' is not an ip address: {}", entry, e)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(servers)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Validate a connection-specific dns suffix, like "corp.example.com".
+///
+/// An empty suffix is valid and clears it.
+pub fn validate_suffix(suffix: &str) -> Result<(), String> {
+    if suffix.is_empty() {
+        return Ok(());
+    }
+    if suffix.len() > MAX_DOMAIN_LEN {
+        return Err(format!(
+            "suffix must be at most {} characters",
+            MAX_DOMAIN_LEN
+        ));
+    }
+
+    for label in suffix.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > MAX_LABEL_LEN {
+            return Err(format!(
+                "'{}' must be between 1 and {} characters",
+                label, MAX_LABEL_LEN
+            ));
+        }
+        if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!(
+                "'{}' may only contain letters, digits and hyphens",
+                label
+            ));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(format!("'{}' may not start or end with a hyphen", label));
+        }
+    }
+
+    Ok(())
+}
+
+/// A change to the dns settings of an adapter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsChange {
+    /// The new dns servers. Empty to use the servers from dhcp.
+    pub servers: Vec<IpAddr>,
+    pub domain: String,
+}
+
+/// Apply dns settings to the adapter with the given name.
+///
+/// This uses `SetInterfaceDnsSettings` if the os has it,
+/// falling back to writing the registry directly, which only supports ipv4 servers.
+pub async fn apply_dns_change(adapter_name: String, change: DnsChange) -> anyhow::Result<()> {
+    tokio::task::spawn_blocking(move || {
+        match *SET_INTERFACE_DNS_SETTINGS {
+            Some(set_interface_dns_settings) => {
+                apply_with_api(set_interface_dns_settings, &adapter_name, &change)?;
+            }
+            None => {
+                apply_with_registry(&adapter_name, &change)?;
+            }
+        }
+
+        info!(
+            "Set dns servers of '{}' to {:?} with suffix '{}'",
+            adapter_name, change.servers, change.domain
+        );
+
+        Ok(())
+    })
+    .await
+    .context("tokio task failed to join")?
+}
+
+fn apply_with_registry(adapter_name: &str, change: &DnsChange) -> anyhow::Result<()> {
+    if change.servers.iter().any(|server| server.is_ipv6()) {
+        anyhow::bail!("ipv6 dns servers are not supported on this version of Windows");
+    }
+
+    let interface =
+        TcpipInterface::open(adapter_name).context("failed to open tcpip interface key")?;
+    interface
+        .set_name_server(&join_servers(change.servers.iter()))
+        .context("failed to set name server")?;
+    interface
+        .set_domain(&change.domain)
+        .context("failed to set domain")?;
+
+    Ok(())
+}
+
+fn apply_with_api(
+    set_interface_dns_settings: SetInterfaceDnsSettingsFn,
+    adapter_name: &str,
+    change: &DnsChange,
+) -> anyhow::Result<()> {
+    let uuid = adapter_name_to_uuid(adapter_name).context("adapter name is not a guid")?;
+    let (data1, data2, data3, data4) = uuid.as_fields();
+    let guid = GUID {
+        Data1: data1,
+        Data2: data2,
+        Data3: data3,
+        Data4: *data4,
+    };
+
+    let mut domain = to_wide(&change.domain);
+    let mut v4_servers = to_wide(&join_servers(
+        change.servers.iter().filter(|server| server.is_ipv4()),
+    ));
+    let mut v6_servers = to_wide(&join_servers(
+        change.servers.iter().filter(|server| server.is_ipv6()),
+    ));
+
+    // The ipv4 and ipv6 server lists are set separately.
+    let v4_settings = DnsInterfaceSettings {
+        version: DNS_INTERFACE_SETTINGS_VERSION1,
+        flags: DNS_SETTING_NAMESERVER | DNS_SETTING_DOMAIN,
+        domain: domain.as_mut_ptr(),
+        name_server: v4_servers.as_mut_ptr(),
+        ..DnsInterfaceSettings::default()
+    };
+    let code = unsafe { set_interface_dns_settings(guid, &v4_settings) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32))
+            .context("failed to set ipv4 dns settings");
+    }
+
+    let v6_settings = DnsInterfaceSettings {
+        version: DNS_INTERFACE_SETTINGS_VERSION1,
+        flags: DNS_SETTING_IPV6 | DNS_SETTING_NAMESERVER,
+        name_server: v6_servers.as_mut_ptr(),
+        ..DnsInterfaceSettings::default()
+    };
+    let code = unsafe { set_interface_dns_settings(guid, &v6_settings) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32))
+            .context("failed to set ipv6 dns settings");
+    }
+
+    Ok(())
+}
+
+fn join_servers<'a>(servers: impl Iterator<Item = &'a IpAddr>) -> String {
+    servers
+        .map(|server| server.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Encode a string as a nul-terminated wide string.
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+// `SetInterfaceDnsSettings` was added in Windows 10 2004, and is not in winapi.
+const DNS_INTERFACE_SETTINGS_VERSION1: u32 = 1;
+const DNS_SETTING_IPV6: u64 = 0x0001;
+const DNS_SETTING_NAMESERVER: u64 = 0x0002;
+const DNS_SETTING_DOMAIN: u64 = 0x0020;
+
+/// `DNS_INTERFACE_SETTINGS`
+#[repr(C)]
+struct DnsInterfaceSettings {
+    version: u32,
+    flags: u64,
+    domain: PWSTR,
+    name_server: PWSTR,
+    search_list: PWSTR,
+    registration_enabled: u32,
+    register_adapter_name: u32,
+    enable_llmnr: u32,
+    query_adapter_name: u32,
+    profile_name_server: PWSTR,
+}
+
+impl Default for DnsInterfaceSettings {
+    fn default() -> Self {
+        Self {
+            version: 0,
+            flags: 0,
+            domain: std::ptr::null_mut(),
+            name_server: std::ptr::null_mut(),
+            search_list: std::ptr::null_mut(),
+            registration_enabled: 0,
+            register_adapter_name: 0,
+            enable_llmnr: 0,
+            query_adapter_name: 0,
+            profile_name_server: std::ptr::null_mut(),
+        }
+    }
+}
+
+type SetInterfaceDnsSettingsFn =
+    unsafe extern "system" fn(GUID, *const DnsInterfaceSettings) -> DWORD;
+
+static SET_INTERFACE_DNS_SETTINGS: Lazy<Option<SetInterfaceDnsSettingsFn>> = Lazy::new(|| {
+    let module_name = to_wide("iphlpapi.dll");
+
+    // The module is never freed, so the function pointer stays valid.
+    let module = unsafe { LoadLibraryW(module_name.as_ptr()) };
+    if module.is_null() {
+        return None;
+    }
+
+    let proc = unsafe { GetProcAddress(module, b"SetInterfaceDnsSettings\0".as_ptr().cast()) };
+    if proc.is_null() {
+        info!("SetInterfaceDnsSettings is not available, falling back to the registry");
+        return None;
+    }
+
+    Some(unsafe { std::mem::transmute::<_, SetInterfaceDnsSettingsFn>(proc) })
+});
+
+#[derive(Debug, Clone)]
+pub enum DnsMessage {
+    UpdateServers(String),
+    UpdateSuffix(String),
+    PresetSelected(DnsPreset),
+
+    Apply,
+    RevertToDhcp,
+}
+
+/// An editor for the dns settings of an adapter
+#[derive(Debug, Clone)]
+pub struct DnsEditor {
+    current: Result<DnsSettings, String>,
+
+    servers_input: String,
+    suffix_input: String,
+    errors: Vec<String>,
+
+    is_applying: bool,
+    status: String,
+
+    servers_input_state: iced::text_input::State,
+    suffix_input_state: iced::text_input::State,
+    preset_state: iced::pick_list::State<DnsPreset>,
+    apply_button_state: iced::button::State,
+    revert_button_state: iced::button::State,
+}
+
+impl DnsEditor {
+    pub fn new(current: std::io::Result<DnsSettings>) -> Self {
+        let current = current.map_err(|e| e.to_string());
+        let (servers_input, suffix_input) = inputs_from_settings(&current);
+
+        Self {
+            current,
+
+            servers_input,
+            suffix_input,
+            errors: Vec::new(),
+
+            is_applying: false,
+            status: String::new(),
+
+            servers_input_state: iced::text_input::State::new(),
+            suffix_input_state: iced::text_input::State::new(),
+            preset_state: iced::pick_list::State::default(),
+            apply_button_state: iced::button::State::new(),
+            revert_button_state: iced::button::State::new(),
+        }
+    }
+
+    /// Take the inputs and status of the editor this one replaces on refresh.
+    ///
+    /// Inputs are kept only if the user edited them.
+    pub fn keep_view_state(&mut self, old: DnsEditor) {
+        let (old_servers_input, old_suffix_input) = inputs_from_settings(&old.current);
+        if old.servers_input != old_servers_input || old.suffix_input != old_suffix_input {
+            self.servers_input = old.servers_input;
+            self.suffix_input = old.suffix_input;
+        }
+        self.errors = old.errors;
+        self.is_applying = old.is_applying;
+        self.status = old.status;
+    }
+
+    pub fn update(&mut self, message: DnsMessage) {
+        match message {
+            DnsMessage::UpdateServers(servers_input) => {
+                self.servers_input = servers_input;
+            }
+            DnsMessage::UpdateSuffix(suffix_input) => {
+                self.suffix_input = suffix_input;
+            }
+            DnsMessage::PresetSelected(preset) => {
+                self.servers_input = join_servers(preset.servers.iter()).replace(',', ", ");
+                self.suffix_input = preset.suffix;
+            }
+            DnsMessage::Apply | DnsMessage::RevertToDhcp => {
+                // Handled by `start_apply`, as it needs the adapter.
+            }
+        }
+    }
+
+    /// Validate the inputs and start applying them.
+    ///
+    /// If `revert_to_dhcp` is true, the server list is cleared so that the servers from dhcp are used.
+    /// Returns `None` if validation failed or a change is already being applied.
+    pub fn start_apply(&mut self, revert_to_dhcp: bool) -> Option<DnsChange> {
+        if self.is_applying {
+            return None;
+        }
+
+        let servers = if revert_to_dhcp {
+            Ok(Vec::new())
+        } else {
+            parse_servers(&self.servers_input)
+        };
+        let domain = self.suffix_input.trim().to_string();
+
+        self.errors.clear();
+        let servers = match servers {
+            Ok(servers) => Some(servers),
+            Err(errors) => {
+                self.errors.extend(errors);
+                None
+            }
+        };
+        if let Err(e) = validate_suffix(&domain) {
+            self.errors.push(e);
+        }
+
+        let servers = servers.filter(|_| self.errors.is_empty())?;
+        self.is_applying = true;
+        self.status = "Applying...".to_string();
+
+        Some(DnsChange { servers, domain })
+    }
+
+    /// Record the result of applying a change.
+    pub fn finish_apply(&mut self, result: &anyhow::Result<()>) {
+        self.is_applying = false;
+        self.status = match result {
+            Ok(()) => "Applied".to_string(),
+            Err(e) => format!("Failed: {:#}", e),
+        };
+    }
+
+    pub fn view<'a>(&'a mut self, presets: &'a [DnsPreset]) -> Element<'a, DnsMessage> {
+        let mut column = Column::new().spacing(5);
+
+        match self.current.as_ref() {
+            Ok(current) => {
+                let configured = if current.name_server.is_empty() {
+                    "From DHCP"
+                } else {
+                    current.name_server.as_str()
+                };
+                column = column
+                    .push(Text::new(format!("Configured DNS Servers: {}", configured)).size(15))
+                    .push(
+                        Text::new(format!("DHCP DNS Servers: {}", current.dhcp_name_server))
+                            .size(15),
+                    )
+                    .push(Text::new(format!("Configured Suffix: {}", current.domain)).size(15));
+            }
+            Err(e) => {
+                column =
+                    column.push(Text::new(format!("Failed to get DNS settings: {}", e)).size(15));
+            }
+        }
+
+        column = column
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Text::new("Servers").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.servers_input_state,
+                            "1.1.1.1, 1.0.0.1",
+                            &self.servers_input,
+                            DnsMessage::UpdateServers,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(250)),
+                    )
+                    .push(PickList::new(
+                        &mut self.preset_state,
+                        presets,
+                        None,
+                        DnsMessage::PresetSelected,
+                    )),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Text::new("Suffix").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.suffix_input_state,
+                            "corp.example.com",
+                            &self.suffix_input,
+                            DnsMessage::UpdateSuffix,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(250)),
+                    ),
+            );
+
+        column = self.errors.iter().fold(column, |column, error| {
+            column.push(Text::new(format!("Error: {}", error)).size(15))
+        });
+
+        let mut apply_button =
+            Button::new(&mut self.apply_button_state, Text::new("Apply").size(15))
+                .style(ForegroundGreenButtonStyle);
+        let mut revert_button = Button::new(
+            &mut self.revert_button_state,
+            Text::new("Revert to DHCP").size(15),
+        )
+        .style(ForegroundGreenButtonStyle);
+        if !self.is_applying {
+            apply_button = apply_button.on_press(DnsMessage::Apply);
+            revert_button = revert_button.on_press(DnsMessage::RevertToDhcp);
+        }
+
+        column
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(apply_button)
+                    .push(revert_button)
+                    .push(Text::new(&self.status).size(15)),
+            )
+            .into()
+    }
+}
+
+/// Get the initial server and suffix inputs for some settings.
+fn inputs_from_settings(settings: &Result<DnsSettings, String>) -> (String, String) {
+    match settings {
+        Ok(settings) => (
+            settings.name_server.replace(',', ", "),
+            settings.domain.clone(),
+        ),
+        Err(_) => (String::new(), String::new()),
+    }
+}
@@ -0,0 +1,342 @@
+use crate::{
+    adapter_addresses::{
+        AdapterAddresses,
+        LegacyAdapterInfo,
+    },
+    format_mac_address_to_string,
+};
+use anyhow::Context;
+use std::{
+    fmt::Write,
+    net::IpAddr,
+    path::PathBuf,
+    time::SystemTime,
+};
+
+/// The separator used for list fields in formats that do not support lists.
+const LIST_SEPARATOR: &str = "; ";
+
+/// A row of a table that can be exported
+pub trait ExportRecord: serde::Serialize {
+    /// The names of the columns, in order, for tabular formats.
+    const COLUMNS: &'static [&'static str];
+
+    /// Get the values of this record, in the same order as [`Self::COLUMNS`].
+    fn values(&self) -> Vec<String>;
+}
+
+/// A format that tables can be exported to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+    Markdown,
+}
+
+impl ExportFormat {
+    /// All export formats
+    pub const ALL: &'static [Self] = &[Self::Json, Self::Csv, Self::Markdown];
+
+    /// Get the file extension for this format, without the leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+        }
+    }
+
+    /// Export the given records to this format.
+    pub fn export<R: ExportRecord>(self, records: &[R]) -> anyhow::Result<String> {
+        match self {
+            Self::Json => to_json(records).context("failed to serialize json"),
+            Self::Csv => Ok(to_csv(records)),
+            Self::Markdown => Ok(to_markdown(records)),
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Json => "JSON",
+            Self::Csv => "CSV",
+            Self::Markdown => "Markdown",
+        };
+        s.fmt(f)
+    }
+}
+
+/// A flattened view of an adapter, for exporting.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct AdapterRecord {
+    pub name: String,
+    pub friendly_name: String,
+    pub description: String,
+    pub combo_index: Option<u32>,
+    pub if_index: u32,
+    pub luid: u64,
+    pub hardware_address: String,
+    pub if_type: String,
+    pub oper_status: String,
+    pub mtu: u32,
+    pub dhcp_enabled: bool,
+    pub dhcp_server: Option<String>,
+    pub dns_suffix: String,
+
+    /// Addresses in CIDR notation, like "192.168.1.2/24"
+    pub ip_addresses: Vec<String>,
+    pub gateways: Vec<String>,
+    pub dns_servers: Vec<String>,
+}
+
+impl ExportRecord for AdapterRecord {
+    const COLUMNS: &'static [&'static str] = &[
+        "Name",
+        "Friendly Name",
+        "Description",
+        "Combo Index",
+        "Interface Index",
+        "LUID",
+        "Hardware Address",
+        "Interface Type",
+        "Status",
+        "MTU",
+        "DHCP Enabled",
+        "DHCP Server",
+        "DNS Suffix",
+        "IP Addresses",
+        "Gateways",
+        "DNS Servers",
+    ];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.friendly_name.clone(),
+            self.description.clone(),
+            self.combo_index
+                .map(|combo_index| combo_index.to_string())
+                .unwrap_or_default(),
+            self.if_index.to_string(),
+            format!("{:#018X}", self.luid),
+            self.hardware_address.clone(),
+            self.if_type.clone(),
+            self.oper_status.clone(),
+            self.mtu.to_string(),
+            self.dhcp_enabled.to_string(),
+            self.dhcp_server.clone().unwrap_or_default(),
+            self.dns_suffix.clone(),
+            self.ip_addresses.join(LIST_SEPARATOR),
+            self.gateways.join(LIST_SEPARATOR),
+            self.dns_servers.join(LIST_SEPARATOR),
+        ]
+    }
+}
+
+impl AdapterRecord {
+    pub fn new(adapter: &AdapterAddresses, legacy_info: Option<&LegacyAdapterInfo>) -> Self {
+        Self {
+            name: adapter.name.clone(),
+            friendly_name: adapter.friendly_name.clone(),
+            description: adapter.description.clone(),
+            combo_index: legacy_info.map(|legacy_info| legacy_info.combo_index),
+            if_index: adapter.if_index,
+            luid: adapter.luid,
+            hardware_address: format_mac_address_to_string(&adapter.physical_address)
+                .trim_end()
+                .to_string(),
+            if_type: adapter.if_type_name().to_string(),
+            oper_status: adapter.oper_status.to_string(),
+            mtu: adapter.mtu,
+            dhcp_enabled: adapter.dhcp_v4_enabled,
+            dhcp_server: legacy_info
+                .and_then(|legacy_info| legacy_info.dhcp_server)
+                .map(|dhcp_server| dhcp_server.to_string()),
+            dns_suffix: adapter.dns_suffix.clone(),
+
+            ip_addresses: adapter
+                .unicast_addresses
+                .iter()
+                .map(|address| format!("{}/{}", address.address, address.prefix_length))
+                .collect(),
+            gateways: ip_addr_strings(&adapter.gateway_addresses),
+            dns_servers: ip_addr_strings(&adapter.dns_servers),
+        }
+    }
+}
+
+fn ip_addr_strings(addresses: &[IpAddr]) -> Vec<String> {
+    addresses
+        .iter()
+        .map(|address| address.to_string())
+        .collect()
+}
+
+/// Export records as a pretty-printed json array.
+pub fn to_json<R: ExportRecord>(records: &[R]) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(records)
+}
+
+/// Export records as csv, with a header row.
+///
+/// Lines are terminated with CRLF, as RFC 4180 specifies.
+pub fn to_csv<R: ExportRecord>(records: &[R]) -> String {
+    let mut ret = String::new();
+
+    write_csv_row(&mut ret, R::COLUMNS.iter().copied());
+    for record in records {
+        write_csv_row(&mut ret, record.values().iter().map(String::as_str));
+    }
+
+    ret
+}
+
+fn write_csv_row<'a>(out: &mut String, values: impl Iterator<Item = &'a str>) {
+    for (i, value) in values.enumerate() {
+        if i != 0 {
+            out.push(',');
+        }
+        out.push_str(&escape_csv(value));
+    }
+    out.push_str("\r\n");
+}
+
+/// Quote a csv field if needed.
+fn escape_csv(value: &str) -> std::borrow::Cow<'_, str> {
+    if value.contains(|c| matches!(c, ',' | '"' | '\r' | '\n')) {
+        format!("\"{}\"", value.replace('"', "\"\"")).into()
+    } else {
+        value.into()
+    }
+}
+
+/// Export records as a markdown table.
+pub fn to_markdown<R: ExportRecord>(records: &[R]) -> String {
+    let mut ret = String::new();
+
+    write_markdown_row(&mut ret, R::COLUMNS.iter().copied());
+    write_markdown_row(&mut ret, R::COLUMNS.iter().map(|_| "---"));
+    for record in records {
+        write_markdown_row(&mut ret, record.values().iter().map(String::as_str));
+    }
+
+    ret
+}
+
+fn write_markdown_row<'a>(out: &mut String, values: impl Iterator<Item = &'a str>) {
+    out.push('|');
+    for value in values {
+        write!(out, " {} |", escape_markdown(value)).expect("failed to write to string");
+    }
+    out.push('\n');
+}
+
+/// Escape text for a markdown table cell.
+///
+/// Pipes would end the cell and newlines would end the row.
+fn escape_markdown(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// Save an export to the exports folder in the data dir, returning the path.
+///
+/// The file is named after `name` and the current time, like "adapters-1630000000.json".
+pub fn save_export(name: &str, data: &str, extension: &str) -> anyhow::Result<PathBuf> {
+    let dir = crate::UserSettings::data_dir()?.join("exports");
+    std::fs::create_dir_all(&dir).context("failed to create exports dir")?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .context("system time is before the unix epoch")?
+        .as_secs();
+    let path = dir.join(format!("{}-{}.{}", name, timestamp, extension));
+    std::fs::write(&path, data).context("failed to write export")?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Records covering escaping, empty lists and missing values
+    fn records() -> Vec<AdapterRecord> {
+        vec![
+            AdapterRecord {
+                name: "{4D36E972-E325-11CE-BFC1-08002BE10318}".to_string(),
+                friendly_name: "Ethernet".to_string(),
+                description: "Intel(R) Ethernet Connection I219-V".to_string(),
+                combo_index: Some(7),
+                if_index: 12,
+                luid: 0x0006_0000_0100_0000,
+                hardware_address: "00:1A:2B:3C:4D:5E".to_string(),
+                if_type: "Ethernet".to_string(),
+                oper_status: "Up".to_string(),
+                mtu: 1500,
+                dhcp_enabled: true,
+                dhcp_server: Some("192.168.1.1".to_string()),
+                dns_suffix: "home.lan".to_string(),
+
+                ip_addresses: vec!["192.168.1.20/24".to_string(), "fe80::1/64".to_string()],
+                gateways: vec!["192.168.1.1".to_string()],
+                dns_servers: vec!["192.168.1.1".to_string(), "1.1.1.1".to_string()],
+            },
+            AdapterRecord {
+                name: "{0B3B5A1C-0000-4F5B-9C6D-1E2F3A4B5C6D}".to_string(),
+                friendly_name: "Lab, \"Bench\" | 2".to_string(),
+                description: "Line one\nLine two\r\nback\\slash".to_string(),
+                combo_index: None,
+                if_index: 3,
+                luid: 0,
+                hardware_address: String::new(),
+                if_type: "Tunnel".to_string(),
+                oper_status: "Down".to_string(),
+                mtu: 0,
+                dhcp_enabled: false,
+                dhcp_server: None,
+                dns_suffix: String::new(),
+
+                ip_addresses: Vec::new(),
+                gateways: Vec::new(),
+                dns_servers: Vec::new(),
+            },
+        ]
+    }
+
+    #[test]
+    fn json_golden() {
+        assert_eq!(
+            to_json(&records()).unwrap(),
+            include_str!("../tests/golden/adapters.json")
+        );
+    }
+
+    #[test]
+    fn csv_golden() {
+        assert_eq!(
+            to_csv(&records()),
+            include_str!("../tests/golden/adapters.csv")
+        );
+    }
+
+    #[test]
+    fn markdown_golden() {
+        assert_eq!(
+            to_markdown(&records()),
+            include_str!("../tests/golden/adapters.md")
+        );
+    }
+
+    #[test]
+    fn no_records() {
+        assert_eq!(to_json::<AdapterRecord>(&[]).unwrap(), "[]");
+        assert_eq!(to_csv::<AdapterRecord>(&[]).lines().count(), 1);
+        assert_eq!(to_markdown::<AdapterRecord>(&[]).lines().count(), 2);
+    }
+}
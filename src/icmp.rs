@@ -0,0 +1,283 @@
+use crate::sockaddr::ip_addr_to_sockaddr_inet;
+use std::{
+    convert::TryFrom,
+    net::{
+        IpAddr,
+        Ipv4Addr,
+        Ipv6Addr,
+    },
+    time::Duration,
+};
+use winapi::{
+    shared::{
+        minwindef::{
+            BOOL,
+            DWORD,
+            LPVOID,
+            WORD,
+        },
+        ntdef::{
+            HANDLE,
+            PVOID,
+        },
+        ws2ipdef::SOCKADDR_IN6,
+    },
+    um::{
+        handleapi::INVALID_HANDLE_VALUE,
+        ipexport::{
+            IPAddr,
+            ICMPV6_ECHO_REPLY,
+            ICMP_ECHO_REPLY,
+            IP_DEST_HOST_UNREACHABLE,
+            IP_DEST_NET_UNREACHABLE,
+            IP_DEST_PORT_UNREACHABLE,
+            IP_DEST_PROT_UNREACHABLE,
+            IP_DEST_UNREACHABLE,
+            IP_OPTION_INFORMATION,
+            IP_REQ_TIMED_OUT,
+            IP_STATUS_BASE,
+            IP_SUCCESS,
+            IP_TIME_EXCEEDED,
+            IP_TTL_EXPIRED_REASSEM,
+            IP_TTL_EXPIRED_TRANSIT,
+        },
+    },
+};
+
+// winapi 0.3 does not bind icmpapi.h.
+#[link(name = "iphlpapi")]
+extern "system" {
+    fn IcmpCreateFile() -> HANDLE;
+    fn Icmp6CreateFile() -> HANDLE;
+    fn IcmpCloseHandle(IcmpHandle: HANDLE) -> BOOL;
+    fn IcmpSendEcho2(
+        IcmpHandle: HANDLE,
+        Event: HANDLE,
+        ApcRoutine: PVOID,
+        ApcContext: PVOID,
+        DestinationAddress: IPAddr,
+        RequestData: LPVOID,
+        RequestSize: WORD,
+        RequestOptions: *mut IP_OPTION_INFORMATION,
+        ReplyBuffer: LPVOID,
+        ReplySize: DWORD,
+        Timeout: DWORD,
+    ) -> DWORD;
+    fn Icmp6SendEcho2(
+        IcmpHandle: HANDLE,
+        Event: HANDLE,
+        ApcRoutine: PVOID,
+        ApcContext: PVOID,
+        SourceAddress: *mut SOCKADDR_IN6,
+        DestinationAddress: *mut SOCKADDR_IN6,
+        RequestData: LPVOID,
+        RequestSize: WORD,
+        RequestOptions: *mut IP_OPTION_INFORMATION,
+        ReplyBuffer: LPVOID,
+        ReplySize: DWORD,
+        Timeout: DWORD,
+    ) -> DWORD;
+}
+
+/// Extra reply buffer space for an icmp error message and, on ipv6, an `IO_STATUS_BLOCK`
+const REPLY_BUFFER_SLACK: usize = 8 + 64;
+
+/// An icmp handle that is closed on drop
+struct IcmpHandle(HANDLE);
+
+impl IcmpHandle {
+    fn new(ipv6: bool) -> std::io::Result<Self> {
+        let handle = unsafe {
+            if ipv6 {
+                Icmp6CreateFile()
+            } else {
+                IcmpCreateFile()
+            }
+        };
+        if handle == INVALID_HANDLE_VALUE {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Self(handle))
+    }
+}
+
+impl Drop for IcmpHandle {
+    fn drop(&mut self) {
+        unsafe {
+            IcmpCloseHandle(self.0);
+        }
+    }
+}
+
+/// The options of an echo request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EchoOptions {
+    /// The number of bytes of data to send
+    pub size: u16,
+    pub ttl: u8,
+    pub timeout: Duration,
+}
+
+/// The answer to an echo request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EchoReply {
+    /// An `IP_STATUS` code, `IP_SUCCESS` for an echo reply
+    pub status: u32,
+
+    /// The address that answered, which is a router for errors like an expired ttl
+    pub from: Option<IpAddr>,
+    pub rtt: Duration,
+
+    /// The ttl of the reply, which is only known for ipv4
+    pub ttl: Option<u8>,
+}
+
+impl EchoReply {
+    fn no_reply(status: u32) -> Self {
+        Self {
+            status,
+            from: None,
+            rtt: Duration::from_millis(0),
+            ttl: None,
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.status == IP_SUCCESS
+    }
+
+    /// Whether this came from a router on the way because the ttl ran out.
+    pub fn is_ttl_expired(&self) -> bool {
+        matches!(
+            self.status,
+            IP_TTL_EXPIRED_TRANSIT | IP_TTL_EXPIRED_REASSEM | IP_TIME_EXCEEDED
+        )
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.status == IP_REQ_TIMED_OUT
+    }
+
+    /// Get a human readable description of the status.
+    pub fn status_name(&self) -> String {
+        match self.status {
+            IP_SUCCESS => "Reply".to_string(),
+            IP_REQ_TIMED_OUT => "Request timed out".to_string(),
+            IP_TTL_EXPIRED_TRANSIT | IP_TIME_EXCEEDED => "TTL expired in transit".to_string(),
+            IP_TTL_EXPIRED_REASSEM => "TTL expired during reassembly".to_string(),
+            IP_DEST_NET_UNREACHABLE => "Destination net unreachable".to_string(),
+            IP_DEST_HOST_UNREACHABLE | IP_DEST_UNREACHABLE => {
+                "Destination host unreachable".to_string()
+            }
+            IP_DEST_PROT_UNREACHABLE => "Destination protocol unreachable".to_string(),
+            IP_DEST_PORT_UNREACHABLE => "Destination port unreachable".to_string(),
+            status => format!("IP status {}", status),
+        }
+    }
+}
+
+/// Send an icmp echo request and wait for the answer, blocking the current thread.
+///
+/// `scope_id` is the zone index of link-local ipv6 addresses.
+/// Failures reported as an `IP_STATUS`, like a timeout, are returned as replies.
+pub fn send_echo(
+    target: IpAddr,
+    scope_id: Option<u32>,
+    options: EchoOptions,
+) -> std::io::Result<EchoReply> {
+    let handle = IcmpHandle::new(target.is_ipv6())?;
+    let mut data: Vec<u8> = (0..options.size).map(|i| b'a' + (i % 23) as u8).collect();
+    let mut request_options = IP_OPTION_INFORMATION {
+        Ttl: options.ttl,
+        Tos: 0,
+        Flags: 0,
+        OptionsSize: 0,
+        OptionsData: std::ptr::null_mut(),
+    };
+    let timeout = DWORD::try_from(options.timeout.as_millis()).unwrap_or(DWORD::MAX);
+
+    let reply_size = std::mem::size_of::<ICMP_ECHO_REPLY>()
+        .max(std::mem::size_of::<ICMPV6_ECHO_REPLY>())
+        + data.len()
+        + REPLY_BUFFER_SLACK;
+    // Use a u64 buffer so that the reply is properly aligned.
+    let mut reply_buffer: Vec<u64> = vec![0; (reply_size + 7) / 8];
+    let reply_size = (reply_buffer.len() * 8) as DWORD;
+
+    let num_replies = match target {
+        IpAddr::V4(address) => unsafe {
+            IcmpSendEcho2(
+                handle.0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                u32::from_ne_bytes(address.octets()),
+                data.as_mut_ptr().cast(),
+                data.len() as WORD,
+                &mut request_options,
+                reply_buffer.as_mut_ptr().cast(),
+                reply_size,
+                timeout,
+            )
+        },
+        IpAddr::V6(_) => unsafe {
+            let mut source = ip_addr_to_sockaddr_inet(IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+            let mut destination = ip_addr_to_sockaddr_inet(target);
+            *destination.Ipv6_mut().u.sin6_scope_id_mut() = scope_id.unwrap_or(0);
+            Icmp6SendEcho2(
+                handle.0,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                source.Ipv6_mut(),
+                destination.Ipv6_mut(),
+                data.as_mut_ptr().cast(),
+                data.len() as WORD,
+                &mut request_options,
+                reply_buffer.as_mut_ptr().cast(),
+                reply_size,
+                timeout,
+            )
+        },
+    };
+
+    if num_replies == 0 {
+        let error = std::io::Error::last_os_error();
+        // Codes past IP_STATUS_BASE describe the network, not a failure to send.
+        match error.raw_os_error().map(|code| code as u32) {
+            Some(code) if code > IP_STATUS_BASE && code < IP_STATUS_BASE + 1000 => {
+                return Ok(EchoReply::no_reply(code));
+            }
+            _ => return Err(error),
+        }
+    }
+
+    let reply = match target {
+        IpAddr::V4(_) => unsafe {
+            let reply = &*reply_buffer.as_ptr().cast::<ICMP_ECHO_REPLY>();
+            EchoReply {
+                status: reply.Status,
+                from: Some(IpAddr::V4(Ipv4Addr::from(reply.Address.to_ne_bytes()))),
+                rtt: Duration::from_millis(reply.RoundTripTime.into()),
+                ttl: Some(reply.Options.Ttl),
+            }
+        },
+        IpAddr::V6(_) => unsafe {
+            let reply = std::ptr::read_unaligned(reply_buffer.as_ptr().cast::<ICMPV6_ECHO_REPLY>());
+            let words = reply.Address.sin6_addr;
+            let mut octets = [0; 16];
+            for (chunk, word) in octets.chunks_mut(2).zip(words.iter()) {
+                // The words are in network byte order.
+                chunk.copy_from_slice(&word.to_ne_bytes());
+            }
+            EchoReply {
+                status: reply.Status,
+                from: Some(IpAddr::V6(Ipv6Addr::from(octets))),
+                rtt: Duration::from_millis(reply.RoundTripTime.max(0) as u64),
+                ttl: None,
+            }
+        },
+    };
+
+    Ok(reply)
+}
@@ -0,0 +1,391 @@
+use iced::{
+    canvas::{
+        Cache,
+        Cursor,
+        Frame,
+        Geometry,
+        Path,
+        Program,
+        Stroke,
+    },
+    Color,
+    Point,
+    Rectangle,
+    Size,
+};
+use log::debug;
+use std::{
+    collections::VecDeque,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use winapi::shared::{
+    netioapi::{
+        GetIfEntry2,
+        MIB_IF_ROW2,
+    },
+    winerror::NO_ERROR,
+};
+
+/// The number of throughput samples kept per interface
+const HISTORY_LEN: usize = 60;
+
+/// The smallest scale of a throughput graph, in bytes per second.
+///
+/// This keeps idle adapters from having their noise blown up to the full height of the graph.
+const MIN_GRAPH_SCALE: f64 = 1024.0;
+
+const RX_COLOR: Color = Color {
+    r: 0.2,
+    g: 0.8,
+    b: 0.2,
+    a: 1.0,
+};
+const TX_COLOR: Color = Color {
+    r: 0.9,
+    g: 0.6,
+    b: 0.1,
+    a: 1.0,
+};
+
+/// The interval at which interface statistics are sampled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleInterval(u64);
+
+impl SampleInterval {
+    /// The intervals that may be picked by the user
+    pub const ALL: &'static [Self] = &[Self(250), Self(500), Self(1000), Self(2000), Self(5000)];
+
+    /// The smallest allowed interval, in milliseconds
+    const MIN_MILLIS: u64 = 100;
+
+    /// Make a new interval from a number of milliseconds.
+    ///
+    /// This is clamped to a minimum of 100ms.
+    pub fn from_millis(millis: u64) -> Self {
+        Self(millis.max(Self::MIN_MILLIS))
+    }
+
+    /// Get this interval in milliseconds.
+    pub fn as_millis(self) -> u64 {
+        self.0
+    }
+
+    /// Get this interval as a [`Duration`].
+    pub fn as_duration(self) -> Duration {
+        Duration::from_millis(self.0)
+    }
+}
+
+impl Default for SampleInterval {
+    fn default() -> Self {
+        Self(1000)
+    }
+}
+
+impl std::fmt::Display for SampleInterval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0 % 1000 == 0 {
+            write!(f, "{}s", self.0 / 1000)
+        } else {
+            write!(f, "{}ms", self.0)
+        }
+    }
+}
+
+/// A snapshot of the counters of an interface
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IfStats {
+    pub in_octets: u64,
+    pub out_octets: u64,
+    pub in_ucast_pkts: u64,
+    pub in_nucast_pkts: u64,
+    pub out_ucast_pkts: u64,
+    pub out_nucast_pkts: u64,
+    pub in_errors: u64,
+    pub out_errors: u64,
+    pub in_discards: u64,
+    pub out_discards: u64,
+}
+
+/// Get the counters of the interface with the given luid.
+pub fn get_if_stats(luid: u64) -> std::io::Result<IfStats> {
+    let mut row: MIB_IF_ROW2 = unsafe { std::mem::zeroed() };
+    row.InterfaceLuid.Value = luid;
+
+    let code = unsafe { GetIfEntry2(&mut row) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+
+    Ok(IfStats {
+        in_octets: row.InOctets,
+        out_octets: row.OutOctets,
+        in_ucast_pkts: row.InUcastPkts,
+        in_nucast_pkts: row.InNUcastPkts,
+        out_ucast_pkts: row.OutUcastPkts,
+        out_nucast_pkts: row.OutNUcastPkts,
+        in_errors: row.InErrors,
+        out_errors: row.OutErrors,
+        in_discards: row.InDiscards,
+        out_discards: row.OutDiscards,
+    })
+}
+
+/// The largest value of the counters from `GetIfEntry2`, which are 64 bits
+pub const IF_COUNTER_MAX: u64 = u64::MAX;
+
+/// Get the increase of a counter that wraps after `max` between two samples.
+///
+/// A decrease is treated as a wrap if it is plausible, meaning the wrapped delta is less than half the counter's range.
+/// Otherwise, the counter is assumed to have been reset, like when an adapter is disabled, and `None` is returned.
+/// Only pass a `max` of `u32::MAX` for counters known to be 32 bits, as a reset would otherwise be read as a huge wrap.
+pub fn counter_delta(previous: u64, current: u64, max: u64) -> Option<u64> {
+    if current >= previous {
+        return Some(current - previous);
+    }
+
+    if previous <= max {
+        let wrapped = (max - previous) + current + 1;
+        if wrapped <= max / 2 {
+            return Some(wrapped);
+        }
+    }
+
+    None
+}
+
+/// The throughput of an interface over a sample interval
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throughput {
+    /// Received bytes per second
+    pub rx: f64,
+
+    /// Transmitted bytes per second
+    pub tx: f64,
+}
+
+/// The sampled statistics of an interface
+pub struct InterfaceStatistics {
+    last: Option<(Instant, IfStats)>,
+    last_error: Option<String>,
+    history: VecDeque<Throughput>,
+
+    cache: Cache,
+}
+
+impl InterfaceStatistics {
+    pub fn new() -> Self {
+        Self {
+            last: None,
+            last_error: None,
+            history: VecDeque::with_capacity(HISTORY_LEN),
+
+            cache: Cache::new(),
+        }
+    }
+
+    /// Sample the interface with the given luid.
+    pub fn sample(&mut self, luid: u64, now: Instant) {
+        match get_if_stats(luid) {
+            Ok(stats) => {
+                self.push(now, stats);
+                self.last_error = None;
+            }
+            Err(e) => {
+                // The adapter may have been removed, so start over when it comes back.
+                self.last = None;
+                self.last_error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Add a sample, recording the throughput since the last one.
+    pub fn push(&mut self, now: Instant, stats: IfStats) {
+        if let Some((last_time, last_stats)) = self.last {
+            let elapsed = now.duration_since(last_time).as_secs_f64();
+            let rx = counter_delta(last_stats.in_octets, stats.in_octets, IF_COUNTER_MAX);
+            let tx = counter_delta(last_stats.out_octets, stats.out_octets, IF_COUNTER_MAX);
+
+            match (rx, tx) {
+                (Some(rx), Some(tx)) if elapsed > 0.0 => {
+                    if self.history.len() == HISTORY_LEN {
+                        self.history.pop_front();
+                    }
+                    self.history.push_back(Throughput {
+                        rx: rx as f64 / elapsed,
+                        tx: tx as f64 / elapsed,
+                    });
+                    self.cache.clear();
+                }
+                (Some(_), Some(_)) => {}
+                _ => {
+                    // The counters were reset, so this interval cannot be measured.
+                    // The new values are used as the baseline for the next sample.
+                    debug!("Interface counters were reset");
+                }
+            }
+        }
+
+        self.last = Some((now, stats));
+    }
+
+    /// Get the latest counters.
+    pub fn latest(&self) -> Option<&IfStats> {
+        self.last.as_ref().map(|(_, stats)| stats)
+    }
+
+    /// Get the latest throughput.
+    pub fn latest_throughput(&self) -> Option<Throughput> {
+        self.history.back().copied()
+    }
+
+    /// Get the error from the last sample, if it failed.
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Get a graph of the throughput history.
+    pub fn graph(&self) -> ThroughputGraph<'_> {
+        ThroughputGraph {
+            history: &self.history,
+            cache: &self.cache,
+        }
+    }
+}
+
+impl Default for InterfaceStatistics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A rolling graph of rx and tx throughput
+pub struct ThroughputGraph<'a> {
+    history: &'a VecDeque<Throughput>,
+    cache: &'a Cache,
+}
+
+impl<'a, Message> Program<Message> for ThroughputGraph<'a> {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let geometry = self.cache.draw(bounds.size(), |frame| {
+            frame.fill_rectangle(
+                Point::ORIGIN,
+                frame.size(),
+                Color::from_rgb8(0x1E, 0x1E, 0x1E),
+            );
+
+            let scale = self
+                .history
+                .iter()
+                .map(|throughput| throughput.rx.max(throughput.tx))
+                .fold(MIN_GRAPH_SCALE, f64::max);
+
+            draw_line(frame, self.history.iter().map(|t| t.rx), scale, RX_COLOR);
+            draw_line(frame, self.history.iter().map(|t| t.tx), scale, TX_COLOR);
+
+            frame.fill_text(iced::canvas::Text {
+                content: format_bytes_per_sec(scale),
+                position: Point::new(4.0, 2.0),
+                color: Color::WHITE,
+                size: 12.0,
+                ..Default::default()
+            });
+        });
+
+        vec![geometry]
+    }
+}
+
+/// Draw a line of values scaled to `scale`, with the newest value at the right edge.
+fn draw_line(
+    frame: &mut Frame,
+    values: impl ExactSizeIterator<Item = f64>,
+    scale: f64,
+    color: Color,
+) {
+    let Size { width, height } = frame.size();
+    let step = width / (HISTORY_LEN - 1) as f32;
+    let offset = (HISTORY_LEN - values.len()) as f32 * step;
+
+    let path = Path::new(|builder| {
+        for (i, value) in values.enumerate() {
+            let point = Point::new(
+                offset + i as f32 * step,
+                height - (value / scale) as f32 * height,
+            );
+
+            if i == 0 {
+                builder.move_to(point);
+            } else {
+                builder.line_to(point);
+            }
+        }
+    });
+
+    frame.stroke(&path, Stroke::default().with_color(color).with_width(2.0));
+}
+
+/// Format a throughput, like "1.50 MiB/s".
+pub fn format_bytes_per_sec(bytes_per_sec: f64) -> String {
+    const UNITS: [&str; 5] = ["B/s", "KiB/s", "MiB/s", "GiB/s", "TiB/s"];
+
+    let mut value = bytes_per_sec;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.2} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const U32_MAX: u64 = u32::MAX as u64;
+
+    #[test]
+    fn normal_increase() {
+        assert_eq!(counter_delta(100, 100, IF_COUNTER_MAX), Some(0));
+        assert_eq!(counter_delta(100, 1_600, IF_COUNTER_MAX), Some(1_500));
+        assert_eq!(
+            counter_delta(U32_MAX, U32_MAX + 10, IF_COUNTER_MAX),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn wrap_32() {
+        assert_eq!(counter_delta(U32_MAX - 9, 5, U32_MAX), Some(15));
+        assert_eq!(counter_delta(U32_MAX, 0, U32_MAX), Some(1));
+    }
+
+    #[test]
+    fn wrap_64() {
+        assert_eq!(counter_delta(u64::MAX - 9, 5, IF_COUNTER_MAX), Some(15));
+    }
+
+    #[test]
+    fn reset_64() {
+        assert_eq!(counter_delta(50_000_000_000, 1_000, IF_COUNTER_MAX), None);
+        assert_eq!(counter_delta(1_000, 0, IF_COUNTER_MAX), None);
+    }
+
+    #[test]
+    fn reset_near_u32_boundary() {
+        // An adapter reset after about 4 GB of traffic is not a wrap of a 64 bit counter.
+        assert_eq!(counter_delta(U32_MAX - 1_000, 2_000, IF_COUNTER_MAX), None);
+        assert_eq!(counter_delta(U32_MAX + 1_000, 2_000, IF_COUNTER_MAX), None);
+
+        // A 32 bit counter past its range was never 32 bits.
+        assert_eq!(counter_delta(U32_MAX + 1_000, 2_000, U32_MAX), None);
+    }
+}
@@ -0,0 +1,955 @@
+use crate::{
+    adapter_addresses::AdapterAddresses,
+    dhcp::DhcpAction,
+    routing_table::{
+        add_route,
+        default_destination,
+        delete_route,
+    },
+    sockaddr::ip_addr_to_sockaddr_inet,
+    style::{
+        ForegroundGreenButtonStyle,
+        ForegroundGreenTextInputStyle,
+    },
+    subnet::SubnetInfo,
+    tcpip_interface::TcpipInterface,
+};
+use anyhow::Context;
+use iced::{
+    Align,
+    Button,
+    Checkbox,
+    Column,
+    Element,
+    Length,
+    Row,
+    Space,
+    Text,
+    TextInput,
+};
+use log::{
+    info,
+    warn,
+};
+use std::{
+    net::IpAddr,
+    str::FromStr,
+};
+use winapi::shared::{
+    netioapi::{
+        CreateUnicastIpAddressEntry,
+        DeleteUnicastIpAddressEntry,
+        InitializeUnicastIpAddressEntry,
+        MIB_UNICASTIPADDRESS_ROW,
+    },
+    nldef::{
+        IpDadStatePreferred,
+        IpPrefixOriginManual,
+        IpSuffixOriginManual,
+    },
+    winerror::{
+        ERROR_NOT_FOUND,
+        ERROR_OBJECT_ALREADY_EXISTS,
+        NO_ERROR,
+    },
+};
+
+/// An address with a prefix length, like "192.168.1.2/24"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AddressConfig {
+    pub address: IpAddr,
+    pub prefix_length: u8,
+}
+
+impl AddressConfig {
+    /// Calculate the subnet this address is in.
+    pub fn subnet(&self) -> Option<SubnetInfo> {
+        SubnetInfo::new(self.address, self.prefix_length)
+    }
+
+    /// Get the network this address is in, like "10.0.0.0/8" for "10.0.0.5/8".
+    pub fn network(&self) -> Option<Self> {
+        self.subnet().map(|subnet| Self {
+            address: subnet.network,
+            prefix_length: self.prefix_length,
+        })
+    }
+
+    /// Whether `address` is inside this subnet.
+    pub fn contains(&self, address: IpAddr) -> bool {
+        self.subnet()
+            .map_or(false, |subnet| subnet.contains(address))
+    }
+
+    /// Whether this subnet and `other` share any addresses.
+    ///
+    /// Two subnets overlap exactly when the shorter one contains the other.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let (shorter, longer) = if self.prefix_length <= other.prefix_length {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        shorter.contains(longer.address)
+    }
+}
+
+impl FromStr for AddressConfig {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (address, prefix_length) = input
+            .trim()
+            .split_once('/')
+            .context("missing prefix length, like /24")?;
+        let address: IpAddr = address.trim().parse().context("invalid address")?;
+        let prefix_length: u8 = prefix_length
+            .trim()
+            .parse()
+            .context("invalid prefix length")?;
+
+        if prefix_length > max_prefix_length(address) {
+            anyhow::bail!(
+                "prefix length must be at most {}",
+                max_prefix_length(address)
+            );
+        }
+
+        Ok(Self {
+            address,
+            prefix_length,
+        })
+    }
+}
+
+impl std::fmt::Display for AddressConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.address, self.prefix_length)
+    }
+}
+
+fn max_prefix_length(address: IpAddr) -> u8 {
+    match address {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+/// The ip configuration of an interface
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpConfig {
+    pub dhcp_enabled: bool,
+    pub addresses: Vec<AddressConfig>,
+    pub gateways: Vec<IpAddr>,
+}
+
+impl IpConfig {
+    pub fn new(adapter: &AdapterAddresses) -> Self {
+        Self {
+            dhcp_enabled: adapter.dhcp_v4_enabled,
+            addresses: adapter
+                .unicast_addresses
+                .iter()
+                .map(|address| AddressConfig {
+                    address: address.address,
+                    prefix_length: address.prefix_length,
+                })
+                .collect(),
+            gateways: adapter.gateway_addresses.clone(),
+        }
+    }
+}
+
+/// A single change to an interface's ip configuration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigChange {
+    EnableDhcp,
+    DisableDhcp,
+    RemoveAddress(AddressConfig),
+    AddAddress(AddressConfig),
+    RemoveGateway(IpAddr),
+    AddGateway(IpAddr),
+}
+
+impl ConfigChange {
+    /// Apply this change to an interface, blocking the current thread.
+    fn apply(&self, luid: u64, adapter_name: &str) -> anyhow::Result<()> {
+        match self {
+            Self::EnableDhcp => {
+                TcpipInterface::open(adapter_name)
+                    .and_then(|interface| interface.set_enable_dhcp(true))
+                    .context("failed to enable dhcp in the registry")?;
+                DhcpAction::Renew.run_blocking(adapter_name)?;
+            }
+            Self::DisableDhcp => {
+                // Give back the lease first, as the dhcp client cannot do it once disabled.
+                if let Err(e) = DhcpAction::Release.run_blocking(adapter_name) {
+                    warn!("{:?}", e);
+                }
+                TcpipInterface::open(adapter_name)
+                    .and_then(|interface| interface.set_enable_dhcp(false))
+                    .context("failed to disable dhcp in the registry")?;
+            }
+            Self::RemoveAddress(address) => {
+                let row = unicast_ip_address_row(luid, address);
+                let code = unsafe { DeleteUnicastIpAddressEntry(&row) };
+
+                // Releasing a lease may have already removed it.
+                if code != NO_ERROR && code != ERROR_NOT_FOUND {
+                    return Err(std::io::Error::from_raw_os_error(code as i32).into());
+                }
+            }
+            Self::AddAddress(address) => {
+                let row = unicast_ip_address_row(luid, address);
+                let code = unsafe { CreateUnicastIpAddressEntry(&row) };
+
+                // A lease that failed to release may have left it in place.
+                if code != NO_ERROR && code != ERROR_OBJECT_ALREADY_EXISTS {
+                    return Err(std::io::Error::from_raw_os_error(code as i32).into());
+                }
+            }
+            Self::RemoveGateway(gateway) => {
+                match delete_route(luid, &default_destination(*gateway), *gateway) {
+                    Ok(()) => {}
+                    Err(e) if e.raw_os_error() == Some(ERROR_NOT_FOUND as i32) => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            Self::AddGateway(gateway) => {
+                match add_route(luid, &default_destination(*gateway), *gateway, 0) {
+                    Ok(()) => {}
+                    Err(e) if e.raw_os_error() == Some(ERROR_OBJECT_ALREADY_EXISTS as i32) => {}
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for ConfigChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EnableDhcp => write!(f, "~ Switch to DHCP"),
+            Self::DisableDhcp => write!(f, "~ Switch to static"),
+            Self::RemoveAddress(address) => write!(f, "- Address {}", address),
+            Self::AddAddress(address) => write!(f, "+ Address {}", address),
+            Self::RemoveGateway(gateway) => write!(f, "- Gateway {}", gateway),
+            Self::AddGateway(gateway) => write!(f, "+ Gateway {}", gateway),
+        }
+    }
+}
+
+fn unicast_ip_address_row(luid: u64, address: &AddressConfig) -> MIB_UNICASTIPADDRESS_ROW {
+    let mut row: MIB_UNICASTIPADDRESS_ROW = unsafe { std::mem::zeroed() };
+    unsafe {
+        InitializeUnicastIpAddressEntry(&mut row);
+    }
+    row.InterfaceLuid.Value = luid;
+    row.Address = ip_addr_to_sockaddr_inet(address.address);
+    row.OnLinkPrefixLength = address.prefix_length;
+    row.PrefixOrigin = IpPrefixOriginManual;
+    row.SuffixOrigin = IpSuffixOriginManual;
+    row.DadState = IpDadStatePreferred;
+    row
+}
+
+/// Whether an address is ipv4 and not link local, so it can come from a lease or static config.
+fn is_routable_v4(address: IpAddr) -> bool {
+    matches!(address, IpAddr::V4(address) if !address.is_link_local())
+}
+
+/// Validate a desired config and get the changes needed to get there from the current one.
+///
+/// `other_interfaces` are the addresses of all other interfaces, which new addresses may not overlap.
+/// All problems are returned at once so they can be shown together.
+pub fn plan_changes(
+    current: &IpConfig,
+    desired: &IpConfig,
+    other_interfaces: &[AddressConfig],
+) -> Result<Vec<ConfigChange>, Vec<String>> {
+    let mut errors = Vec::new();
+    let mut changes = Vec::new();
+
+    if desired.dhcp_enabled {
+        // Static v4 settings would conflict with the lease, so they are all dropped.
+        let static_v4_addresses = current
+            .addresses
+            .iter()
+            .filter(|address| is_routable_v4(address.address));
+        let static_v4_gateways = current.gateways.iter().filter(|gateway| gateway.is_ipv4());
+
+        if !current.dhcp_enabled {
+            changes.extend(static_v4_gateways.copied().map(ConfigChange::RemoveGateway));
+            changes.extend(
+                static_v4_addresses
+                    .copied()
+                    .map(ConfigChange::RemoveAddress),
+            );
+            changes.push(ConfigChange::EnableDhcp);
+        }
+
+        let edited_v4 = desired
+            .addresses
+            .iter()
+            .chain(current.addresses.iter())
+            .filter(|address| address.address.is_ipv4())
+            .any(|address| {
+                !current.addresses.contains(address) || !desired.addresses.contains(address)
+            });
+        if current.dhcp_enabled && edited_v4 {
+            errors.push("IPv4 addresses cannot be edited while DHCP is enabled".to_string());
+        }
+    } else if current.dhcp_enabled {
+        changes.push(ConfigChange::DisableDhcp);
+    }
+
+    for (i, address) in desired.addresses.iter().enumerate() {
+        let ip = address.address;
+        if ip.is_unspecified() || ip.is_multicast() || ip.is_loopback() {
+            errors.push(format!("{} is not a usable unicast address", address));
+        }
+        if desired.addresses[..i]
+            .iter()
+            .any(|other| other.address == address.address)
+        {
+            errors.push(format!("{} is listed more than once", address.address));
+        }
+
+        if !current.addresses.contains(address) {
+            if let Some(other) = other_interfaces
+                .iter()
+                .find(|other| other.overlaps(address))
+            {
+                errors.push(format!(
+                    "{} overlaps {} on another interface",
+                    address, other
+                ));
+            }
+        }
+    }
+
+    for (i, gateway) in desired.gateways.iter().enumerate() {
+        if desired
+            .addresses
+            .iter()
+            .any(|address| address.address == *gateway)
+        {
+            errors.push(format!(
+                "Gateway {} is one of this interface's addresses",
+                gateway
+            ));
+        } else if !desired
+            .addresses
+            .iter()
+            .any(|address| address.contains(*gateway))
+        {
+            errors.push(format!(
+                "Gateway {} is not inside any of this interface's subnets",
+                gateway
+            ));
+        }
+        if desired.gateways[..i].contains(gateway) {
+            errors.push(format!("Gateway {} is listed more than once", gateway));
+        }
+    }
+
+    // Gateways depend on the addresses, so remove them first and add them last.
+    // Switching to dhcp already removed the static ones above.
+    let takes_lease = desired.dhcp_enabled && !current.dhcp_enabled;
+    if !takes_lease {
+        changes.extend(
+            current
+                .gateways
+                .iter()
+                .filter(|gateway| !desired.gateways.contains(gateway))
+                .copied()
+                .map(ConfigChange::RemoveGateway),
+        );
+        changes.extend(
+            current
+                .addresses
+                .iter()
+                .filter(|address| !desired.addresses.contains(address))
+                .copied()
+                .map(ConfigChange::RemoveAddress),
+        );
+    }
+
+    // Switching to static releases the lease, which takes its address and gateway with it,
+    // so every ipv4 address and gateway that is kept has to be added back.
+    let releases_lease = current.dhcp_enabled && !desired.dhcp_enabled;
+    changes.extend(
+        desired
+            .addresses
+            .iter()
+            .filter(|address| {
+                !current.addresses.contains(address)
+                    || (releases_lease && is_routable_v4(address.address))
+            })
+            .copied()
+            .map(ConfigChange::AddAddress),
+    );
+    changes.extend(
+        desired
+            .gateways
+            .iter()
+            .filter(|gateway| {
+                !current.gateways.contains(gateway) || (releases_lease && gateway.is_ipv4())
+            })
+            .copied()
+            .map(ConfigChange::AddGateway),
+    );
+
+    if errors.is_empty() {
+        Ok(changes)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Apply changes to an interface in order, stopping at the first failure.
+///
+/// This runs on a blocking thread, as dhcp changes may take several seconds to complete.
+pub async fn apply_changes(
+    luid: u64,
+    adapter_name: String,
+    changes: Vec<ConfigChange>,
+) -> anyhow::Result<()> {
+    tokio::task::spawn_blocking(move || {
+        for change in changes.iter() {
+            change
+                .apply(luid, &adapter_name)
+                .with_context(|| format!("failed to apply '{}'", change))?;
+            info!("Applied '{}' to '{}'", change, adapter_name);
+        }
+
+        Ok(())
+    })
+    .await
+    .context("tokio task failed to join")?
+}
+
+#[derive(Debug, Clone)]
+pub enum IpConfigMessage {
+    DhcpToggled(bool),
+    UpdateAddressInput(String),
+    AddAddress,
+    RemoveAddress(usize),
+    UpdateGatewayInput(String),
+    AddGateway,
+    RemoveGateway(usize),
+
+    Review,
+    Apply,
+    Cancel,
+    Reset,
+}
+
+/// An editor for the ip configuration of an interface
+#[derive(Debug, Clone)]
+pub struct IpConfigEditor {
+    current: IpConfig,
+    desired: IpConfig,
+
+    address_input: String,
+    gateway_input: String,
+    input_error: Option<String>,
+
+    review: Option<Result<Vec<ConfigChange>, Vec<String>>>,
+    is_applying: bool,
+    status: String,
+
+    address_input_state: iced::text_input::State,
+    add_address_button_state: iced::button::State,
+    remove_address_button_states: Vec<iced::button::State>,
+    gateway_input_state: iced::text_input::State,
+    add_gateway_button_state: iced::button::State,
+    remove_gateway_button_states: Vec<iced::button::State>,
+    review_button_state: iced::button::State,
+    apply_button_state: iced::button::State,
+    cancel_button_state: iced::button::State,
+    reset_button_state: iced::button::State,
+}
+
+impl IpConfigEditor {
+    pub fn new(current: IpConfig) -> Self {
+        Self {
+            desired: current.clone(),
+            current,
+
+            address_input: String::new(),
+            gateway_input: String::new(),
+            input_error: None,
+
+            review: None,
+            is_applying: false,
+            status: String::new(),
+
+            address_input_state: iced::text_input::State::new(),
+            add_address_button_state: iced::button::State::new(),
+            remove_address_button_states: Vec::new(),
+            gateway_input_state: iced::text_input::State::new(),
+            add_gateway_button_state: iced::button::State::new(),
+            remove_gateway_button_states: Vec::new(),
+            review_button_state: iced::button::State::new(),
+            apply_button_state: iced::button::State::new(),
+            cancel_button_state: iced::button::State::new(),
+            reset_button_state: iced::button::State::new(),
+        }
+    }
+
+    /// Get the config the interface currently has.
+    pub fn current(&self) -> &IpConfig {
+        &self.current
+    }
+
+    /// Update the config the interface currently has, like after a refresh.
+    ///
+    /// Pending edits are kept, unless there were none.
+    pub fn set_current(&mut self, current: IpConfig) {
+        if self.desired == self.current {
+            self.desired = current.clone();
+        }
+        if self.current != current {
+            self.review = None;
+        }
+        self.current = current;
+    }
+
+    pub fn update(&mut self, message: IpConfigMessage, other_interfaces: &[AddressConfig]) {
+        // Any edit invalidates the last review.
+        if !matches!(
+            message,
+            IpConfigMessage::Review | IpConfigMessage::Apply | IpConfigMessage::Cancel
+        ) {
+            self.review = None;
+        }
+
+        match message {
+            IpConfigMessage::DhcpToggled(dhcp_enabled) => {
+                self.desired.dhcp_enabled = dhcp_enabled;
+            }
+            IpConfigMessage::UpdateAddressInput(address_input) => {
+                self.address_input = address_input;
+            }
+            IpConfigMessage::AddAddress => match self.address_input.parse::<AddressConfig>() {
+                Ok(address) => {
+                    self.desired.addresses.push(address);
+                    self.address_input.clear();
+                    self.input_error = None;
+                }
+                Err(e) => {
+                    self.input_error = Some(format!("Invalid address: {:#}", e));
+                }
+            },
+            IpConfigMessage::RemoveAddress(i) => {
+                if i < self.desired.addresses.len() {
+                    self.desired.addresses.remove(i);
+                }
+            }
+            IpConfigMessage::UpdateGatewayInput(gateway_input) => {
+                self.gateway_input = gateway_input;
+            }
+            IpConfigMessage::AddGateway => match self.gateway_input.trim().parse::<IpAddr>() {
+                Ok(gateway) => {
+                    self.desired.gateways.push(gateway);
+                    self.gateway_input.clear();
+                    self.input_error = None;
+                }
+                Err(e) => {
+                    self.input_error = Some(format!("Invalid gateway: {}", e));
+                }
+            },
+            IpConfigMessage::RemoveGateway(i) => {
+                if i < self.desired.gateways.len() {
+                    self.desired.gateways.remove(i);
+                }
+            }
+            IpConfigMessage::Review => {
+                self.review = Some(plan_changes(&self.current, &self.desired, other_interfaces));
+            }
+            IpConfigMessage::Cancel => {
+                self.review = None;
+            }
+            IpConfigMessage::Reset => {
+                self.desired = self.current.clone();
+                self.input_error = None;
+            }
+            IpConfigMessage::Apply => {
+                // Handled by `start_apply`, as it needs the interface.
+            }
+        }
+    }
+
+    /// Take the reviewed changes to start applying them.
+    ///
+    /// Returns `None` if nothing was reviewed, the review failed, or changes are already being applied.
+    pub fn start_apply(&mut self) -> Option<Vec<ConfigChange>> {
+        if self.is_applying {
+            return None;
+        }
+
+        match self.review.take() {
+            Some(Ok(changes)) if !changes.is_empty() => {
+                self.is_applying = true;
+                self.status = "Applying...".to_string();
+                Some(changes)
+            }
+            review => {
+                self.review = review;
+                None
+            }
+        }
+    }
+
+    /// Record the result of applying changes.
+    pub fn finish_apply(&mut self, result: &anyhow::Result<()>) {
+        self.is_applying = false;
+        match result {
+            Ok(()) => {
+                self.status = "Applied".to_string();
+
+                // The refresh from the change notifications will fill in the new config.
+                self.desired = self.current.clone();
+            }
+            Err(e) => {
+                self.status = format!("Failed: {:#}", e);
+            }
+        }
+    }
+
+    pub fn view(&mut self) -> Element<IpConfigMessage> {
+        let mut column = Column::new().spacing(5).push(Checkbox::new(
+            self.desired.dhcp_enabled,
+            "Use DHCP (IPv4)",
+            IpConfigMessage::DhcpToggled,
+        ));
+
+        column = column.push(Text::new("Addresses").size(15));
+        self.remove_address_button_states
+            .resize(self.desired.addresses.len(), iced::button::State::new());
+        for (i, (address, button_state)) in self
+            .desired
+            .addresses
+            .iter()
+            .zip(self.remove_address_button_states.iter_mut())
+            .enumerate()
+        {
+            column = column.push(editable_row(
+                address.to_string(),
+                !self.current.addresses.contains(address),
+                button_state,
+                IpConfigMessage::RemoveAddress(i),
+            ));
+        }
+        column = column.push(input_row(
+            &mut self.address_input_state,
+            "192.168.1.2/24",
+            &self.address_input,
+            IpConfigMessage::UpdateAddressInput,
+            IpConfigMessage::AddAddress,
+            &mut self.add_address_button_state,
+        ));
+
+        column = column.push(Text::new("Gateways").size(15));
+        self.remove_gateway_button_states
+            .resize(self.desired.gateways.len(), iced::button::State::new());
+        for (i, (gateway, button_state)) in self
+            .desired
+            .gateways
+            .iter()
+            .zip(self.remove_gateway_button_states.iter_mut())
+            .enumerate()
+        {
+            column = column.push(editable_row(
+                gateway.to_string(),
+                !self.current.gateways.contains(gateway),
+                button_state,
+                IpConfigMessage::RemoveGateway(i),
+            ));
+        }
+        column = column.push(input_row(
+            &mut self.gateway_input_state,
+            "192.168.1.1",
+            &self.gateway_input,
+            IpConfigMessage::UpdateGatewayInput,
+            IpConfigMessage::AddGateway,
+            &mut self.add_gateway_button_state,
+        ));
+
+        if let Some(input_error) = self.input_error.as_deref() {
+            column = column.push(Text::new(input_error).size(15));
+        }
+
+        let mut review_button = Button::new(
+            &mut self.review_button_state,
+            Text::new("Review Changes").size(15),
+        )
+        .style(ForegroundGreenButtonStyle);
+        let mut reset_button =
+            Button::new(&mut self.reset_button_state, Text::new("Reset").size(15))
+                .style(ForegroundGreenButtonStyle);
+        if !self.is_applying {
+            review_button = review_button.on_press(IpConfigMessage::Review);
+            reset_button = reset_button.on_press(IpConfigMessage::Reset);
+        }
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(review_button)
+                .push(reset_button)
+                .push(Text::new(&self.status).size(15)),
+        );
+
+        match self.review.as_ref() {
+            Some(Ok(changes)) if changes.is_empty() => {
+                column = column.push(Text::new("No changes").size(15));
+            }
+            Some(Ok(changes)) => {
+                column = changes.iter().fold(column, |column, change| {
+                    column.push(Text::new(change.to_string()).size(15))
+                });
+                column = column.push(
+                    Row::new()
+                        .spacing(10)
+                        .push(
+                            Button::new(&mut self.apply_button_state, Text::new("Apply").size(15))
+                                .style(ForegroundGreenButtonStyle)
+                                .on_press(IpConfigMessage::Apply),
+                        )
+                        .push(
+                            Button::new(
+                                &mut self.cancel_button_state,
+                                Text::new("Cancel").size(15),
+                            )
+                            .style(ForegroundGreenButtonStyle)
+                            .on_press(IpConfigMessage::Cancel),
+                        ),
+                );
+            }
+            Some(Err(errors)) => {
+                column = errors.iter().fold(column, |column, error| {
+                    column.push(Text::new(format!("Error: {}", error)).size(15))
+                });
+            }
+            None => {}
+        }
+
+        column.into()
+    }
+}
+
+/// A value with a remove button.
+fn editable_row(
+    value: String,
+    is_new: bool,
+    button_state: &mut iced::button::State,
+    on_remove: IpConfigMessage,
+) -> Element<IpConfigMessage> {
+    let value = if is_new {
+        format!("{} (new)", value)
+    } else {
+        value
+    };
+
+    Row::new()
+        .spacing(10)
+        .align_items(Align::Center)
+        .push(Space::new(Length::Units(20), Length::Shrink))
+        .push(Text::new(value).size(15))
+        .push(
+            Button::new(button_state, Text::new("Remove").size(15))
+                .style(ForegroundGreenButtonStyle)
+                .on_press(on_remove),
+        )
+        .into()
+}
+
+/// A text input with an add button.
+fn input_row<'a>(
+    input_state: &'a mut iced::text_input::State,
+    placeholder: &str,
+    value: &str,
+    on_change: fn(String) -> IpConfigMessage,
+    on_add: IpConfigMessage,
+    button_state: &'a mut iced::button::State,
+) -> Element<'a, IpConfigMessage> {
+    Row::new()
+        .spacing(10)
+        .align_items(Align::Center)
+        .push(Space::new(Length::Units(20), Length::Shrink))
+        .push(
+            TextInput::new(input_state, placeholder, value, on_change)
+                .on_submit(on_add.clone())
+                .style(ForegroundGreenTextInputStyle)
+                .size(15)
+                .padding(2)
+                .width(Length::Units(250)),
+        )
+        .push(
+            Button::new(button_state, Text::new("Add").size(15))
+                .style(ForegroundGreenButtonStyle)
+                .on_press(on_add),
+        )
+        .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn address(input: &str) -> AddressConfig {
+        input.parse().unwrap()
+    }
+
+    fn ip(input: &str) -> IpAddr {
+        input.parse().unwrap()
+    }
+
+    fn config(dhcp_enabled: bool, addresses: &[&str], gateways: &[&str]) -> IpConfig {
+        IpConfig {
+            dhcp_enabled,
+            addresses: addresses.iter().map(|input| address(input)).collect(),
+            gateways: gateways.iter().map(|input| ip(input)).collect(),
+        }
+    }
+
+    #[test]
+    fn contains_and_overlaps() {
+        let subnet = address("192.168.1.5/24");
+        assert!(subnet.contains(ip("192.168.1.200")));
+        assert!(!subnet.contains(ip("192.168.2.1")));
+        assert!(!subnet.contains(ip("::ffff:192.168.1.1")));
+
+        assert!(subnet.overlaps(&address("192.168.0.1/16")));
+        assert!(address("192.168.0.1/16").overlaps(&subnet));
+        assert!(!subnet.overlaps(&address("192.168.2.1/24")));
+        assert!(!subnet.overlaps(&address("::/0")));
+    }
+
+    #[test]
+    fn network() {
+        assert_eq!(address("10.0.0.5/8").network(), Some(address("10.0.0.0/8")));
+        assert_eq!(address("10.0.0.0/8").network(), Some(address("10.0.0.0/8")));
+        assert_eq!(
+            address("2001:db8::1/32").network(),
+            Some(address("2001:db8::/32"))
+        );
+    }
+
+    #[test]
+    fn static_to_dhcp() {
+        let current = config(false, &["192.168.1.5/24", "fe80::1/64"], &["192.168.1.1"]);
+        let desired = IpConfig {
+            dhcp_enabled: true,
+            ..current.clone()
+        };
+
+        assert_eq!(
+            plan_changes(&current, &desired, &[]),
+            Ok(vec![
+                ConfigChange::RemoveGateway(ip("192.168.1.1")),
+                ConfigChange::RemoveAddress(address("192.168.1.5/24")),
+                ConfigChange::EnableDhcp,
+            ])
+        );
+    }
+
+    #[test]
+    fn dhcp_to_static_keeps_lease() {
+        let current = config(
+            true,
+            &["192.168.1.5/24", "169.254.3.4/16", "fe80::1/64"],
+            &["192.168.1.1"],
+        );
+        let desired = IpConfig {
+            dhcp_enabled: false,
+            ..current.clone()
+        };
+
+        // The released address and gateway are added back, but link local and ipv6 addresses are not.
+        assert_eq!(
+            plan_changes(&current, &desired, &[]),
+            Ok(vec![
+                ConfigChange::DisableDhcp,
+                ConfigChange::AddAddress(address("192.168.1.5/24")),
+                ConfigChange::AddGateway(ip("192.168.1.1")),
+            ])
+        );
+    }
+
+    #[test]
+    fn dhcp_to_static_with_edits() {
+        let current = config(true, &["192.168.1.5/24", "fe80::1/64"], &["192.168.1.1"]);
+        let desired = config(false, &["192.168.1.50/24", "fe80::1/64"], &["192.168.1.1"]);
+
+        assert_eq!(
+            plan_changes(&current, &desired, &[]),
+            Ok(vec![
+                ConfigChange::DisableDhcp,
+                ConfigChange::RemoveAddress(address("192.168.1.5/24")),
+                ConfigChange::AddAddress(address("192.168.1.50/24")),
+                ConfigChange::AddGateway(ip("192.168.1.1")),
+            ])
+        );
+    }
+
+    #[test]
+    fn editing_v4_with_dhcp_is_an_error() {
+        let current = config(true, &["192.168.1.5/24"], &["192.168.1.1"]);
+        let desired = config(true, &["192.168.1.50/24"], &["192.168.1.1"]);
+
+        assert!(plan_changes(&current, &desired, &[]).is_err());
+    }
+
+    #[test]
+    fn overlap_errors() {
+        let current = config(false, &["192.168.1.5/24"], &[]);
+        let desired = config(false, &["192.168.1.5/24", "10.1.2.3/16"], &[]);
+
+        // Existing addresses are not checked again.
+        let other_interfaces = [address("192.168.1.9/24"), address("10.1.0.1/24")];
+        assert_eq!(
+            plan_changes(&current, &desired, &other_interfaces),
+            Err(vec![
+                "10.1.2.3/16 overlaps 10.1.0.1/24 on another interface".to_string()
+            ])
+        );
+
+        assert_eq!(
+            plan_changes(&current, &desired, &[address("10.2.0.1/16")]),
+            Ok(vec![ConfigChange::AddAddress(address("10.1.2.3/16"))])
+        );
+    }
+
+    #[test]
+    fn gateway_outside_subnet() {
+        let current = config(false, &["192.168.1.5/24"], &[]);
+        let desired = config(false, &["192.168.1.5/24"], &["192.168.2.1", "192.168.1.5"]);
+
+        assert_eq!(
+            plan_changes(&current, &desired, &[]),
+            Err(vec![
+                "Gateway 192.168.2.1 is not inside any of this interface's subnets".to_string(),
+                "Gateway 192.168.1.5 is one of this interface's addresses".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn invalid_addresses() {
+        let current = config(false, &[], &[]);
+        let desired = config(false, &["0.0.0.0/8", "10.0.0.1/8", "10.0.0.1/24"], &[]);
+
+        assert_eq!(
+            plan_changes(&current, &desired, &[]),
+            Err(vec![
+                "0.0.0.0/8 is not a usable unicast address".to_string(),
+                "10.0.0.1 is listed more than once".to_string(),
+            ])
+        );
+    }
+}
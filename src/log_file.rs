@@ -0,0 +1,500 @@
+use crate::logger::LogRecord;
+use std::{
+    fs::File,
+    io::{
+        BufWriter,
+        Write,
+    },
+    path::{
+        Path,
+        PathBuf,
+    },
+    time::{
+        Duration,
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+
+/// The prefix of every log file name
+const FILE_PREFIX: &str = "hekk-";
+
+/// The format of log files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFileFormat {
+    #[default]
+    Text,
+    JsonLines,
+}
+
+impl LogFileFormat {
+    /// All log file formats
+    pub const ALL: &'static [Self] = &[Self::Text, Self::JsonLines];
+
+    /// Get the file extension for this format, without the leading dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Text => "log",
+            Self::JsonLines => "jsonl",
+        }
+    }
+
+    /// Format a record as a single line, including the line ending.
+    pub fn format_line(self, record: &LogRecord) -> String {
+        match self {
+            Self::Text => format!("{}\n", record),
+            Self::JsonLines => {
+                let value = serde_json::json!({
+                    "time": format_utc_time(record.time),
+                    "level": record.level.to_string(),
+                    "target": record.target,
+                    "message": record.message,
+                });
+                format!("{}\n", value)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for LogFileFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Text => "Plain Text",
+            Self::JsonLines => "JSON Lines",
+        };
+        s.fmt(f)
+    }
+}
+
+/// Settings for writing logs to files
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct LogFileSettings {
+    pub enabled: bool,
+    pub format: LogFileFormat,
+
+    /// Start a new file once the current one would grow past this many megabytes
+    pub max_size_mb: u64,
+
+    /// Start a new file once the current one is this many hours old
+    pub max_age_hours: u64,
+
+    /// How many files are kept, counting the current one
+    pub retention: usize,
+}
+
+impl LogFileSettings {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            format: LogFileFormat::Text,
+            max_size_mb: 10,
+            max_age_hours: 24,
+            retention: 5,
+        }
+    }
+
+    /// Get the max size in bytes.
+    ///
+    /// Hand edited settings are not validated, so a limit of 0 is raised to 1 rather than putting every record in its own file.
+    fn max_size(&self) -> u64 {
+        self.max_size_mb.max(1).saturating_mul(1024 * 1024)
+    }
+
+    /// Get the max age, raised to at least an hour like the max size.
+    fn max_age(&self) -> Duration {
+        Duration::from_secs(self.max_age_hours.max(1).saturating_mul(60 * 60))
+    }
+}
+
+impl Default for LogFileSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The file currently being written
+struct CurrentFile {
+    writer: BufWriter<File>,
+    opened: SystemTime,
+    size: u64,
+}
+
+/// Writes log records to a directory, rotating and pruning files by the settings
+///
+/// Files are named by the time they were opened, like "hekk-20210830T140312Z.log", so they sort oldest first.
+pub struct LogFileSink {
+    dir: PathBuf,
+    settings: LogFileSettings,
+    current: Option<CurrentFile>,
+
+    /// Why old files could not be deleted at the last rotation, if they could not
+    prune_error: Option<std::io::Error>,
+}
+
+impl LogFileSink {
+    /// Make a sink for a directory.
+    ///
+    /// Nothing is created until the first record is written.
+    pub fn new(dir: PathBuf, settings: LogFileSettings) -> Self {
+        Self {
+            dir,
+            settings,
+            current: None,
+
+            prune_error: None,
+        }
+    }
+
+    /// Write a record, starting a new file first if the current one is too big or too old.
+    pub fn write(&mut self, record: &LogRecord) -> std::io::Result<()> {
+        let line = self.settings.format.format_line(record);
+        let line_len = line.len() as u64;
+
+        let needs_rotation = match self.current.as_ref() {
+            Some(current) => {
+                let too_big = current.size > 0
+                    && current.size.saturating_add(line_len) > self.settings.max_size();
+                let too_old = record
+                    .time
+                    .duration_since(current.opened)
+                    .map_or(false, |age| age >= self.settings.max_age());
+                too_big || too_old
+            }
+            None => true,
+        };
+        if needs_rotation {
+            self.rotate(record.time)?;
+        }
+
+        if let Some(current) = self.current.as_mut() {
+            current.writer.write_all(line.as_bytes())?;
+            current.size += line_len;
+        }
+
+        Ok(())
+    }
+
+    /// Take the error from deleting old files, if there was one.
+    ///
+    /// This does not stop writing, as an old file may just be open in another program.
+    pub fn take_prune_error(&mut self) -> Option<std::io::Error> {
+        self.prune_error.take()
+    }
+
+    /// Flush buffered records to the current file.
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        match self.current.as_mut() {
+            Some(current) => current.writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Close the current file and open a new one named after `now`, then delete the oldest files past the retention count.
+    fn rotate(&mut self, now: SystemTime) -> std::io::Result<()> {
+        if let Some(mut current) = self.current.take() {
+            current.writer.flush()?;
+        }
+
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!(
+            "{}{}.{}",
+            FILE_PREFIX,
+            format_file_stamp(now),
+            self.settings.format.extension()
+        ));
+
+        // Rotating twice in one second reuses the same name, so append rather than truncate.
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        let size = file.metadata()?.len();
+        self.current = Some(CurrentFile {
+            writer: BufWriter::new(file),
+            opened: now,
+            size,
+        });
+
+        self.prune_error = prune_log_files(&self.dir, self.settings.retention.max(1)).err();
+
+        Ok(())
+    }
+}
+
+/// Get the log files in a directory, oldest first.
+pub fn list_log_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+
+        let file_name = entry.file_name();
+        let file_name = match file_name.to_str() {
+            Some(file_name) => file_name,
+            None => continue,
+        };
+        let is_log_file = file_name.starts_with(FILE_PREFIX)
+            && LogFileFormat::ALL
+                .iter()
+                .any(|format| file_name.ends_with(&format!(".{}", format.extension())));
+        if is_log_file {
+            paths.push(entry.path());
+        }
+    }
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Delete the oldest log files in a directory until only `retention` are left.
+///
+/// Every file is tried, returning the last error if any could not be deleted.
+fn prune_log_files(dir: &Path, retention: usize) -> std::io::Result<()> {
+    let paths = list_log_files(dir)?;
+    let num_extra = paths.len().saturating_sub(retention);
+    let mut ret = Ok(());
+    for path in paths.iter().take(num_extra) {
+        if let Err(e) = std::fs::remove_file(path) {
+            ret = Err(e);
+        }
+    }
+    ret
+}
+
+/// Split a point in time into utc date and time parts.
+///
+/// Times before the unix epoch are clamped to it.
+fn utc_parts(time: SystemTime) -> (i64, u32, u32, u64, u64, u64, u32) {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let days = (secs / 86_400) as i64;
+    let secs_of_day = secs % 86_400;
+
+    // Howard Hinnant's civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (
+        year,
+        month,
+        day,
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis(),
+    )
+}
+
+/// Format a point in time as rfc 3339 in utc, like "2021-08-30T14:03:12.345Z".
+pub fn format_utc_time(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second, millis) = utc_parts(time);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year, month, day, hour, minute, second, millis
+    )
+}
+
+/// Format a point in time for a file name, like "20210830T140312Z".
+fn format_file_stamp(time: SystemTime) -> String {
+    let (year, month, day, hour, minute, second, _millis) = utc_parts(time);
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year, month, day, hour, minute, second
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{
+        AtomicUsize,
+        Ordering,
+    };
+
+    /// A temporary directory that is deleted when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let path = std::env::temp_dir().join(format!(
+                "hekk-log-file-test-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::SeqCst)
+            ));
+            let _ = std::fs::remove_dir_all(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn at(secs: u64) -> SystemTime {
+        // 2021-08-30T00:00:00Z
+        UNIX_EPOCH + Duration::from_secs(1_630_281_600 + secs)
+    }
+
+    fn record(time: SystemTime, message: &str) -> LogRecord {
+        LogRecord {
+            time,
+            level: log::Level::Warn,
+            target: "hekk::test".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        list_log_files(dir)
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap().to_string())
+            .collect()
+    }
+
+    fn settings(format: LogFileFormat) -> LogFileSettings {
+        LogFileSettings {
+            enabled: true,
+            format,
+            ..LogFileSettings::new()
+        }
+    }
+
+    #[test]
+    fn utc_formats() {
+        assert_eq!(format_utc_time(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_utc_time(at(14 * 3_600 + 3 * 60 + 12) + Duration::from_millis(345)),
+            "2021-08-30T14:03:12.345Z"
+        );
+        // Leap day
+        assert_eq!(
+            format_utc_time(UNIX_EPOCH + Duration::from_secs(951_782_400)),
+            "2000-02-29T00:00:00.000Z"
+        );
+        assert_eq!(format_file_stamp(at(59)), "20210830T000059Z");
+    }
+
+    #[test]
+    fn json_lines() {
+        let line = LogFileFormat::JsonLines.format_line(&record(at(0), "a \"quoted\" message"));
+        assert!(line.ends_with('\n'));
+
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["time"], "2021-08-30T00:00:00.000Z");
+        assert_eq!(value["level"], "WARN");
+        assert_eq!(value["target"], "hekk::test");
+        assert_eq!(value["message"], "a \"quoted\" message");
+    }
+
+    #[test]
+    fn writes_to_one_file() {
+        let dir = TempDir::new();
+        let mut sink = LogFileSink::new(dir.0.clone(), settings(LogFileFormat::JsonLines));
+        sink.write(&record(at(0), "first")).unwrap();
+        sink.write(&record(at(1), "second")).unwrap();
+        sink.flush().unwrap();
+
+        assert_eq!(file_names(&dir.0), vec!["hekk-20210830T000000Z.jsonl"]);
+        let data = std::fs::read_to_string(dir.0.join("hekk-20210830T000000Z.jsonl")).unwrap();
+        assert_eq!(data.lines().count(), 2);
+    }
+
+    #[test]
+    fn rotates_by_age() {
+        let dir = TempDir::new();
+        let mut sink = LogFileSink::new(
+            dir.0.clone(),
+            LogFileSettings {
+                max_age_hours: 1,
+                ..settings(LogFileFormat::JsonLines)
+            },
+        );
+        sink.write(&record(at(0), "first")).unwrap();
+        sink.write(&record(at(3_599), "still first")).unwrap();
+        sink.write(&record(at(3_600), "second")).unwrap();
+        sink.flush().unwrap();
+
+        assert_eq!(
+            file_names(&dir.0),
+            vec!["hekk-20210830T000000Z.jsonl", "hekk-20210830T010000Z.jsonl"]
+        );
+    }
+
+    #[test]
+    fn rotates_by_size_and_prunes() {
+        let dir = TempDir::new();
+        let mut sink = LogFileSink::new(
+            dir.0.clone(),
+            LogFileSettings {
+                max_size_mb: 1,
+                retention: 2,
+                ..settings(LogFileFormat::JsonLines)
+            },
+        );
+
+        // Each message is over half the limit, so each gets its own file.
+        let message = "x".repeat(600 * 1024);
+        for i in 0..4 {
+            sink.write(&record(at(i), &message)).unwrap();
+        }
+        sink.flush().unwrap();
+
+        assert_eq!(
+            file_names(&dir.0),
+            vec!["hekk-20210830T000002Z.jsonl", "hekk-20210830T000003Z.jsonl"]
+        );
+        assert!(sink.take_prune_error().is_none());
+    }
+
+    #[test]
+    fn zero_limits_are_clamped() {
+        let dir = TempDir::new();
+        let mut sink = LogFileSink::new(
+            dir.0.clone(),
+            LogFileSettings {
+                max_size_mb: 0,
+                max_age_hours: 0,
+                ..settings(LogFileFormat::JsonLines)
+            },
+        );
+        for i in 0..4 {
+            sink.write(&record(at(i), "message")).unwrap();
+        }
+        sink.flush().unwrap();
+
+        assert_eq!(file_names(&dir.0), vec!["hekk-20210830T000000Z.jsonl"]);
+    }
+
+    #[test]
+    fn ignores_other_files() {
+        let dir = TempDir::new();
+        std::fs::create_dir_all(&dir.0).unwrap();
+        std::fs::write(dir.0.join("notes.txt"), "keep me").unwrap();
+
+        let mut sink = LogFileSink::new(
+            dir.0.clone(),
+            LogFileSettings {
+                retention: 1,
+                ..settings(LogFileFormat::JsonLines)
+            },
+        );
+        sink.write(&record(at(0), "message")).unwrap();
+
+        assert!(dir.0.join("notes.txt").exists());
+    }
+}
@@ -0,0 +1,235 @@
+use log::{
+    Level,
+    LevelFilter,
+};
+use std::str::FromStr;
+
+/// The filter used when none is configured: everything from hekk at info, and warnings from everything else
+pub const DEFAULT_LOG_FILTER: &str = "warn,hekk=info";
+
+/// The environment variable that overrides the saved filter
+pub const LOG_FILTER_ENV_VAR: &str = "HEKK_LOG";
+
+/// The command line flag that overrides the saved filter and the environment, like `--log hekk=debug`
+pub const LOG_FILTER_FLAG: &str = "--log";
+
+/// Which log records are kept, by target and level
+///
+/// This is parsed from a spec like "hekk=debug,netcon=trace,iced=warn".
+/// Each directive applies to targets that start with its name, with the longest name winning.
+/// A bare level like "info" applies to targets no directive matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    default: LevelFilter,
+
+    /// Directives sorted by name, so longer names come after their prefixes
+    directives: Vec<(String, LevelFilter)>,
+}
+
+impl LogFilter {
+    /// Whether a record from `target` at `level` is kept.
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        let level_filter = self
+            .directives
+            .iter()
+            .rev()
+            .find(|(name, _)| target.starts_with(name.as_str()))
+            .map_or(self.default, |(_, level_filter)| *level_filter);
+        level <= level_filter
+    }
+
+    /// Get the most verbose level any target is kept at.
+    pub fn max_level(&self) -> LevelFilter {
+        self.directives
+            .iter()
+            .map(|(_, level_filter)| *level_filter)
+            .fold(self.default, Ord::max)
+    }
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        DEFAULT_LOG_FILTER
+            .parse()
+            .expect("the default log filter is invalid")
+    }
+}
+
+impl FromStr for LogFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut default = LevelFilter::Off;
+        let mut directives: Vec<(String, LevelFilter)> = Vec::new();
+
+        for directive in input.split(',').map(str::trim) {
+            if directive.is_empty() {
+                continue;
+            }
+
+            match directive.split_once('=') {
+                Some((name, level)) => {
+                    let name = name.trim();
+                    if name.is_empty() {
+                        anyhow::bail!("'{}' is missing a target", directive);
+                    }
+                    let level: LevelFilter = level
+                        .trim()
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("invalid level in '{}'", directive))?;
+
+                    // Later directives for the same target win.
+                    directives.retain(|(existing, _)| existing != name);
+                    directives.push((name.to_string(), level));
+                }
+                None => {
+                    default = directive
+                        .parse()
+                        .map_err(|_| anyhow::anyhow!("invalid level '{}'", directive))?;
+                }
+            }
+        }
+        directives.sort();
+
+        Ok(Self {
+            default,
+            directives,
+        })
+    }
+}
+
+impl std::fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let default = self.default.to_string().to_lowercase();
+        let directives = self
+            .directives
+            .iter()
+            .map(|(name, level)| format!("{}={}", name, level.to_string().to_lowercase()));
+        let directives: Vec<String> = std::iter::once(default).chain(directives).collect();
+        write!(f, "{}", directives.join(","))
+    }
+}
+
+/// A filter spec given outside of the settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilterOverride {
+    /// Where the spec came from, like "HEKK_LOG"
+    pub source: String,
+    pub spec: String,
+}
+
+/// Get a filter spec from command line arguments, or from the environment variable if there is none there.
+pub fn log_filter_override(
+    mut args: impl Iterator<Item = String>,
+    env: Option<String>,
+) -> Option<LogFilterOverride> {
+    let flag_with_value = format!("{}=", LOG_FILTER_FLAG);
+    let mut spec = None;
+    while let Some(arg) = args.next() {
+        if arg == LOG_FILTER_FLAG {
+            spec = args.next();
+        } else if let Some(value) = arg.strip_prefix(&flag_with_value) {
+            spec = Some(value.to_string());
+        }
+    }
+
+    match spec {
+        Some(spec) => Some(LogFilterOverride {
+            source: LOG_FILTER_FLAG.to_string(),
+            spec,
+        }),
+        None => env.map(|spec| LogFilterOverride {
+            source: LOG_FILTER_ENV_VAR.to_string(),
+            spec,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> impl Iterator<Item = String> {
+        std::iter::once("hekk.exe".to_string())
+            .chain(args.iter().map(|arg| arg.to_string()))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn default_matches_hekk_only() {
+        let filter = LogFilter::default();
+        assert!(filter.enabled("hekk::adapters_info", Level::Info));
+        assert!(!filter.enabled("hekk::adapters_info", Level::Debug));
+        assert!(filter.enabled("wgpu_core::device", Level::Warn));
+        assert!(!filter.enabled("wgpu_core::device", Level::Info));
+        assert_eq!(filter.max_level(), LevelFilter::Info);
+    }
+
+    #[test]
+    fn longest_target_wins() {
+        let filter: LogFilter = "hekk=debug, netcon=trace,iced=warn,hekk::ping=error"
+            .parse()
+            .unwrap();
+        assert!(filter.enabled("hekk::settings", Level::Debug));
+        assert!(!filter.enabled("hekk::ping", Level::Warn));
+        assert!(filter.enabled("netcon::connection", Level::Trace));
+        assert!(filter.enabled("iced_wgpu", Level::Warn));
+        assert!(!filter.enabled("iced_wgpu", Level::Info));
+
+        // Without a bare level, everything else is off.
+        assert!(!filter.enabled("wgpu_core", Level::Error));
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn bare_level_and_repeats() {
+        let filter: LogFilter = "info,hekk=warn,hekk=DEBUG".parse().unwrap();
+        assert!(filter.enabled("wgpu_core", Level::Info));
+        assert!(filter.enabled("hekk", Level::Debug));
+        assert_eq!(filter.to_string(), "info,hekk=debug");
+
+        let filter: LogFilter = "".parse().unwrap();
+        assert_eq!(filter.max_level(), LevelFilter::Off);
+    }
+
+    #[test]
+    fn round_trips() {
+        let filter: LogFilter = "hekk=debug,netcon=trace,iced=warn".parse().unwrap();
+        assert_eq!(filter.to_string(), "off,hekk=debug,iced=warn,netcon=trace");
+        assert_eq!(filter.to_string().parse::<LogFilter>().unwrap(), filter);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!("hekk=loud".parse::<LogFilter>().is_err());
+        assert!("=debug".parse::<LogFilter>().is_err());
+        assert!("verbose".parse::<LogFilter>().is_err());
+    }
+
+    #[test]
+    fn overrides() {
+        assert_eq!(log_filter_override(args(&[]), None), None);
+        assert_eq!(
+            log_filter_override(args(&[]), Some("hekk=debug".to_string())),
+            Some(LogFilterOverride {
+                source: "HEKK_LOG".to_string(),
+                spec: "hekk=debug".to_string(),
+            })
+        );
+        assert_eq!(
+            log_filter_override(
+                args(&["--log", "hekk=trace"]),
+                Some("hekk=debug".to_string())
+            ),
+            Some(LogFilterOverride {
+                source: "--log".to_string(),
+                spec: "hekk=trace".to_string(),
+            })
+        );
+        assert_eq!(
+            log_filter_override(args(&["--log=iced=warn"]), None).map(|o| o.spec),
+            Some("iced=warn".to_string())
+        );
+    }
+}
@@ -1,15 +1,130 @@
+use crate::{
+    log_file::LogFileSink,
+    log_filter::LogFilter,
+};
 use once_cell::sync::Lazy;
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc,
+        Mutex,
+        RwLock,
+    },
+    time::{
+        SystemTime,
+        UNIX_EPOCH,
+    },
+};
+use winapi::{
+    shared::minwindef::FILETIME,
+    um::{
+        minwinbase::SYSTEMTIME,
+        timezoneapi::{
+            FileTimeToSystemTime,
+            SystemTimeToTzSpecificLocalTime,
+        },
+    },
+};
+
+/// The most records kept in memory for the Logs tab
+pub const LOG_BUFFER_CAPACITY: usize = 5_000;
+
+/// The `FILETIME` of the unix epoch, in 100ns intervals since 1601
+const UNIX_EPOCH_FILETIME: u64 = 116_444_736_000_000_000;
+
+/// A log message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub time: SystemTime,
+    pub level: log::Level,
+    pub target: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} [{}] {}: {}",
+            format_local_time(self.time),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+/// A bounded buffer of the most recent log records
+///
+/// Every record gets a sequence number, so readers can ask for only what they have not seen yet.
+#[derive(Debug)]
+pub struct LogBuffer {
+    records: VecDeque<(u64, LogRecord)>,
+    capacity: usize,
+    next_sequence: u64,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+            next_sequence: 0,
+        }
+    }
+
+    /// Add a record, dropping the oldest one if the buffer is full.
+    pub fn push(&mut self, record: LogRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records.push_back((self.next_sequence, record));
+        self.next_sequence += 1;
+    }
+
+    /// Get the records with a sequence number of at least `sequence`, oldest first.
+    ///
+    /// Records that were already dropped are skipped.
+    pub fn since(&self, sequence: u64) -> Vec<(u64, LogRecord)> {
+        let start = self
+            .records
+            .iter()
+            .position(|(record_sequence, _)| *record_sequence >= sequence)
+            .unwrap_or(self.records.len());
+        self.records.range(start..).cloned().collect()
+    }
+
+    /// Drop all records.
+    ///
+    /// Sequence numbers keep counting up, so readers never see an old number again.
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+}
+
+/// A message to the logger thread
+enum LoggerMessage {
+    Record(LogRecord),
+
+    /// Replace the file sink, or turn it off
+    SetFileSink(Option<LogFileSink>),
+}
 
 pub struct ThreadLogger {
-    sender: crossbeam_channel::Sender<(log::Level, String)>,
+    sender: crossbeam_channel::Sender<LoggerMessage>,
+    buffer: Arc<Mutex<LogBuffer>>,
+    filter: RwLock<LogFilter>,
 
     handle: std::thread::JoinHandle<()>,
 }
 
 impl ThreadLogger {
     pub fn new() -> Self {
-        fn process_message((level, msg): (log::Level, &str)) {
-            match level {
+        fn process_message(record: &LogRecord) {
+            match record.level {
                 log::Level::Info => {
                     print!("\x1B[96m");
                 }
@@ -26,24 +141,107 @@ impl ThreadLogger {
                     print!("\x1B[96m");
                 }
             }
-            print!("[{}] ", level);
+            print!("[{}] ", record.level);
             print!("\x1B[0m");
 
-            println!("{}", msg);
+            println!("{}", record.message);
         }
 
-        let (tx, rx) = crossbeam_channel::unbounded::<(log::Level, String)>();
-        let handle = std::thread::spawn(move || {
-            process_message((log::Level::Info, "Starting logger thread"));
+        fn internal_record(level: log::Level, message: &str) -> LogRecord {
+            LogRecord {
+                time: SystemTime::now(),
+                level,
+                target: module_path!().to_string(),
+                message: message.to_string(),
+            }
+        }
+
+        /// Print a record, write it to the file sink and add it to the buffer.
+        fn push(record: LogRecord, file_sink: &mut Option<LogFileSink>, buffer: &Mutex<LogBuffer>) {
+            process_message(&record);
+
+            // A failing sink is dropped so it does not report the same error for every record.
+            let file_error = file_sink
+                .as_mut()
+                .and_then(|sink| sink.write(&record).err());
+            let prune_error = file_sink.as_mut().and_then(LogFileSink::take_prune_error);
+
+            let mut buffer = buffer.lock().unwrap_or_else(|e| e.into_inner());
+            buffer.push(record);
 
-            for (level, msg) in rx {
-                process_message((level, msg.as_str()))
+            if let Some(e) = prune_error {
+                let record = internal_record(
+                    log::Level::Warn,
+                    &format!("Failed to delete old log files: {}", e),
+                );
+                process_message(&record);
+                buffer.push(record);
             }
 
-            process_message((log::Level::Info, "Shutting down logger thread"));
-        });
+            if let Some(e) = file_error {
+                *file_sink = None;
+                let record = internal_record(
+                    log::Level::Error,
+                    &format!("Failed to write log file, turning it off: {}", e),
+                );
+                process_message(&record);
+                buffer.push(record);
+            }
+        }
+
+        let buffer = Arc::new(Mutex::new(LogBuffer::new(LOG_BUFFER_CAPACITY)));
+        let (tx, rx) = crossbeam_channel::unbounded::<LoggerMessage>();
+        let handle = {
+            let buffer = buffer.clone();
+            std::thread::spawn(move || {
+                let mut file_sink: Option<LogFileSink> = None;
+                push(
+                    internal_record(log::Level::Info, "Starting logger thread"),
+                    &mut file_sink,
+                    &buffer,
+                );
+
+                for message in rx.iter() {
+                    match message {
+                        LoggerMessage::Record(record) => push(record, &mut file_sink, &buffer),
+                        LoggerMessage::SetFileSink(sink) => {
+                            file_sink = sink;
+                        }
+                    }
 
-        Self { sender: tx, handle }
+                    // Write to disk once caught up, so a crash loses as little as possible.
+                    if rx.is_empty() {
+                        if let Some(Err(e)) = file_sink.as_mut().map(LogFileSink::flush) {
+                            file_sink = None;
+                            push(
+                                internal_record(
+                                    log::Level::Error,
+                                    &format!("Failed to flush log file, turning it off: {}", e),
+                                ),
+                                &mut file_sink,
+                                &buffer,
+                            );
+                        }
+                    }
+                }
+
+                push(
+                    internal_record(log::Level::Info, "Shutting down logger thread"),
+                    &mut file_sink,
+                    &buffer,
+                );
+                if let Some(sink) = file_sink.as_mut() {
+                    let _ = sink.flush();
+                }
+            })
+        };
+
+        Self {
+            sender: tx,
+            buffer,
+            filter: RwLock::new(LogFilter::default()),
+            handle,
+        }
     }
 
     #[allow(dead_code)]
@@ -58,7 +256,10 @@ impl ThreadLogger {
 
 impl log::Log for ThreadLogger {
     fn enabled(&self, metadata: &log::Metadata) -> bool {
-        metadata.target().starts_with("hekk") || metadata.level() <= log::Level::Warn
+        self.filter
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .enabled(metadata.target(), metadata.level())
     }
 
     fn log(&self, record: &log::Record) {
@@ -67,7 +268,12 @@ impl log::Log for ThreadLogger {
         }
 
         self.sender
-            .send((record.level(), format!("{}", record.args())))
+            .send(LoggerMessage::Record(LogRecord {
+                time: SystemTime::now(),
+                level: record.level(),
+                target: record.target().to_string(),
+                message: format!("{}", record.args()),
+            }))
             .expect("failed to send message to logger thread");
     }
 
@@ -87,7 +293,132 @@ pub fn setup() -> anyhow::Result<()> {
         anyhow::bail!("failed to set logger: {}", e);
     }
 
-    log::set_max_level(log::LevelFilter::Info);
+    log::set_max_level(LogFilter::default().max_level());
 
     Ok(())
 }
+
+/// Replace which records are kept.
+pub fn set_filter(filter: LogFilter) {
+    log::set_max_level(filter.max_level());
+    *LOGGER.filter.write().unwrap_or_else(|e| e.into_inner()) = filter;
+}
+
+/// Replace the file sink, or turn it off with `None`.
+///
+/// The sink is written by the logger thread, so logging never blocks on disk.
+pub fn set_file_sink(sink: Option<LogFileSink>) {
+    LOGGER
+        .sender
+        .send(LoggerMessage::SetFileSink(sink))
+        .expect("failed to send message to logger thread");
+}
+
+/// Get the buffered records with a sequence number of at least `sequence`, oldest first.
+pub fn records_since(sequence: u64) -> Vec<(u64, LogRecord)> {
+    LOGGER
+        .buffer
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .since(sequence)
+}
+
+/// Drop all buffered records.
+pub fn clear_records() {
+    LOGGER
+        .buffer
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clear();
+}
+
+/// Format a point in time in the local time zone, like "2021-08-30 14:03:12.345".
+pub fn format_local_time(time: SystemTime) -> String {
+    let intervals = time
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| (duration.as_nanos() / 100) as u64)
+        + UNIX_EPOCH_FILETIME;
+    let file_time = FILETIME {
+        dwLowDateTime: intervals as u32,
+        dwHighDateTime: (intervals >> 32) as u32,
+    };
+
+    let mut utc: SYSTEMTIME = unsafe { std::mem::zeroed() };
+    let mut local: SYSTEMTIME = unsafe { std::mem::zeroed() };
+    let ok = unsafe {
+        FileTimeToSystemTime(&file_time, &mut utc) != 0
+            && SystemTimeToTzSpecificLocalTime(std::ptr::null(), &utc, &mut local) != 0
+    };
+    if !ok {
+        return "????-??-?? ??:??:??.???".to_string();
+    }
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+        local.wYear,
+        local.wMonth,
+        local.wDay,
+        local.wHour,
+        local.wMinute,
+        local.wSecond,
+        local.wMilliseconds
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(message: &str) -> LogRecord {
+        LogRecord {
+            time: UNIX_EPOCH,
+            level: log::Level::Info,
+            target: "hekk".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn messages(records: &[(u64, LogRecord)]) -> Vec<(u64, &str)> {
+        records
+            .iter()
+            .map(|(sequence, record)| (*sequence, record.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn since_skips_seen_records() {
+        let mut buffer = LogBuffer::new(10);
+        buffer.push(record("a"));
+        buffer.push(record("b"));
+        buffer.push(record("c"));
+
+        assert_eq!(
+            messages(&buffer.since(0)),
+            vec![(0, "a"), (1, "b"), (2, "c")]
+        );
+        assert_eq!(messages(&buffer.since(2)), vec![(2, "c")]);
+        assert!(buffer.since(3).is_empty());
+    }
+
+    #[test]
+    fn full_buffer_drops_oldest() {
+        let mut buffer = LogBuffer::new(2);
+        buffer.push(record("a"));
+        buffer.push(record("b"));
+        buffer.push(record("c"));
+
+        // Asking for a dropped record gets everything that is left.
+        assert_eq!(messages(&buffer.since(0)), vec![(1, "b"), (2, "c")]);
+    }
+
+    #[test]
+    fn clear_keeps_counting() {
+        let mut buffer = LogBuffer::new(10);
+        buffer.push(record("a"));
+        buffer.clear();
+        assert!(buffer.since(0).is_empty());
+
+        buffer.push(record("b"));
+        assert_eq!(messages(&buffer.since(0)), vec![(1, "b")]);
+    }
+}
@@ -0,0 +1,330 @@
+use crate::{
+    logger::{
+        clear_records,
+        format_local_time,
+        records_since,
+        LogRecord,
+        LOG_BUFFER_CAPACITY,
+    },
+    style::{
+        ForegroundGreenButtonStyle,
+        ForegroundGreenTextInputStyle,
+        HIGHLIGHT_TEXT_COLOR,
+    },
+    GreyStyle,
+};
+use iced::{
+    Align,
+    Button,
+    Checkbox,
+    Clipboard,
+    Column,
+    Command,
+    Container,
+    Element,
+    Length,
+    Row,
+    Scrollable,
+    Subscription,
+    Text,
+    TextInput,
+};
+use std::{
+    collections::{
+        BTreeSet,
+        VecDeque,
+    },
+    time::Duration,
+};
+
+/// How often new records are fetched from the logger
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The most matching records shown at once
+const MAX_SHOWN: usize = 500;
+
+/// Every level, most severe first
+const LEVELS: [log::Level; 5] = [
+    log::Level::Error,
+    log::Level::Warn,
+    log::Level::Info,
+    log::Level::Debug,
+    log::Level::Trace,
+];
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Tick,
+
+    LevelToggled(log::Level, bool),
+    UpdateSearchInput(String),
+    AutoScrollToggled(bool),
+
+    RecordToggled(u64, bool),
+    CopySelected,
+    ClearSelection,
+    Clear,
+}
+
+pub struct Logs {
+    /// Records with their sequence numbers, oldest first
+    records: VecDeque<(u64, LogRecord)>,
+    next_sequence: u64,
+
+    /// Whether each level is shown, in the order of `LEVELS`
+    shown_levels: [bool; 5],
+    search_input: String,
+    auto_scroll: bool,
+    selected: BTreeSet<u64>,
+    status: String,
+
+    search_input_state: iced::text_input::State,
+    copy_button_state: iced::button::State,
+    clear_selection_button_state: iced::button::State,
+    clear_button_state: iced::button::State,
+    scroll_state: iced::scrollable::State,
+}
+
+impl Logs {
+    pub fn new() -> Self {
+        let mut ret = Self {
+            records: VecDeque::new(),
+            next_sequence: 0,
+
+            shown_levels: [true; 5],
+            search_input: String::new(),
+            auto_scroll: true,
+            selected: BTreeSet::new(),
+            status: String::new(),
+
+            search_input_state: iced::text_input::State::new(),
+            copy_button_state: iced::button::State::new(),
+            clear_selection_button_state: iced::button::State::new(),
+            clear_button_state: iced::button::State::new(),
+            scroll_state: iced::scrollable::State::new(),
+        };
+        ret.fetch_records();
+        ret
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        iced::time::every(POLL_INTERVAL).map(|_| Message::Tick)
+    }
+
+    pub fn update(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::Tick => {
+                self.fetch_records();
+            }
+            Message::LevelToggled(level, shown) => {
+                if let Some(i) = LEVELS.iter().position(|l| *l == level) {
+                    self.shown_levels[i] = shown;
+                }
+            }
+            Message::UpdateSearchInput(search_input) => {
+                self.search_input = search_input;
+            }
+            Message::AutoScrollToggled(auto_scroll) => {
+                self.auto_scroll = auto_scroll;
+            }
+            Message::RecordToggled(sequence, selected) => {
+                if selected {
+                    self.selected.insert(sequence);
+                } else {
+                    self.selected.remove(&sequence);
+                }
+            }
+            Message::CopySelected => {
+                let text = self
+                    .records
+                    .iter()
+                    .filter(|(sequence, _)| self.selected.contains(sequence))
+                    .map(|(_, record)| record.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                clipboard.write(text);
+                self.status = match self.selected.len() {
+                    1 => "Copied 1 record".to_string(),
+                    n => format!("Copied {} records", n),
+                };
+            }
+            Message::ClearSelection => {
+                self.selected.clear();
+            }
+            Message::Clear => {
+                clear_records();
+                self.records.clear();
+                self.selected.clear();
+                self.status = String::new();
+            }
+        }
+
+        Command::none()
+    }
+
+    /// Take new records from the logger.
+    fn fetch_records(&mut self) {
+        let new_records = records_since(self.next_sequence);
+        let last_sequence = match new_records.last() {
+            Some((sequence, _)) => *sequence,
+            None => return,
+        };
+        self.next_sequence = last_sequence + 1;
+
+        self.records.extend(new_records);
+        while self.records.len() > LOG_BUFFER_CAPACITY {
+            if let Some((sequence, _)) = self.records.pop_front() {
+                self.selected.remove(&sequence);
+            }
+        }
+
+        // New records are shown at the top, so following them means going back there.
+        if self.auto_scroll {
+            self.scroll_state = iced::scrollable::State::new();
+        }
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        let mut column = Column::new().spacing(10).push(Text::new("Logs").size(36));
+
+        let level_row = LEVELS.iter().zip(self.shown_levels.iter()).fold(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(Text::new("Levels").size(15)),
+            |row, (level, shown)| {
+                let level = *level;
+                row.push(Checkbox::new(*shown, level.to_string(), move |shown| {
+                    Message::LevelToggled(level, shown)
+                }))
+            },
+        );
+        column = column.push(level_row).push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(
+                    TextInput::new(
+                        &mut self.search_input_state,
+                        "Search",
+                        &self.search_input,
+                        Message::UpdateSearchInput,
+                    )
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(300)),
+                )
+                .push(Checkbox::new(
+                    self.auto_scroll,
+                    "Auto-scroll",
+                    Message::AutoScrollToggled,
+                )),
+        );
+
+        let mut copy_button = Button::new(
+            &mut self.copy_button_state,
+            Text::new("Copy Selected").size(15),
+        )
+        .style(ForegroundGreenButtonStyle);
+        let mut clear_selection_button = Button::new(
+            &mut self.clear_selection_button_state,
+            Text::new("Clear Selection").size(15),
+        )
+        .style(ForegroundGreenButtonStyle);
+        if !self.selected.is_empty() {
+            copy_button = copy_button.on_press(Message::CopySelected);
+            clear_selection_button = clear_selection_button.on_press(Message::ClearSelection);
+        }
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(copy_button)
+                .push(clear_selection_button)
+                .push(
+                    Button::new(&mut self.clear_button_state, Text::new("Clear").size(15))
+                        .style(ForegroundGreenButtonStyle)
+                        .on_press(Message::Clear),
+                )
+                .push(Text::new(&self.status).size(15)),
+        );
+
+        // Show the newest records first.
+        let search = self.search_input.trim().to_lowercase();
+        let shown: Vec<&(u64, LogRecord)> = self
+            .records
+            .iter()
+            .rev()
+            .filter(|(_, record)| is_shown(record, &self.shown_levels, &search))
+            .collect();
+        column = column.push(
+            Text::new(if shown.len() > MAX_SHOWN {
+                format!(
+                    "Showing the newest {} of {} matching records",
+                    MAX_SHOWN,
+                    shown.len()
+                )
+            } else {
+                format!("{} matching records", shown.len())
+            })
+            .size(15),
+        );
+
+        let mut records = Column::new().spacing(2);
+        for (sequence, record) in shown.into_iter().take(MAX_SHOWN) {
+            let sequence = *sequence;
+            let mut level = Text::new(record.level.to_string())
+                .size(15)
+                .width(Length::Units(50));
+            if record.level <= log::Level::Warn {
+                level = level.color(HIGHLIGHT_TEXT_COLOR);
+            }
+
+            records = records.push(
+                Row::new()
+                    .spacing(10)
+                    .push(
+                        Checkbox::new(self.selected.contains(&sequence), "", move |selected| {
+                            Message::RecordToggled(sequence, selected)
+                        })
+                        .size(15),
+                    )
+                    .push(
+                        Text::new(format_local_time(record.time))
+                            .size(15)
+                            .width(Length::Units(180)),
+                    )
+                    .push(level)
+                    .push(Text::new(&record.target).size(15).width(Length::Units(180)))
+                    .push(Text::new(&record.message).size(15)),
+            );
+        }
+        column = column.push(records);
+
+        Container::new(
+            Scrollable::new(&mut self.scroll_state)
+                .push(Container::new(column).padding(20))
+                .width(Length::Fill),
+        )
+        .style(GreyStyle)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+/// Whether a record passes the level filters and a lowercase search.
+fn is_shown(record: &LogRecord, shown_levels: &[bool; 5], search: &str) -> bool {
+    let level_shown = LEVELS
+        .iter()
+        .position(|level| *level == record.level)
+        .map_or(true, |i| shown_levels[i]);
+    if !level_shown {
+        return false;
+    }
+
+    search.is_empty()
+        || record.message.to_lowercase().contains(search)
+        || record.target.to_lowercase().contains(search)
+}
@@ -1,4 +1,5 @@
 use crate::{
+    dhcp::DhcpAction,
     registry_adapter::RegistryAdapter,
     style::{
         ForegroundGreenTextInputStyle,
@@ -25,20 +26,32 @@ use log::{
     info,
 };
 use macaddr::MacAddr;
+use netcon::{
+    adapter_name_to_uuid,
+    NetConPropertiesSnapshot,
+};
 use std::{
+    collections::HashMap,
     sync::Arc,
     time::Instant,
 };
+use uuid::Uuid;
 
 #[derive(Debug, Clone)]
 pub enum Message {
-    Adapter(usize, AdapterMessage),
+    /// A message for the adapter with the given uuid.
+    ///
+    /// Adapters are keyed by uuid rather than position, as a refresh may reorder them while a reset or renewal is running.
+    Adapter(Uuid, AdapterMessage),
+
+    RefreshConnectionsComplete(Arc<anyhow::Result<Vec<NetConPropertiesSnapshot>>>),
 
     Nop,
 }
 
 pub struct MacSpoof {
     registry_adapters: std::io::Result<Vec<std::io::Result<Adapter>>>,
+    connections: Vec<NetConPropertiesSnapshot>,
 
     com_thread: ComThread,
 
@@ -49,6 +62,7 @@ impl MacSpoof {
     pub fn new(com_thread: ComThread) -> Self {
         let mut ret = MacSpoof {
             registry_adapters: Err(std::io::Error::from_raw_os_error(0)),
+            connections: Vec::new(),
             com_thread,
             scroll_state: iced::scrollable::State::new(),
         };
@@ -58,37 +72,105 @@ impl MacSpoof {
 
     pub fn refresh_adapters(&mut self) {
         let start = Instant::now();
+
+        // Keep the previous state of each adapter, so that the new state does not lose resets and renewals in progress, or an address being typed.
+        let mut previous: HashMap<Uuid, Adapter> =
+            std::mem::replace(&mut self.registry_adapters, Ok(Vec::new()))
+                .map(|registry_adapters| {
+                    registry_adapters
+                        .into_iter()
+                        .filter_map(Result::ok)
+                        .filter_map(|adapter| Some((adapter.get_uuid()?, adapter)))
+                        .collect()
+                })
+                .unwrap_or_default();
+
         self.registry_adapters = RegistryAdapter::get_all().map(|registry_adapters| {
             registry_adapters
                 .into_iter()
-                .map(|adapter| adapter.map(Adapter::new))
+                .map(|adapter| {
+                    adapter.map(|adapter| {
+                        let mut adapter = Adapter::new(adapter);
+                        if let Some(previous) =
+                            adapter.get_uuid().and_then(|uuid| previous.remove(&uuid))
+                        {
+                            adapter.is_resetting = previous.is_resetting;
+                            adapter.is_renewing = previous.is_renewing;
+                            adapter.harware_address_state = previous.harware_address_state;
+                            if previous.is_hardware_address_edited {
+                                adapter.hardware_address = previous.hardware_address;
+                                adapter.is_hardware_address_edited = true;
+                            }
+                        }
+                        adapter
+                    })
+                })
                 .collect()
         });
         info!("Got registry adapters in {:?}", start.elapsed());
+
+        self.update_adapter_connections();
+    }
+
+    /// Make a command that fetches the network connections from the COM thread.
+    pub fn refresh_connections_command(&self) -> Command<Message> {
+        let com_thread = self.com_thread.clone();
+        Command::perform(
+            async move { com_thread.get_network_connections().await },
+            |result| Message::RefreshConnectionsComplete(Arc::new(result)),
+        )
+    }
+
+    /// Match each registry adapter with its network connection, if it has one.
+    fn update_adapter_connections(&mut self) {
+        let connections = &self.connections;
+        if let Ok(registry_adapters) = self.registry_adapters.as_mut() {
+            for adapter in registry_adapters
+                .iter_mut()
+                .filter_map(|adapter| adapter.as_mut().ok())
+            {
+                adapter.connection = adapter.get_uuid().and_then(|uuid| {
+                    connections
+                        .iter()
+                        .find(|connection| connection.guid == uuid)
+                        .cloned()
+                });
+            }
+        }
     }
 
     pub fn update(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
         match message {
             Message::Nop => Command::none(),
-            Message::Adapter(i, message) => {
-                match self
-                    .registry_adapters
-                    .as_mut()
-                    .map(|registry_adapters| registry_adapters.get_mut(i))
-                {
-                    Ok(Some(Ok(adapter))) => adapter
-                        .update(message, &self.com_thread, clipboard)
-                        .map(move |msg| Message::Adapter(i, msg)),
-                    Ok(Some(Err(_e))) => {
-                        error!("Cannot process Adapter Message for adapter {} as it is in the error state: {:#?}", i, message);
-                        Command::none()
+            Message::RefreshConnectionsComplete(result) => {
+                match result.as_ref() {
+                    Ok(connections) => {
+                        self.connections = connections.clone();
+                        self.update_adapter_connections();
+                    }
+                    Err(e) => {
+                        error!("Failed to get network connections: {:?}", e);
                     }
+                }
+
+                Command::none()
+            }
+            Message::Adapter(uuid, message) => {
+                match self.registry_adapters.as_mut().map(|registry_adapters| {
+                    registry_adapters
+                        .iter_mut()
+                        .filter_map(|adapter| adapter.as_mut().ok())
+                        .find(|adapter| adapter.get_uuid() == Some(uuid))
+                }) {
+                    Ok(Some(adapter)) => adapter
+                        .update(message, &self.com_thread, clipboard)
+                        .map(move |msg| Message::Adapter(uuid, msg)),
                     Ok(None) => {
-                        error!("Cannot process Adapter Message for adapter {} as it does not exist: {:#?}", i, message);
+                        error!("Cannot process Adapter Message for adapter {} as it does not exist: {:#?}", uuid, message);
                         Command::none()
                     }
                     Err(_e) => {
-                        error!("`registry_adapters` is in error state. Cannot process Adapter Message for adapter {}: {:#?}", i, message);
+                        error!("`registry_adapters` is in error state. Cannot process Adapter Message for adapter {}: {:#?}", uuid, message);
                         Command::none()
                     }
                 }
@@ -103,6 +185,10 @@ impl MacSpoof {
         match self.registry_adapters.as_mut() {
             Ok(registry_adapters) => {
                 for (i, registry_adapter) in registry_adapters.iter_mut().enumerate() {
+                    let uuid = registry_adapter
+                        .as_ref()
+                        .ok()
+                        .and_then(|registry_adapter| registry_adapter.get_uuid());
                     let info: Element<_> = match registry_adapter {
                         Ok(registry_adapter) => registry_adapter.view(),
                         Err(e) => Text::new(format!("Failed to get info: {}", e))
@@ -122,10 +208,14 @@ impl MacSpoof {
                                 ),
                         );
 
-                    column = column.push(
-                        Element::<AdapterMessage>::from(row)
-                            .map(move |msg| Message::Adapter(i, msg)),
-                    );
+                    // Adapters without a uuid cannot be addressed, so their messages are dropped.
+                    column =
+                        column.push(Element::<AdapterMessage>::from(row).map(
+                            move |msg| match uuid {
+                                Some(uuid) => Message::Adapter(uuid, msg),
+                                None => Message::Nop,
+                            },
+                        ));
                 }
             }
             Err(e) => {
@@ -149,35 +239,51 @@ impl MacSpoof {
 pub enum AdapterMessage {
     UpdateHardwareAddressField(String),
     SetHardwareAddress,
-    DoneResetting(Arc<anyhow::Result<()>>),
+    DoneResetting(Arc<anyhow::Result<NetConPropertiesSnapshot>>),
+    DoneRenewing(Arc<anyhow::Result<()>>),
 
     Nop,
 }
 
 pub struct Adapter {
     registry_adapter: RegistryAdapter,
+    uuid: Option<Uuid>,
+    connection: Option<NetConPropertiesSnapshot>,
 
     hardware_address: String,
     harware_address_state: iced::text_input::State,
 
+    /// Whether `hardware_address` was typed in and not yet set
+    is_hardware_address_edited: bool,
+
     is_resetting: bool,
+    is_renewing: bool,
 }
 
 impl Adapter {
     pub fn new(registry_adapter: RegistryAdapter) -> Self {
+        let uuid = registry_adapter
+            .get_name()
+            .ok()
+            .and_then(|name| adapter_name_to_uuid(&name));
         let mut ret = Adapter {
             registry_adapter,
+            uuid,
+            connection: None,
 
             hardware_address: String::new(),
             harware_address_state: iced::text_input::State::new(),
+            is_hardware_address_edited: false,
 
             is_resetting: false,
+            is_renewing: false,
         };
         ret.refresh_mac_address();
         ret
     }
 
     pub fn refresh_mac_address(&mut self) {
+        self.is_hardware_address_edited = false;
         self.hardware_address = self
             .registry_adapter
             .get_hardware_address()
@@ -188,6 +294,13 @@ impl Adapter {
             .unwrap_or_else(|e| e.to_string());
     }
 
+    /// Get the name of this adapter as a uuid.
+    ///
+    /// Returns `None` if the name could not be retrieved or is not a guid.
+    pub fn get_uuid(&self) -> Option<Uuid> {
+        self.uuid
+    }
+
     pub fn update(
         &mut self,
         message: AdapterMessage,
@@ -197,6 +310,7 @@ impl Adapter {
         match message {
             AdapterMessage::UpdateHardwareAddressField(hardware_address) => {
                 self.hardware_address = hardware_address;
+                self.is_hardware_address_edited = true;
                 Command::none()
             }
             AdapterMessage::SetHardwareAddress => {
@@ -231,6 +345,7 @@ impl Adapter {
 
                             self.hardware_address =
                                 hardware_address.unwrap_or_else(|| "not set".into());
+                            self.is_hardware_address_edited = false;
 
                             match (
                                 self.registry_adapter.get_name(),
@@ -276,16 +391,40 @@ impl Adapter {
                 }
             }
             AdapterMessage::DoneResetting(result) => {
+                self.is_resetting = false;
+                self.refresh_mac_address();
+
                 match result.as_ref() {
-                    Ok(()) => {}
+                    Ok(connection) => {
+                        self.connection = Some(connection.clone());
+
+                        // Some dhcp servers hand out leases per MAC, so get a fresh lease for the new address.
+                        match self.registry_adapter.get_name() {
+                            Ok(name) => {
+                                self.is_renewing = true;
+                                Command::perform(DhcpAction::Renew.run(name), |result| {
+                                    AdapterMessage::DoneRenewing(Arc::new(result))
+                                })
+                            }
+                            Err(e) => {
+                                error!("Failed to get adapter name: {}", e);
+                                Command::none()
+                            }
+                        }
+                    }
                     Err(e) => {
                         // TODO: Give user visual feedback
                         error!("Failed to reset adapter: {:?}", e);
+                        Command::none()
                     }
                 }
-
-                self.is_resetting = false;
-                self.refresh_mac_address();
+            }
+            AdapterMessage::DoneRenewing(result) => {
+                if let Err(e) = result.as_ref() {
+                    // TODO: Give user visual feedback
+                    error!("Failed to renew dhcp lease: {:?}", e);
+                }
+                self.is_renewing = false;
 
                 Command::none()
             }
@@ -328,7 +467,22 @@ impl Adapter {
                 ))
                 .size(15),
             )
+            .push(
+                Text::new(match self.connection.as_ref() {
+                    Some(connection) => format!(
+                        "Connection: {} | {}",
+                        connection.name,
+                        connection.status.map_or_else(
+                            || format!("Unknown Status ({})", connection.raw_status),
+                            |status| format!("{:?}", status)
+                        )
+                    ),
+                    None => "Connection: None".to_string(),
+                })
+                .size(15),
+            )
             .push(Text::new(format!("Is Resetting: {}", self.is_resetting)).size(15))
+            .push(Text::new(format!("Is Renewing: {}", self.is_renewing)).size(15))
             .push(hardware_address);
 
         column.into()
@@ -0,0 +1,153 @@
+use anyhow::Context;
+use std::net::{
+    Ipv4Addr,
+    SocketAddr,
+    SocketAddrV4,
+    UdpSocket,
+};
+
+/// The port magic packets are usually sent to
+pub const DEFAULT_PORT: u16 = 9;
+
+/// How many times the hardware address is repeated in a magic packet
+const REPETITIONS: usize = 16;
+
+/// Build a Wake-on-LAN magic packet.
+///
+/// The packet is 6 `0xFF` bytes, then the hardware address 16 times, then the SecureOn password if there is one.
+pub fn magic_packet(hardware_address: [u8; 6], secure_on: Option<&SecureOn>) -> Vec<u8> {
+    let secure_on = secure_on.map_or(&[][..], SecureOn::as_bytes);
+    let mut packet = Vec::with_capacity(6 + (6 * REPETITIONS) + secure_on.len());
+    packet.extend_from_slice(&[0xFF; 6]);
+    for _ in 0..REPETITIONS {
+        packet.extend_from_slice(&hardware_address);
+    }
+    packet.extend_from_slice(secure_on);
+    packet
+}
+
+/// A SecureOn password, which is 4 or 6 bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecureOn {
+    Four([u8; 4]),
+    Six([u8; 6]),
+}
+
+impl SecureOn {
+    /// Parse a password written like a hardware address, "00-11-22-33-44-55", or an ipv4 address, "1.2.3.4".
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        let input = input.trim();
+        if let Ok(address) = input.parse::<Ipv4Addr>() {
+            return Ok(Self::Four(address.octets()));
+        }
+
+        let mut bytes = [0; 6];
+        let mut parts = input.split(&['-', ':'][..]);
+        for byte in bytes.iter_mut() {
+            let part = parts.next().context("password is too short")?;
+            if part.len() != 2 {
+                anyhow::bail!("invalid byte \"{}\"", part);
+            }
+            *byte = u8::from_str_radix(part, 16)
+                .with_context(|| format!("invalid byte \"{}\"", part))?;
+        }
+        if parts.next().is_some() {
+            anyhow::bail!("password is too long");
+        }
+
+        Ok(Self::Six(bytes))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Four(bytes) => bytes,
+            Self::Six(bytes) => bytes,
+        }
+    }
+}
+
+/// Broadcast a magic packet, sending it from `source` if given.
+pub fn send_magic_packet(
+    packet: &[u8],
+    destination: SocketAddrV4,
+    source: Option<Ipv4Addr>,
+) -> std::io::Result<()> {
+    let bind_address = SocketAddrV4::new(source.unwrap_or(Ipv4Addr::UNSPECIFIED), 0);
+    let socket = UdpSocket::bind(SocketAddr::V4(bind_address))?;
+    socket.set_broadcast(true)?;
+    socket.send_to(packet, SocketAddr::V4(destination))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MAC: [u8; 6] = [0x00, 0x11, 0x22, 0xAA, 0xBB, 0xCC];
+
+    #[test]
+    fn packet_layout() {
+        let packet = magic_packet(MAC, None);
+        assert_eq!(packet.len(), 102);
+        assert_eq!(packet[..6], [0xFF; 6]);
+        for chunk in packet[6..].chunks(6) {
+            assert_eq!(chunk, MAC);
+        }
+    }
+
+    #[test]
+    fn packet_with_secure_on() {
+        let secure_on = SecureOn::parse("01-02-03-04-05-06").unwrap();
+        let packet = magic_packet(MAC, Some(&secure_on));
+        assert_eq!(packet.len(), 108);
+        assert_eq!(packet[..102], magic_packet(MAC, None)[..]);
+        assert_eq!(packet[102..], [1, 2, 3, 4, 5, 6]);
+
+        let secure_on = SecureOn::parse("192.168.0.1").unwrap();
+        let packet = magic_packet(MAC, Some(&secure_on));
+        assert_eq!(packet.len(), 106);
+        assert_eq!(packet[102..], [192, 168, 0, 1]);
+    }
+
+    #[test]
+    fn packet_does_not_depend_on_ff_bytes_in_address() {
+        let mac = [0xFF; 6];
+        let packet = magic_packet(mac, None);
+        assert_eq!(packet, vec![0xFF; 102]);
+    }
+
+    #[test]
+    fn parse_secure_on() {
+        assert_eq!(
+            SecureOn::parse("aa:bb:cc:dd:ee:ff").unwrap(),
+            SecureOn::Six([0xAA, 0xBB, 0xCC, 0xDD, 0xEE, 0xFF])
+        );
+        assert_eq!(
+            SecureOn::parse(" 1.2.3.4 ").unwrap(),
+            SecureOn::Four([1, 2, 3, 4])
+        );
+
+        assert!(SecureOn::parse("").is_err());
+        assert!(SecureOn::parse("00-11-22-33-44").is_err());
+        assert!(SecureOn::parse("00-11-22-33-44-55-66").is_err());
+        assert!(SecureOn::parse("00-11-22-33-44-GG").is_err());
+        assert!(SecureOn::parse("0-11-22-33-44-55").is_err());
+        assert!(SecureOn::parse("1.2.3").is_err());
+    }
+
+    #[test]
+    fn send_to_loopback() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let destination = match receiver.local_addr().unwrap() {
+            SocketAddr::V4(address) => address,
+            SocketAddr::V6(_) => unreachable!(),
+        };
+
+        let packet = magic_packet(MAC, None);
+        send_magic_packet(&packet, destination, Some(Ipv4Addr::LOCALHOST)).unwrap();
+
+        let mut buffer = [0; 256];
+        let len = receiver.recv(&mut buffer).unwrap();
+        assert_eq!(buffer[..len], packet[..]);
+    }
+}
@@ -1,12 +1,43 @@
+mod adapter_addresses;
+mod adapter_watcher;
 mod adapters_info;
+mod arp_monitor;
+mod arp_sweep;
 mod com_thread;
+mod conflict_check;
 mod console;
+mod dhcp;
+mod dns_config;
+mod export;
+mod icmp;
+mod if_stats;
+mod ip_config;
+mod log_file;
+mod log_filter;
 mod logger;
+mod logs;
 mod mac_spoof;
+mod magic_packet;
+mod neighbor_table;
+mod oui;
+mod ping;
+mod ping_stats;
+mod port_scan;
+mod port_scanner;
 mod registry_adapter;
 mod resolve_arp;
+mod resolve_history;
+mod routing_table;
 mod settings;
+mod snapshot_diff;
+mod sockaddr;
+mod spoof_detector;
 mod style;
+mod subnet;
+mod subnet_calculator;
+mod tcpip_interface;
+mod traceroute;
+mod wake_on_lan;
 
 pub use crate::console::{
     ConsoleHandle,
@@ -16,10 +47,19 @@ pub use crate::console::{
 };
 use crate::{
     adapters_info::AdaptersInfo,
+    arp_monitor::ArpMonitor,
     com_thread::ComThread,
+    logs::Logs,
     mac_spoof::MacSpoof,
+    neighbor_table::NeighborTable,
+    ping::Ping,
+    port_scanner::PortScanner,
     resolve_arp::ResolveArp,
+    routing_table::RoutingTable,
     style::GreyStyle,
+    subnet_calculator::SubnetCalculator,
+    traceroute::Traceroute,
+    wake_on_lan::WakeOnLan,
 };
 use anyhow::Context;
 use iced::{
@@ -29,15 +69,61 @@ use iced::{
     Element,
     Length,
     Settings,
+    Subscription,
 };
 use iced_aw::TabLabel;
-use log::warn;
+use log::{
+    info,
+    warn,
+};
 use macaddr::MacAddr;
 use std::{
     convert::TryInto,
     path::PathBuf,
 };
 
+/// The tabs, in the order they are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    AdapterInfo,
+    MacSpoof,
+    ResolveArp,
+    Ping,
+    Traceroute,
+    PortScanner,
+    RoutingTable,
+    NeighborTable,
+    ArpMonitor,
+    WakeOnLan,
+    SubnetCalculator,
+    Logs,
+    Settings,
+}
+
+impl Tab {
+    /// All tabs, in declaration order
+    const ALL: [Self; 13] = [
+        Self::AdapterInfo,
+        Self::MacSpoof,
+        Self::ResolveArp,
+        Self::Ping,
+        Self::Traceroute,
+        Self::PortScanner,
+        Self::RoutingTable,
+        Self::NeighborTable,
+        Self::ArpMonitor,
+        Self::WakeOnLan,
+        Self::SubnetCalculator,
+        Self::Logs,
+        Self::Settings,
+    ];
+
+    /// Get the index of this tab in the tab bar, for switching to it from other tabs.
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
     TabSelected(usize),
@@ -45,8 +131,19 @@ pub enum Message {
     AdaptersInfo(crate::adapters_info::Message),
     MacSpoof(crate::mac_spoof::Message),
     ResolveArp(crate::resolve_arp::Message),
+    Ping(crate::ping::Message),
+    Traceroute(crate::traceroute::Message),
+    PortScanner(crate::port_scanner::Message),
+    RoutingTable(crate::routing_table::Message),
+    NeighborTable(crate::neighbor_table::Message),
+    ArpMonitor(crate::arp_monitor::Message),
+    WakeOnLan(crate::wake_on_lan::Message),
+    SubnetCalculator(crate::subnet_calculator::Message),
+    Logs(crate::logs::Message),
     Settings(crate::settings::Message),
 
+    AdaptersChanged,
+
     Nop,
 }
 
@@ -56,6 +153,15 @@ pub struct App {
     adapters_info: crate::adapters_info::AdaptersInfo,
     mac_spoof: crate::mac_spoof::MacSpoof,
     resolve_arp: crate::resolve_arp::ResolveArp,
+    ping: crate::ping::Ping,
+    traceroute: crate::traceroute::Traceroute,
+    port_scanner: crate::port_scanner::PortScanner,
+    routing_table: crate::routing_table::RoutingTable,
+    neighbor_table: crate::neighbor_table::NeighborTable,
+    arp_monitor: crate::arp_monitor::ArpMonitor,
+    wake_on_lan: crate::wake_on_lan::WakeOnLan,
+    subnet_calculator: crate::subnet_calculator::SubnetCalculator,
+    logs: crate::logs::Logs,
     settings: crate::settings::Settings,
 }
 
@@ -70,14 +176,32 @@ impl Application for App {
         let (com_thread, _com_thread_has_exited) =
             ComThread::new().expect("failed to create com thread");
 
-        let adapters_info = AdaptersInfo::new();
+        let mut adapters_info = AdaptersInfo::new();
         let mac_spoof = MacSpoof::new(com_thread);
+        let mac_spoof_command = mac_spoof.refresh_connections_command();
         let resolve_arp = ResolveArp::new();
+        let ping = Ping::new();
+        let traceroute = Traceroute::new();
+        let port_scanner = PortScanner::new();
+        let routing_table = RoutingTable::new();
+        let neighbor_table = NeighborTable::new();
+        let mut arp_monitor = ArpMonitor::new();
+        let wake_on_lan = WakeOnLan::new();
+        let subnet_calculator = SubnetCalculator::new();
+        let logs = Logs::new();
         let mut settings = crate::settings::Settings::new();
 
         // Copy settings
         settings.set_console(flags.console);
-        settings.set_debug(flags.debug);
+        settings.set_log_filter(flags.log_filter, flags.log_filter_override);
+        settings.set_stats_interval(crate::if_stats::SampleInterval::from_millis(
+            flags.stats_interval_ms,
+        ));
+        adapters_info.set_dns_presets(flags.dns_presets.clone());
+        settings.set_dns_presets(flags.dns_presets);
+        arp_monitor.set_known_neighbors(&flags.known_neighbors);
+        settings.set_known_neighbors(flags.known_neighbors);
+        settings.set_log_file(flags.log_file);
 
         (
             App {
@@ -86,9 +210,18 @@ impl Application for App {
                 adapters_info,
                 mac_spoof,
                 resolve_arp,
+                ping,
+                traceroute,
+                port_scanner,
+                routing_table,
+                neighbor_table,
+                arp_monitor,
+                wake_on_lan,
+                subnet_calculator,
+                logs,
                 settings,
             },
-            Command::none(),
+            mac_spoof_command.map(Message::MacSpoof),
         )
     }
 
@@ -102,39 +235,243 @@ impl Application for App {
                 self.active_tab = new_active_tab;
                 Command::none()
             }
+            Message::AdaptersInfo(crate::adapters_info::Message::PingGateway(gateway)) => {
+                self.active_tab = Tab::Ping.index();
+                self.ping.ping_target(gateway);
+                Command::none()
+            }
             Message::AdaptersInfo(msg) => self
                 .adapters_info
                 .update(msg, clipboard)
                 .map(Message::AdaptersInfo),
             Message::MacSpoof(msg) => self.mac_spoof.update(msg, clipboard).map(Message::MacSpoof),
+            Message::ResolveArp(crate::resolve_arp::Message::AddWakeDevice(device)) => {
+                self.wake_on_lan.add_device(device).map(Message::WakeOnLan)
+            }
+            Message::ResolveArp(crate::resolve_arp::Message::ScanPorts(hosts)) => {
+                self.active_tab = Tab::PortScanner.index();
+                self.port_scanner.set_hosts(&hosts);
+                Command::none()
+            }
             Message::ResolveArp(msg) => self
                 .resolve_arp
                 .update(msg, clipboard)
                 .map(Message::ResolveArp),
-            Message::Settings(msg) => self.settings.update(msg, clipboard).map(Message::Settings),
+            Message::Ping(msg) => self.ping.update(msg, clipboard).map(Message::Ping),
+            Message::Traceroute(msg) => self
+                .traceroute
+                .update(msg, clipboard)
+                .map(Message::Traceroute),
+            Message::PortScanner(msg) => self
+                .port_scanner
+                .update(msg, clipboard)
+                .map(Message::PortScanner),
+            Message::RoutingTable(msg) => self
+                .routing_table
+                .update(msg, clipboard)
+                .map(Message::RoutingTable),
+            Message::NeighborTable(msg) => self
+                .neighbor_table
+                .update(msg, clipboard)
+                .map(Message::NeighborTable),
+            Message::ArpMonitor(msg) => self
+                .arp_monitor
+                .update(msg, clipboard)
+                .map(Message::ArpMonitor),
+            Message::WakeOnLan(msg) => self
+                .wake_on_lan
+                .update(msg, clipboard)
+                .map(Message::WakeOnLan),
+            Message::SubnetCalculator(msg) => self
+                .subnet_calculator
+                .update(msg, clipboard)
+                .map(Message::SubnetCalculator),
+            Message::Logs(msg) => self.logs.update(msg, clipboard).map(Message::Logs),
+            Message::Settings(msg) => {
+                let command = self.settings.update(msg, clipboard).map(Message::Settings);
+
+                // Presets may be used from the adapter info tab.
+                self.adapters_info
+                    .set_dns_presets(self.settings.dns_presets().to_vec());
+                self.arp_monitor
+                    .set_known_neighbors(self.settings.known_neighbors());
+
+                command
+            }
+            Message::AdaptersChanged => {
+                info!("Adapters changed, refreshing...");
+
+                self.adapters_info.refresh_adapters_info();
+                self.mac_spoof.refresh_adapters();
+                self.routing_table.refresh_routes();
+                self.neighbor_table.refresh_neighbors();
+                self.resolve_arp.refresh_sources();
+                self.arp_monitor.refresh_gateways();
+                self.wake_on_lan.refresh_sources();
+                self.mac_spoof
+                    .refresh_connections_command()
+                    .map(Message::MacSpoof)
+            }
             Message::Nop => Command::none(),
         }
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+        Subscription::batch(vec![
+            crate::adapter_watcher::adapter_changes().map(|()| Message::AdaptersChanged),
+            self.adapters_info
+                .subscription(self.settings.stats_interval())
+                .map(Message::AdaptersInfo),
+            self.resolve_arp.subscription().map(Message::ResolveArp),
+            self.ping.subscription().map(Message::Ping),
+            self.traceroute.subscription().map(Message::Traceroute),
+            self.port_scanner.subscription().map(Message::PortScanner),
+            self.neighbor_table
+                .subscription()
+                .map(Message::NeighborTable),
+            self.arp_monitor.subscription().map(Message::ArpMonitor),
+            self.logs.subscription().map(Message::Logs),
+        ])
+    }
+
     fn view(&mut self) -> Element<Message> {
-        iced_aw::Tabs::new(self.active_tab, Message::TabSelected)
-            .push(
-                TabLabel::Text("Adapter Info".to_string()),
-                self.adapters_info.view().map(Message::AdaptersInfo),
-            )
-            .push(
-                TabLabel::Text("Spoof MAC".to_string()),
-                self.mac_spoof.view().map(Message::MacSpoof),
-            )
-            .push(
-                TabLabel::Text("Resolve ARP".to_string()),
-                self.resolve_arp.view().map(Message::ResolveArp),
-            )
-            .push(
-                TabLabel::Text("Settings".to_string()),
-                self.settings.view().map(Message::Settings),
-            )
-            .tab_bar_style(GreyStyle)
+        let App {
+            active_tab,
+            adapters_info,
+            mac_spoof,
+            resolve_arp,
+            ping,
+            traceroute,
+            port_scanner,
+            routing_table,
+            neighbor_table,
+            arp_monitor,
+            wake_on_lan,
+            subnet_calculator,
+            logs,
+            settings,
+        } = self;
+
+        // Each tab borrows its own state, so wrap the borrows to hand each one out once.
+        let mut adapters_info = Some(adapters_info);
+        let mut mac_spoof = Some(mac_spoof);
+        let mut resolve_arp = Some(resolve_arp);
+        let mut ping = Some(ping);
+        let mut traceroute = Some(traceroute);
+        let mut port_scanner = Some(port_scanner);
+        let mut routing_table = Some(routing_table);
+        let mut neighbor_table = Some(neighbor_table);
+        let mut arp_monitor = Some(arp_monitor);
+        let mut wake_on_lan = Some(wake_on_lan);
+        let mut subnet_calculator = Some(subnet_calculator);
+        let mut logs = Some(logs);
+        let mut settings = Some(settings);
+
+        let mut tabs = iced_aw::Tabs::new(*active_tab, Message::TabSelected);
+        for tab in Tab::ALL.iter().copied() {
+            const DUPLICATE: &str = "a tab appears twice in `Tab::ALL`";
+            let (label, element): (String, Element<Message>) = match tab {
+                Tab::AdapterInfo => (
+                    "Adapter Info".to_string(),
+                    adapters_info
+                        .take()
+                        .expect(DUPLICATE)
+                        .view()
+                        .map(Message::AdaptersInfo),
+                ),
+                Tab::MacSpoof => (
+                    "Spoof MAC".to_string(),
+                    mac_spoof
+                        .take()
+                        .expect(DUPLICATE)
+                        .view()
+                        .map(Message::MacSpoof),
+                ),
+                Tab::ResolveArp => (
+                    "Resolve ARP".to_string(),
+                    resolve_arp
+                        .take()
+                        .expect(DUPLICATE)
+                        .view()
+                        .map(Message::ResolveArp),
+                ),
+                Tab::Ping => (
+                    "Ping".to_string(),
+                    ping.take().expect(DUPLICATE).view().map(Message::Ping),
+                ),
+                Tab::Traceroute => (
+                    "Traceroute".to_string(),
+                    traceroute
+                        .take()
+                        .expect(DUPLICATE)
+                        .view()
+                        .map(Message::Traceroute),
+                ),
+                Tab::PortScanner => (
+                    "Port Scanner".to_string(),
+                    port_scanner
+                        .take()
+                        .expect(DUPLICATE)
+                        .view()
+                        .map(Message::PortScanner),
+                ),
+                Tab::RoutingTable => (
+                    "Routing Table".to_string(),
+                    routing_table
+                        .take()
+                        .expect(DUPLICATE)
+                        .view()
+                        .map(Message::RoutingTable),
+                ),
+                Tab::NeighborTable => (
+                    "Neighbor Table".to_string(),
+                    neighbor_table
+                        .take()
+                        .expect(DUPLICATE)
+                        .view()
+                        .map(Message::NeighborTable),
+                ),
+                Tab::ArpMonitor => {
+                    let arp_monitor = arp_monitor.take().expect(DUPLICATE);
+                    let label = match arp_monitor.num_alerts() {
+                        0 => "ARP Monitor".to_string(),
+                        num_alerts => format!("ARP Monitor ({})", num_alerts),
+                    };
+                    (label, arp_monitor.view().map(Message::ArpMonitor))
+                }
+                Tab::WakeOnLan => (
+                    "Wake-on-LAN".to_string(),
+                    wake_on_lan
+                        .take()
+                        .expect(DUPLICATE)
+                        .view()
+                        .map(Message::WakeOnLan),
+                ),
+                Tab::SubnetCalculator => (
+                    "Subnet Calculator".to_string(),
+                    subnet_calculator
+                        .take()
+                        .expect(DUPLICATE)
+                        .view()
+                        .map(Message::SubnetCalculator),
+                ),
+                Tab::Logs => (
+                    "Logs".to_string(),
+                    logs.take().expect(DUPLICATE).view().map(Message::Logs),
+                ),
+                Tab::Settings => (
+                    "Settings".to_string(),
+                    settings
+                        .take()
+                        .expect(DUPLICATE)
+                        .view()
+                        .map(Message::Settings),
+                ),
+            };
+            tabs = tabs.push(TabLabel::Text(label), element);
+        }
+
+        tabs.tab_bar_style(GreyStyle)
             // .icon_font(ICON_FONT)
             .width(Length::Fill)
             .height(Length::Fill)
@@ -176,9 +513,23 @@ fn format_mac_address_to_string(address: &[u8]) -> String {
 }
 
 #[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct UserSettings {
+    /// Only read to migrate settings saved before log filters, which had a debug toggle instead
+    #[serde(skip_serializing)]
     pub debug: bool,
     pub console: bool,
+    pub stats_interval_ms: u64,
+    pub log_filter: String,
+
+    /// A filter from the environment or command line, used instead of `log_filter` for this session
+    #[serde(skip)]
+    pub log_filter_override: Option<crate::log_filter::LogFilterOverride>,
+
+    // Tables must come after plain values in toml, so keep these last.
+    pub dns_presets: Vec<crate::dns_config::DnsPreset>,
+    pub known_neighbors: Vec<crate::spoof_detector::KnownNeighbor>,
+    pub log_file: crate::log_file::LogFileSettings,
 }
 
 impl UserSettings {
@@ -186,6 +537,12 @@ impl UserSettings {
         Self {
             debug: false,
             console: true,
+            stats_interval_ms: crate::if_stats::SampleInterval::default().as_millis(),
+            log_filter: crate::log_filter::DEFAULT_LOG_FILTER.to_string(),
+            log_filter_override: None,
+            dns_presets: crate::dns_config::DnsPreset::defaults(),
+            known_neighbors: Vec::new(),
+            log_file: crate::log_file::LogFileSettings::new(),
         }
     }
 
@@ -201,10 +558,20 @@ impl UserSettings {
         Ok(Self::data_dir()?.join("settings.toml"))
     }
 
+    pub fn log_dir() -> anyhow::Result<PathBuf> {
+        Ok(Self::data_dir()?.join("logs"))
+    }
+
     pub fn load() -> anyhow::Result<Self> {
         let path = Self::settings_path()?;
         let data = std::fs::read_to_string(path).context("failed to read data")?;
-        toml::from_str(&data).context("failed to deserialize data")
+        let mut settings: Self = toml::from_str(&data).context("failed to deserialize data")?;
+
+        if settings.debug && settings.log_filter == crate::log_filter::DEFAULT_LOG_FILTER {
+            settings.log_filter = "warn,hekk=debug".to_string();
+        }
+
+        Ok(settings)
     }
 
     pub fn save(&self) -> anyhow::Result<()> {
@@ -255,7 +622,7 @@ fn real_main() -> anyhow::Result<()> {
     crate::logger::setup().context("failed to setup logger")?;
 
     // Load settings
-    let user_settings = match UserSettings::load().context("failed to load user settings") {
+    let mut user_settings = match UserSettings::load().context("failed to load user settings") {
         Ok(settings) => settings,
         Err(e) => {
             warn!("{:?}", e);
@@ -264,6 +631,11 @@ fn real_main() -> anyhow::Result<()> {
         }
     };
 
+    user_settings.log_filter_override = crate::log_filter::log_filter_override(
+        std::env::args(),
+        std::env::var(crate::log_filter::LOG_FILTER_ENV_VAR).ok(),
+    );
+
     let mut settings = Settings::with_flags(user_settings);
     settings.window.size = (640, 480);
     App::run(settings).context("failed to run app")?;
@@ -274,3 +646,15 @@ fn real_main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tab_index_matches_tab_bar() {
+        for (i, tab) in Tab::ALL.iter().enumerate() {
+            assert_eq!(tab.index(), i);
+        }
+    }
+}
@@ -0,0 +1,767 @@
+use crate::{
+    adapter_addresses::InterfaceOption,
+    format_mac_address_to_string,
+    sockaddr::{
+        ip_addr_to_sockaddr_inet,
+        sockaddr_inet_to_ip_addr,
+    },
+    style::{
+        ForegroundGreenButtonStyle,
+        ForegroundGreenTextInputStyle,
+    },
+    GreyStyle,
+};
+use anyhow::Context;
+use iced::{
+    Align,
+    Button,
+    Checkbox,
+    Clipboard,
+    Column,
+    Command,
+    Container,
+    Element,
+    Length,
+    PickList,
+    Row,
+    Scrollable,
+    Space,
+    Subscription,
+    Text,
+    TextInput,
+};
+use log::{
+    error,
+    info,
+    warn,
+};
+use macaddr::MacAddr;
+use std::{
+    collections::HashMap,
+    net::{
+        IpAddr,
+        Ipv4Addr,
+    },
+    sync::Arc,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+use winapi::{
+    shared::{
+        netioapi::{
+            CreateIpNetEntry2,
+            DeleteIpNetEntry2,
+            FlushIpNetTable2,
+            FreeMibTable,
+            GetIpNetEntry2,
+            GetIpNetTable2,
+            ResolveIpNetEntry2,
+            MIB_IPNET_ROW2,
+            PMIB_IPNET_TABLE2,
+        },
+        nldef::{
+            NlnsDelay,
+            NlnsIncomplete,
+            NlnsPermanent,
+            NlnsProbe,
+            NlnsReachable,
+            NlnsStale,
+            NlnsUnreachable,
+        },
+        winerror::{
+            ERROR_BAD_NET_NAME,
+            ERROR_GEN_FAILURE,
+            ERROR_NOT_FOUND,
+            NO_ERROR,
+        },
+        ws2def::AF_UNSPEC,
+        ws2ipdef::SOCKADDR_INET,
+    },
+    um::iphlpapi::GetBestInterfaceEx,
+};
+
+/// How often the table is refreshed when live refresh is on
+const LIVE_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// An entry in the neighbor cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighbor {
+    pub address: IpAddr,
+    pub physical_address: Vec<u8>,
+    pub interface_luid: u64,
+    pub interface_index: u32,
+    pub state: u32,
+    pub is_router: bool,
+}
+
+impl Neighbor {
+    /// Get a human readable name for the reachability state of this entry.
+    pub fn state_name(&self) -> &'static str {
+        match self.state {
+            NlnsUnreachable => "Unreachable",
+            NlnsIncomplete => "Incomplete",
+            NlnsProbe => "Probe",
+            NlnsDelay => "Delay",
+            NlnsStale => "Stale",
+            NlnsReachable => "Reachable",
+            NlnsPermanent => "Permanent",
+            _ => "Unknown",
+        }
+    }
+
+    fn from_raw(row: &MIB_IPNET_ROW2) -> Option<Self> {
+        let physical_address_len =
+            (row.PhysicalAddressLength as usize).min(row.PhysicalAddress.len());
+
+        Some(Self {
+            address: sockaddr_inet_to_ip_addr(&row.Address)?,
+            physical_address: row.PhysicalAddress[..physical_address_len].to_vec(),
+            interface_luid: row.InterfaceLuid.Value,
+            interface_index: row.InterfaceIndex,
+            state: row.State,
+            is_router: row.s.IsRouter() != 0,
+        })
+    }
+}
+
+/// Get the ipv4 and ipv6 neighbor caches.
+pub fn get_neighbors() -> std::io::Result<Vec<Neighbor>> {
+    let mut table: PMIB_IPNET_TABLE2 = std::ptr::null_mut();
+    let code = unsafe { GetIpNetTable2(AF_UNSPEC as u16, &mut table) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+
+    let neighbors = unsafe {
+        let rows =
+            std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize);
+        let neighbors = rows.iter().filter_map(Neighbor::from_raw).collect();
+        FreeMibTable(table.cast());
+        neighbors
+    };
+
+    Ok(neighbors)
+}
+
+/// Make a neighbor row that identifies an entry.
+fn neighbor_row(luid: u64, address: IpAddr) -> MIB_IPNET_ROW2 {
+    let mut row: MIB_IPNET_ROW2 = unsafe { std::mem::zeroed() };
+    row.InterfaceLuid.Value = luid;
+    row.Address = ip_addr_to_sockaddr_inet(address);
+    row
+}
+
+/// Add a permanent entry to the neighbor cache.
+pub fn add_static_neighbor(
+    luid: u64,
+    address: IpAddr,
+    physical_address: &[u8],
+) -> std::io::Result<()> {
+    let mut row = neighbor_row(luid, address);
+    if physical_address.len() > row.PhysicalAddress.len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "hardware address is too long",
+        ));
+    }
+    row.PhysicalAddress[..physical_address.len()].copy_from_slice(physical_address);
+    row.PhysicalAddressLength = physical_address.len() as u32;
+    row.State = NlnsPermanent;
+
+    let code = unsafe { CreateIpNetEntry2(&row) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+    Ok(())
+}
+
+/// Delete an entry from the neighbor cache.
+pub fn delete_neighbor(luid: u64, address: IpAddr) -> std::io::Result<()> {
+    let row = neighbor_row(luid, address);
+    let code = unsafe { DeleteIpNetEntry2(&row) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+    Ok(())
+}
+
+/// Delete all dynamic ipv4 and ipv6 entries of an interface from the neighbor cache.
+pub fn flush_neighbors(interface_index: u32) -> std::io::Result<()> {
+    let code = unsafe { FlushIpNetTable2(AF_UNSPEC as u16, interface_index) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+    Ok(())
+}
+
+/// Get the index of the interface windows would use to reach an address.
+pub fn best_interface_index(address: IpAddr) -> std::io::Result<u32> {
+    let mut sockaddr = ip_addr_to_sockaddr_inet(address);
+    let mut index = 0;
+    let code =
+        unsafe { GetBestInterfaceEx((&mut sockaddr as *mut SOCKADDR_INET).cast(), &mut index) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+    Ok(index)
+}
+
+/// Resolve the link-layer address of a neighbor on an interface.
+///
+/// This uses the cached entry if it is reachable, otherwise it sends an arp request or neighbor solicitation and blocks until it is answered.
+pub fn resolve_neighbor(address: IpAddr, interface_index: u32) -> std::io::Result<Neighbor> {
+    let mut row: MIB_IPNET_ROW2 = unsafe { std::mem::zeroed() };
+    row.InterfaceIndex = interface_index;
+    row.Address = ip_addr_to_sockaddr_inet(address);
+    if address.is_ipv6() {
+        unsafe {
+            *row.Address.Ipv6_mut().u.sin6_scope_id_mut() = interface_index;
+        }
+    }
+
+    let code = unsafe { ResolveIpNetEntry2(&mut row, std::ptr::null()) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+
+    Neighbor::from_raw(&row).ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid neighbor address")
+    })
+}
+
+/// Send an arp probe for an address on an interface, getting the hardware address of whoever answers.
+///
+/// Following RFC 5227, the request is sent with a sender address of 0.0.0.0 so it does not update anyone's cache.
+/// Any cached entry is deleted first so the answer comes from the network.
+/// Static entries are never deleted, so an address with one cannot be probed and this returns an error.
+/// Returns `None` if nobody answered.
+pub fn probe_neighbor(address: Ipv4Addr, interface_index: u32) -> std::io::Result<Option<Vec<u8>>> {
+    let mut row: MIB_IPNET_ROW2 = unsafe { std::mem::zeroed() };
+    row.InterfaceIndex = interface_index;
+    row.Address = ip_addr_to_sockaddr_inet(address.into());
+
+    let mut cached = row;
+    let code = unsafe { GetIpNetEntry2(&mut cached) };
+    match code {
+        NO_ERROR if cached.State == NlnsPermanent => {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!(
+                    "{} has a static neighbor entry, delete it to probe the network",
+                    address
+                ),
+            ));
+        }
+        NO_ERROR => {
+            let code = unsafe { DeleteIpNetEntry2(&row) };
+            if code != NO_ERROR && code != ERROR_NOT_FOUND {
+                return Err(std::io::Error::from_raw_os_error(code as i32));
+            }
+        }
+        ERROR_NOT_FOUND => {}
+        code => return Err(std::io::Error::from_raw_os_error(code as i32)),
+    }
+
+    let source = ip_addr_to_sockaddr_inet(Ipv4Addr::UNSPECIFIED.into());
+    let code = unsafe { ResolveIpNetEntry2(&mut row, &source) };
+    match code {
+        NO_ERROR => {}
+        ERROR_BAD_NET_NAME | ERROR_GEN_FAILURE => return Ok(None),
+        code => return Err(std::io::Error::from_raw_os_error(code as i32)),
+    }
+
+    let physical_address_len = (row.PhysicalAddressLength as usize).min(row.PhysicalAddress.len());
+    let physical_address = &row.PhysicalAddress[..physical_address_len];
+    if physical_address.iter().all(|b| *b == 0) {
+        return Ok(None);
+    }
+    Ok(Some(physical_address.to_vec()))
+}
+
+/// An action on the neighbor cache
+#[derive(Debug, Clone)]
+enum NeighborAction {
+    Add {
+        luid: u64,
+        address: IpAddr,
+        physical_address: [u8; 6],
+    },
+    Delete(Neighbor),
+    Flush(InterfaceOption),
+}
+
+impl NeighborAction {
+    /// Run this action on a blocking thread.
+    async fn run(self) -> anyhow::Result<()> {
+        tokio::task::spawn_blocking(move || {
+            match &self {
+                Self::Add {
+                    luid,
+                    address,
+                    physical_address,
+                } => add_static_neighbor(*luid, *address, physical_address)
+                    .context("failed to add neighbor")?,
+                Self::Delete(neighbor) => {
+                    delete_neighbor(neighbor.interface_luid, neighbor.address)
+                        .context("failed to delete neighbor")?
+                }
+                Self::Flush(interface) => {
+                    flush_neighbors(interface.index).context("failed to flush neighbors")?
+                }
+            }
+            info!("Executed neighbor action {:?}", self);
+
+            Ok(())
+        })
+        .await
+        .context("tokio task failed to join")?
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Refresh,
+    ToggleLiveRefresh(bool),
+    Tick,
+
+    /// Delete the neighbor with this interface luid and address
+    DeleteNeighbor(u64, IpAddr),
+
+    InterfaceSelected(InterfaceOption),
+    Flush,
+
+    UpdateAddressInput(String),
+    UpdatePhysicalAddressInput(String),
+    AddNeighbor,
+
+    ActionComplete(Arc<anyhow::Result<()>>),
+}
+
+pub struct NeighborTable {
+    neighbors: Result<Vec<NeighborState>, String>,
+    interfaces: Vec<InterfaceOption>,
+
+    live_refresh: bool,
+    /// Whether the user has pressed flush once, and has to press it again to confirm
+    pending_flush: bool,
+    /// The interface luid and address of the neighbor the user has pressed delete on once
+    pending_delete: Option<(u64, IpAddr)>,
+    is_busy: bool,
+    status: String,
+
+    interface: Option<InterfaceOption>,
+    address_input: String,
+    physical_address_input: String,
+
+    scroll_state: iced::scrollable::State,
+    refresh_button_state: iced::button::State,
+    interface_state: iced::pick_list::State<InterfaceOption>,
+    flush_button_state: iced::button::State,
+    address_input_state: iced::text_input::State,
+    physical_address_input_state: iced::text_input::State,
+    add_button_state: iced::button::State,
+}
+
+impl NeighborTable {
+    pub fn new() -> Self {
+        let mut ret = Self {
+            neighbors: Ok(Vec::new()),
+            interfaces: Vec::new(),
+
+            live_refresh: false,
+            pending_flush: false,
+            pending_delete: None,
+            is_busy: false,
+            status: String::new(),
+
+            interface: None,
+            address_input: String::new(),
+            physical_address_input: String::new(),
+
+            scroll_state: iced::scrollable::State::new(),
+            refresh_button_state: iced::button::State::new(),
+            interface_state: iced::pick_list::State::default(),
+            flush_button_state: iced::button::State::new(),
+            address_input_state: iced::text_input::State::new(),
+            physical_address_input_state: iced::text_input::State::new(),
+            add_button_state: iced::button::State::new(),
+        };
+        ret.refresh_neighbors();
+        ret
+    }
+
+    pub fn refresh_neighbors(&mut self) {
+        let start = Instant::now();
+
+        // Interfaces are shown with the same names as in the adapter info tab.
+        self.interfaces = match crate::adapter_addresses::get_adapter_addresses() {
+            Ok(adapters) => adapters.iter().map(InterfaceOption::new).collect(),
+            Err(e) => {
+                warn!("Failed to get adapter addresses: {}", e);
+                Vec::new()
+            }
+        };
+        let names: HashMap<u64, &str> = self
+            .interfaces
+            .iter()
+            .map(|interface| (interface.luid, interface.name.as_str()))
+            .collect();
+
+        self.neighbors = get_neighbors()
+            .map(|mut neighbors| {
+                neighbors.sort_by(|a, b| {
+                    (a.interface_index, a.address.is_ipv6(), a.address).cmp(&(
+                        b.interface_index,
+                        b.address.is_ipv6(),
+                        b.address,
+                    ))
+                });
+                neighbors
+                    .into_iter()
+                    .map(|neighbor| {
+                        let interface_name = names.get(&neighbor.interface_luid).map_or_else(
+                            || format!("Index {}", neighbor.interface_index),
+                            |name| name.to_string(),
+                        );
+                        NeighborState::new(neighbor, interface_name)
+                    })
+                    .collect()
+            })
+            .map_err(|e| e.to_string());
+
+        info!("Got neighbors in {:?}", start.elapsed());
+    }
+
+    /// Get the subscriptions of this tab.
+    pub fn subscription(&self) -> Subscription<Message> {
+        if self.live_refresh {
+            iced::time::every(LIVE_REFRESH_INTERVAL).map(|_| Message::Tick)
+        } else {
+            Subscription::none()
+        }
+    }
+
+    fn run_action(&mut self, action: NeighborAction) -> Command<Message> {
+        if self.is_busy {
+            return Command::none();
+        }
+        self.is_busy = true;
+        self.status = "Working...".to_string();
+
+        Command::perform(action.run(), |result| {
+            Message::ActionComplete(Arc::new(result))
+        })
+    }
+
+    /// Validate the new entry inputs.
+    fn parse_new_neighbor(&self) -> anyhow::Result<NeighborAction> {
+        let interface = self.interface.as_ref().context("no interface selected")?;
+        let address: IpAddr = self
+            .address_input
+            .trim()
+            .parse()
+            .context("invalid ip address")?;
+        let physical_address = match self
+            .physical_address_input
+            .trim()
+            .parse::<MacAddr>()
+            .context("invalid hardware address")?
+        {
+            MacAddr::V6(physical_address) => physical_address.into_array(),
+            MacAddr::V8(_) => anyhow::bail!("hardware address must be 6 bytes"),
+        };
+
+        Ok(NeighborAction::Add {
+            luid: interface.luid,
+            address,
+            physical_address,
+        })
+    }
+
+    pub fn update(&mut self, message: Message, _clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::Refresh | Message::Tick => {
+                self.refresh_neighbors();
+                Command::none()
+            }
+            Message::ToggleLiveRefresh(live_refresh) => {
+                self.live_refresh = live_refresh;
+                Command::none()
+            }
+            Message::DeleteNeighbor(luid, address) => {
+                self.pending_flush = false;
+
+                // The first press arms the button, the second deletes.
+                if self.pending_delete != Some((luid, address)) {
+                    self.pending_delete = Some((luid, address));
+                    return Command::none();
+                }
+                self.pending_delete = None;
+
+                // The table may have been refreshed since the button was pressed, so look the neighbor up again.
+                let neighbor = self.neighbors.as_ref().ok().and_then(|neighbors| {
+                    neighbors.iter().find(|neighbor_state| {
+                        neighbor_state.neighbor.interface_luid == luid
+                            && neighbor_state.neighbor.address == address
+                    })
+                });
+                match neighbor {
+                    Some(neighbor_state) => {
+                        let action = NeighborAction::Delete(neighbor_state.neighbor.clone());
+                        self.run_action(action)
+                    }
+                    None => {
+                        self.status = format!("{} is no longer in the table", address);
+                        Command::none()
+                    }
+                }
+            }
+            Message::InterfaceSelected(interface) => {
+                self.interface = Some(interface);
+                self.pending_flush = false;
+                Command::none()
+            }
+            Message::Flush => {
+                let interface = match self.interface.clone() {
+                    Some(interface) => interface,
+                    None => {
+                        self.status = "No interface selected".to_string();
+                        return Command::none();
+                    }
+                };
+
+                self.pending_delete = None;
+
+                // The first press arms the button, the second flushes.
+                if !self.pending_flush {
+                    self.pending_flush = true;
+                    return Command::none();
+                }
+                self.pending_flush = false;
+
+                self.run_action(NeighborAction::Flush(interface))
+            }
+            Message::UpdateAddressInput(address_input) => {
+                self.address_input = address_input;
+                Command::none()
+            }
+            Message::UpdatePhysicalAddressInput(physical_address_input) => {
+                self.physical_address_input = physical_address_input;
+                Command::none()
+            }
+            Message::AddNeighbor => match self.parse_new_neighbor() {
+                Ok(action) => self.run_action(action),
+                Err(e) => {
+                    self.status = format!("{:#}", e);
+                    Command::none()
+                }
+            },
+            Message::ActionComplete(result) => {
+                self.is_busy = false;
+                match result.as_ref() {
+                    Ok(()) => {
+                        self.status = "Done".to_string();
+                    }
+                    Err(e) => {
+                        error!("{:?}", e);
+                        self.status = format!("{:#}", e);
+                    }
+                }
+                self.refresh_neighbors();
+                Command::none()
+            }
+        }
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        let title = Text::new("Neighbor Table").size(36);
+        let mut column = Column::new().spacing(10).push(title);
+
+        let flush_text = if self.pending_flush {
+            "Confirm Flush"
+        } else {
+            "Flush Cache"
+        };
+        let mut flush_button =
+            Button::new(&mut self.flush_button_state, Text::new(flush_text).size(15))
+                .style(ForegroundGreenButtonStyle);
+        let mut add_button =
+            Button::new(&mut self.add_button_state, Text::new("Add Static").size(15))
+                .style(ForegroundGreenButtonStyle);
+        if !self.is_busy {
+            flush_button = flush_button.on_press(Message::Flush);
+            add_button = add_button.on_press(Message::AddNeighbor);
+        }
+
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(Text::new("Interface").size(15))
+                .push(PickList::new(
+                    &mut self.interface_state,
+                    &self.interfaces[..],
+                    self.interface.clone(),
+                    Message::InterfaceSelected,
+                ))
+                .push(flush_button)
+                .push(
+                    TextInput::new(
+                        &mut self.address_input_state,
+                        "IP address",
+                        &self.address_input,
+                        Message::UpdateAddressInput,
+                    )
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(200)),
+                )
+                .push(
+                    TextInput::new(
+                        &mut self.physical_address_input_state,
+                        "Hardware address",
+                        &self.physical_address_input,
+                        Message::UpdatePhysicalAddressInput,
+                    )
+                    .on_submit(Message::AddNeighbor)
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(150)),
+                )
+                .push(add_button),
+        );
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(
+                    Button::new(
+                        &mut self.refresh_button_state,
+                        Text::new("Refresh").size(15),
+                    )
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::Refresh),
+                )
+                .push(Checkbox::new(
+                    self.live_refresh,
+                    "Live Refresh",
+                    Message::ToggleLiveRefresh,
+                ))
+                .push(Text::new(&self.status).size(15)),
+        );
+
+        match self.neighbors.as_mut() {
+            Ok(neighbors) => {
+                let is_busy = self.is_busy;
+                let pending_delete = self.pending_delete;
+                for neighbor_state in neighbors.iter_mut() {
+                    let is_pending_delete = pending_delete
+                        == Some((
+                            neighbor_state.neighbor.interface_luid,
+                            neighbor_state.neighbor.address,
+                        ));
+                    column = column.push(neighbor_state.view(is_busy, is_pending_delete));
+                }
+            }
+            Err(e) => {
+                column = column.push(Text::new(format!("Failed to get neighbors: {}", e)));
+            }
+        }
+
+        Container::new(
+            Scrollable::new(&mut self.scroll_state)
+                .push(Container::new(column).padding(20))
+                .width(Length::Fill),
+        )
+        .style(GreyStyle)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+struct NeighborState {
+    neighbor: Neighbor,
+    interface_name: String,
+    physical_address: String,
+
+    delete_button_state: iced::button::State,
+}
+
+impl NeighborState {
+    fn new(neighbor: Neighbor, interface_name: String) -> Self {
+        let physical_address = if neighbor.physical_address.is_empty() {
+            String::new()
+        } else {
+            let formatted = format_mac_address_to_string(&neighbor.physical_address);
+            match crate::oui::vendor_name(&neighbor.physical_address) {
+                Some(vendor) => format!("{} ({})", formatted.trim_end(), vendor),
+                None => formatted.trim_end().to_string(),
+            }
+        };
+
+        Self {
+            neighbor,
+            interface_name,
+            physical_address,
+
+            delete_button_state: iced::button::State::new(),
+        }
+    }
+
+    fn view(&mut self, is_busy: bool, is_pending_delete: bool) -> Element<Message> {
+        let neighbor = &self.neighbor;
+        let state = if neighbor.is_router {
+            format!("{} (Router)", neighbor.state_name())
+        } else {
+            neighbor.state_name().to_string()
+        };
+
+        let delete_text = if is_pending_delete {
+            "Confirm Delete"
+        } else {
+            "Delete"
+        };
+        let mut delete_button = Button::new(
+            &mut self.delete_button_state,
+            Text::new(delete_text).size(15),
+        )
+        .style(ForegroundGreenButtonStyle);
+        if !is_busy {
+            delete_button = delete_button.on_press(Message::DeleteNeighbor(
+                neighbor.interface_luid,
+                neighbor.address,
+            ));
+        }
+
+        Row::new()
+            .push(Space::new(Length::Units(20), Length::Shrink))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(
+                        Text::new(neighbor.address.to_string())
+                            .size(15)
+                            .width(Length::Units(250)),
+                    )
+                    .push(
+                        Text::new(&self.physical_address)
+                            .size(15)
+                            .width(Length::Units(300)),
+                    )
+                    .push(
+                        Text::new(&self.interface_name)
+                            .size(15)
+                            .width(Length::Units(200)),
+                    )
+                    .push(Text::new(state).size(15).width(Length::Units(150)))
+                    .push(delete_button),
+            )
+            .into()
+    }
+}
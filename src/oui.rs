@@ -0,0 +1,149 @@
+use crate::UserSettings;
+use anyhow::Context;
+use log::{
+    info,
+    warn,
+};
+use once_cell::sync::Lazy;
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+};
+
+/// The name of the optional IEEE OUI listing in the data dir
+///
+/// It can be downloaded from <https://standards-oui.ieee.org/oui/oui.txt>.
+const OUI_FILE_NAME: &str = "oui.txt";
+
+/// Vendors that are common on local networks, used when there is no OUI listing or it lacks an entry.
+const BUILTIN_VENDORS: &[([u8; 3], &str)] = &[
+    ([0x00, 0x00, 0x0C], "Cisco"),
+    ([0x00, 0x03, 0x93], "Apple"),
+    ([0x00, 0x04, 0x4B], "NVIDIA"),
+    ([0x00, 0x05, 0x5D], "D-Link"),
+    ([0x00, 0x05, 0x69], "VMware"),
+    ([0x00, 0x08, 0x9B], "QNAP"),
+    ([0x00, 0x09, 0x5B], "Netgear"),
+    ([0x00, 0x0C, 0x29], "VMware"),
+    ([0x00, 0x0D, 0x3A], "Microsoft"),
+    ([0x00, 0x0D, 0x88], "D-Link"),
+    ([0x00, 0x0D, 0x93], "Apple"),
+    ([0x00, 0x0E, 0xC6], "ASIX Electronics"),
+    ([0x00, 0x0F, 0xB5], "Netgear"),
+    ([0x00, 0x10, 0x18], "Broadcom"),
+    ([0x00, 0x11, 0x32], "Synology"),
+    ([0x00, 0x12, 0xFB], "Samsung"),
+    ([0x00, 0x14, 0x22], "Dell"),
+    ([0x00, 0x14, 0x51], "Apple"),
+    ([0x00, 0x15, 0x5D], "Microsoft (Hyper-V)"),
+    ([0x00, 0x15, 0x6D], "Ubiquiti"),
+    ([0x00, 0x16, 0x3E], "Xen"),
+    ([0x00, 0x17, 0x88], "Philips Lighting"),
+    ([0x00, 0x1B, 0x21], "Intel"),
+    ([0x00, 0x1C, 0x42], "Parallels"),
+    ([0x00, 0x1C, 0xC0], "Intel"),
+    ([0x00, 0x1C, 0xF0], "D-Link"),
+    ([0x00, 0x1D, 0x0F], "TP-Link"),
+    ([0x00, 0x1E, 0x67], "Intel"),
+    ([0x00, 0x1F, 0x33], "Netgear"),
+    ([0x00, 0x22, 0x48], "Microsoft"),
+    ([0x00, 0x24, 0x01], "D-Link"),
+    ([0x00, 0x24, 0xD7], "Intel"),
+    ([0x00, 0x25, 0x00], "Apple"),
+    ([0x00, 0x26, 0xBB], "Apple"),
+    ([0x00, 0x27, 0x22], "Ubiquiti"),
+    ([0x00, 0x40, 0x96], "Cisco"),
+    ([0x00, 0x50, 0x56], "VMware"),
+    ([0x00, 0x50, 0xB6], "Good Way Technology"),
+    ([0x00, 0x50, 0xF2], "Microsoft"),
+    ([0x00, 0x90, 0x4C], "Broadcom"),
+    ([0x00, 0xE0, 0x4C], "Realtek"),
+    ([0x04, 0x18, 0xD6], "Ubiquiti"),
+    ([0x08, 0x00, 0x27], "VirtualBox"),
+    ([0x14, 0xCC, 0x20], "TP-Link"),
+    ([0x18, 0xB4, 0x30], "Nest Labs"),
+    ([0x24, 0x5E, 0xBE], "QNAP"),
+    ([0x24, 0xA4, 0x3C], "Ubiquiti"),
+    ([0x28, 0x18, 0x78], "Microsoft"),
+    ([0x28, 0xCF, 0xE9], "Apple"),
+    ([0x50, 0xC7, 0xBF], "TP-Link"),
+    ([0x52, 0x54, 0x00], "QEMU"),
+    ([0x5C, 0x0A, 0x5B], "Samsung"),
+    ([0x74, 0xDA, 0x38], "Edimax"),
+    ([0x7C, 0x1E, 0x52], "Microsoft"),
+    ([0xB4, 0xFB, 0xE4], "Ubiquiti"),
+    ([0xB8, 0x27, 0xEB], "Raspberry Pi"),
+    ([0xDC, 0xA6, 0x32], "Raspberry Pi"),
+    ([0xE4, 0x5F, 0x01], "Raspberry Pi"),
+];
+
+static VENDORS: Lazy<HashMap<[u8; 3], String>> = Lazy::new(|| {
+    let mut vendors = match load_oui_file() {
+        Ok(vendors) => vendors,
+        Err(e) => {
+            info!("Not using an OUI listing: {:?}", e);
+            HashMap::new()
+        }
+    };
+
+    for (oui, vendor) in BUILTIN_VENDORS.iter() {
+        vendors.entry(*oui).or_insert_with(|| vendor.to_string());
+    }
+
+    vendors
+});
+
+/// Load the IEEE OUI listing from the data dir.
+fn load_oui_file() -> anyhow::Result<HashMap<[u8; 3], String>> {
+    let path = UserSettings::data_dir()?.join(OUI_FILE_NAME);
+    let data = std::fs::read_to_string(&path)
+        .with_context(|| format!("failed to read \"{}\"", path.display()))?;
+
+    let mut vendors = HashMap::new();
+    for line in data.lines() {
+        // Entries look like "00-00-0C   (hex)		Cisco Systems, Inc".
+        let (oui, vendor) = match line.split_once("(hex)") {
+            Some(entry) => entry,
+            None => continue,
+        };
+        match parse_oui(oui.trim()) {
+            Some(oui) => {
+                vendors.insert(oui, vendor.trim().to_string());
+            }
+            None => warn!("Invalid OUI entry \"{}\"", line),
+        }
+    }
+
+    Ok(vendors)
+}
+
+fn parse_oui(oui: &str) -> Option<[u8; 3]> {
+    let mut ret = [0; 3];
+    let mut parts = oui.split('-');
+    for byte in ret.iter_mut() {
+        *byte = u8::from_str_radix(parts.next()?, 16).ok()?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(ret)
+}
+
+/// Get the vendor of a hardware address from its OUI, the first 3 bytes.
+///
+/// Multicast and locally administered addresses have no vendor, so they are described as such instead.
+pub fn vendor_name(address: &[u8]) -> Option<&'static str> {
+    let oui: [u8; 3] = address.get(..3)?.try_into().ok()?;
+    if let Some(vendor) = VENDORS.get(&oui) {
+        return Some(vendor);
+    }
+
+    if oui[0] & 0x01 != 0 {
+        Some("Multicast")
+    } else if oui[0] & 0x02 != 0 {
+        Some("Locally Administered")
+    } else {
+        None
+    }
+}
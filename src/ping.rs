@@ -0,0 +1,670 @@
+use crate::{
+    icmp::{
+        send_echo,
+        EchoOptions,
+        EchoReply,
+    },
+    ping_stats::PingStats,
+    resolve_arp::parse_scoped_address,
+    style::{
+        ForegroundGreenButtonStyle,
+        ForegroundGreenTextInputStyle,
+        HIGHLIGHT_TEXT_COLOR,
+    },
+    GreyStyle,
+};
+use anyhow::Context;
+use iced::{
+    canvas::{
+        Cache,
+        Cursor,
+        Frame,
+        Geometry,
+        Path,
+        Program,
+        Stroke,
+    },
+    futures::{
+        stream::BoxStream,
+        Stream,
+        StreamExt,
+    },
+    Align,
+    Button,
+    Canvas,
+    Clipboard,
+    Color,
+    Column,
+    Command,
+    Container,
+    Element,
+    Length,
+    Point,
+    Rectangle,
+    Row,
+    Scrollable,
+    Size,
+    Space,
+    Subscription,
+    Text,
+    TextInput,
+};
+use log::info;
+use std::{
+    collections::VecDeque,
+    hash::Hash,
+    net::IpAddr,
+    sync::Arc,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+const DEFAULT_SIZE: u16 = 32;
+const DEFAULT_TTL: u8 = 128;
+const DEFAULT_INTERVAL_MS: u64 = 1000;
+const DEFAULT_COUNT: u64 = 4;
+
+/// The shortest allowed interval between requests, in milliseconds
+const MIN_INTERVAL_MS: u64 = 100;
+
+/// How long to wait for each reply, the same as the windows ping
+const TIMEOUT: Duration = Duration::from_secs(4);
+
+/// The most reply rows that are shown
+const MAX_ROWS: usize = 100;
+
+/// The number of samples shown in the latency chart
+const CHART_LEN: usize = 60;
+
+const CHART_HEIGHT: u16 = 120;
+
+/// The smallest scale of the latency chart, so that fast links are not blown up to the full height
+const MIN_CHART_SCALE: Duration = Duration::from_millis(10);
+
+const RTT_COLOR: Color = Color {
+    r: 0.2,
+    g: 0.8,
+    b: 0.2,
+    a: 1.0,
+};
+const LOSS_COLOR: Color = Color {
+    r: 0.9,
+    g: 0.2,
+    b: 0.2,
+    a: 1.0,
+};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    UpdateTarget(String),
+    UpdateSize(String),
+    UpdateTtl(String),
+    UpdateInterval(String),
+    UpdateCount(String),
+    Start,
+    Stop,
+    Ping(u64, PingEvent),
+}
+
+/// The settings of a ping
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PingConfig {
+    pub target: IpAddr,
+    pub scope_id: Option<u32>,
+    pub options: EchoOptions,
+    pub interval: Duration,
+
+    /// The number of requests to send, or `None` to ping until stopped
+    pub count: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub enum PingEvent {
+    Reply {
+        sequence: u64,
+        result: Result<EchoReply, String>,
+    },
+    Finished,
+}
+
+/// Send echo requests every interval, yielding each result and then [`PingEvent::Finished`].
+///
+/// Requests are sent one at a time, so a request that takes longer than the interval delays the next one.
+pub fn ping(config: PingConfig) -> impl Stream<Item = PingEvent> {
+    let config = Arc::new(config);
+    iced::futures::stream::unfold((0, Instant::now()), move |(sequence, next_send)| {
+        let config = config.clone();
+        async move {
+            if config.count.map_or(false, |count| sequence >= count) {
+                return None;
+            }
+
+            tokio::time::sleep_until(next_send.into()).await;
+
+            let PingConfig {
+                target,
+                scope_id,
+                options,
+                ..
+            } = *config;
+            let result = tokio::task::spawn_blocking(move || {
+                send_echo(target, scope_id, options).map_err(|e| e.to_string())
+            })
+            .await
+            .unwrap_or_else(|e| Err(format!("tokio task failed to join: {}", e)));
+
+            let next_send = (next_send + config.interval).max(Instant::now());
+            Some((
+                PingEvent::Reply { sequence, result },
+                (sequence + 1, next_send),
+            ))
+        }
+    })
+    .chain(iced::futures::stream::once(async { PingEvent::Finished }))
+}
+
+/// Get a subscription that pings with `config`, tagging events with `id`.
+///
+/// Changing `id` starts a new ping, and dropping the subscription stops it.
+pub fn ping_subscription(id: u64, config: Arc<PingConfig>) -> Subscription<(u64, PingEvent)> {
+    Subscription::from_recipe(PingRecipe { id, config })
+}
+
+struct PingRecipe {
+    id: u64,
+    config: Arc<PingConfig>,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for PingRecipe
+where
+    H: std::hash::Hasher,
+{
+    type Output = (u64, PingEvent);
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+        self.id.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let id = self.id;
+        ping((*self.config).clone())
+            .map(move |event| (id, event))
+            .boxed()
+    }
+}
+
+/// A running ping
+struct RunningPing {
+    id: u64,
+    config: Arc<PingConfig>,
+}
+
+/// The outcome of a single request
+struct ReplyRow {
+    sequence: u64,
+    text: String,
+    is_success: bool,
+}
+
+pub struct Ping {
+    target_input: String,
+    size_input: String,
+    ttl_input: String,
+    interval_input: String,
+    count_input: String,
+
+    running: Option<RunningPing>,
+    next_id: u64,
+
+    /// The target of the last ping, for the stats heading
+    target: String,
+    stats: PingStats,
+    /// The round trip times of the latest requests, `None` for losses
+    samples: VecDeque<Option<Duration>>,
+    rows: VecDeque<ReplyRow>,
+    status: String,
+
+    chart_cache: Cache,
+
+    target_input_state: iced::text_input::State,
+    size_input_state: iced::text_input::State,
+    ttl_input_state: iced::text_input::State,
+    interval_input_state: iced::text_input::State,
+    count_input_state: iced::text_input::State,
+    start_button_state: iced::button::State,
+    scroll_state: iced::scrollable::State,
+}
+
+impl Ping {
+    pub fn new() -> Self {
+        Self {
+            target_input: String::new(),
+            size_input: DEFAULT_SIZE.to_string(),
+            ttl_input: DEFAULT_TTL.to_string(),
+            interval_input: DEFAULT_INTERVAL_MS.to_string(),
+            count_input: DEFAULT_COUNT.to_string(),
+
+            running: None,
+            next_id: 0,
+
+            target: String::new(),
+            stats: PingStats::new(),
+            samples: VecDeque::with_capacity(CHART_LEN),
+            rows: VecDeque::new(),
+            status: String::new(),
+
+            chart_cache: Cache::new(),
+
+            target_input_state: iced::text_input::State::new(),
+            size_input_state: iced::text_input::State::new(),
+            ttl_input_state: iced::text_input::State::new(),
+            interval_input_state: iced::text_input::State::new(),
+            count_input_state: iced::text_input::State::new(),
+            start_button_state: iced::button::State::new(),
+            scroll_state: iced::scrollable::State::new(),
+        }
+    }
+
+    /// Start pinging `target`, an address with an optional zone index, like "fe80::1%12".
+    pub fn ping_target(&mut self, target: String) {
+        self.target_input = target;
+        self.start();
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        match self.running.as_ref() {
+            Some(running) => ping_subscription(running.id, running.config.clone())
+                .map(|(id, event)| Message::Ping(id, event)),
+            None => Subscription::none(),
+        }
+    }
+
+    /// Validate the inputs.
+    fn parse_config(&self) -> anyhow::Result<PingConfig> {
+        let (target, scope_id) =
+            parse_scoped_address(&self.target_input).context("invalid target")?;
+        let size: u16 = self.size_input.trim().parse().context("invalid size")?;
+        let ttl: u8 = self.ttl_input.trim().parse().context("invalid TTL")?;
+        if ttl == 0 {
+            anyhow::bail!("TTL must be at least 1");
+        }
+        let interval_ms: u64 = self
+            .interval_input
+            .trim()
+            .parse()
+            .context("invalid interval")?;
+        if interval_ms < MIN_INTERVAL_MS {
+            anyhow::bail!("interval must be at least {} ms", MIN_INTERVAL_MS);
+        }
+        let count: u64 = self.count_input.trim().parse().context("invalid count")?;
+
+        Ok(PingConfig {
+            target,
+            scope_id,
+            options: EchoOptions {
+                size,
+                ttl,
+                timeout: TIMEOUT,
+            },
+            interval: Duration::from_millis(interval_ms),
+            count: if count == 0 { None } else { Some(count) },
+        })
+    }
+
+    fn start(&mut self) {
+        match self.parse_config() {
+            Ok(config) => {
+                info!("Pinging {}", self.target_input.trim());
+                self.target = self.target_input.trim().to_string();
+                self.stats = PingStats::new();
+                self.samples.clear();
+                self.rows.clear();
+                self.status = String::new();
+                self.chart_cache.clear();
+                self.running = Some(RunningPing {
+                    id: self.next_id,
+                    config: Arc::new(config),
+                });
+                self.next_id += 1;
+            }
+            Err(e) => {
+                self.status = format!("{:#}", e);
+            }
+        }
+    }
+
+    fn push_reply(&mut self, sequence: u64, result: Result<EchoReply, String>) {
+        let (rtt, text) = match result {
+            Ok(reply) if reply.is_success() => {
+                let from = reply
+                    .from
+                    .map_or_else(|| self.target.clone(), |from| from.to_string());
+                let ttl = reply
+                    .ttl
+                    .map_or_else(String::new, |ttl| format!(" TTL={}", ttl));
+                (
+                    Some(reply.rtt),
+                    format!(
+                        "Reply from {}: time={} ms{}",
+                        from,
+                        reply.rtt.as_millis(),
+                        ttl
+                    ),
+                )
+            }
+            Ok(reply) => match reply.from {
+                Some(from) => (None, format!("{} (from {})", reply.status_name(), from)),
+                None => (None, reply.status_name()),
+            },
+            Err(e) => (None, format!("Error: {}", e)),
+        };
+
+        self.stats.record(rtt);
+        if self.samples.len() == CHART_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(rtt);
+        self.chart_cache.clear();
+
+        self.rows.push_front(ReplyRow {
+            sequence,
+            text,
+            is_success: rtt.is_some(),
+        });
+        self.rows.truncate(MAX_ROWS);
+    }
+
+    pub fn update(&mut self, message: Message, _clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::UpdateTarget(target_input) => {
+                self.target_input = target_input;
+            }
+            Message::UpdateSize(size_input) => {
+                self.size_input = size_input;
+            }
+            Message::UpdateTtl(ttl_input) => {
+                self.ttl_input = ttl_input;
+            }
+            Message::UpdateInterval(interval_input) => {
+                self.interval_input = interval_input;
+            }
+            Message::UpdateCount(count_input) => {
+                self.count_input = count_input;
+            }
+            Message::Start => self.start(),
+            Message::Stop => {
+                // Dropping the subscription stops the ping.
+                self.running = None;
+                self.status = "Stopped".to_string();
+            }
+            Message::Ping(id, event) => {
+                // Ignore events from pings that were stopped.
+                if self.running.as_ref().map(|running| running.id) != Some(id) {
+                    return Command::none();
+                }
+
+                match event {
+                    PingEvent::Reply { sequence, result } => self.push_reply(sequence, result),
+                    PingEvent::Finished => {
+                        self.running = None;
+                        self.status = "Done".to_string();
+                    }
+                }
+            }
+        }
+
+        Command::none()
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        let start_button = if self.running.is_some() {
+            Button::new(&mut self.start_button_state, Text::new("Stop").size(15))
+                .style(ForegroundGreenButtonStyle)
+                .on_press(Message::Stop)
+        } else {
+            Button::new(&mut self.start_button_state, Text::new("Start").size(15))
+                .style(ForegroundGreenButtonStyle)
+                .on_press(Message::Start)
+        };
+
+        let mut column = Column::new()
+            .spacing(10)
+            .push(Text::new("Ping").size(36))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(
+                        TextInput::new(
+                            &mut self.target_input_state,
+                            "Enter ipv4 or ipv6 address, like fe80::1%12",
+                            &self.target_input,
+                            Message::UpdateTarget,
+                        )
+                        .on_submit(Message::Start)
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(300)),
+                    )
+                    .push(start_button),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Text::new("Size (bytes)").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.size_input_state,
+                            "",
+                            &self.size_input,
+                            Message::UpdateSize,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(60)),
+                    )
+                    .push(Text::new("TTL").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.ttl_input_state,
+                            "",
+                            &self.ttl_input,
+                            Message::UpdateTtl,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(50)),
+                    )
+                    .push(Text::new("Interval (ms)").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.interval_input_state,
+                            "",
+                            &self.interval_input,
+                            Message::UpdateInterval,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(60)),
+                    )
+                    .push(Text::new("Count (0 = until stopped)").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.count_input_state,
+                            "",
+                            &self.count_input,
+                            Message::UpdateCount,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(50)),
+                    ),
+            )
+            .push(Text::new(&self.status).size(15).color(HIGHLIGHT_TEXT_COLOR));
+
+        if !self.target.is_empty() {
+            let format_rtt = |rtt: Option<Duration>| {
+                rtt.map_or_else(
+                    || "N/A".to_string(),
+                    |rtt| format!("{:.1} ms", rtt.as_secs_f64() * 1000.0),
+                )
+            };
+            column = column
+                .push(Text::new(format!("Statistics for {}", self.target)).size(24))
+                .push(
+                    Text::new(format!(
+                        "Sent: {}, Received: {}, Lost: {} ({:.1}% loss)",
+                        self.stats.sent,
+                        self.stats.received,
+                        self.stats.sent - self.stats.received,
+                        self.stats.loss_percent()
+                    ))
+                    .size(15),
+                )
+                .push(
+                    Text::new(format!(
+                        "Min: {}, Avg: {}, Max: {}, Jitter: {}",
+                        format_rtt(self.stats.min),
+                        format_rtt(self.stats.average()),
+                        format_rtt(self.stats.max),
+                        format_rtt(self.stats.jitter())
+                    ))
+                    .size(15),
+                )
+                .push(Text::new("Latency (red: lost)").size(15))
+                .push(
+                    Canvas::new(LatencyChart {
+                        samples: &self.samples,
+                        cache: &self.chart_cache,
+                    })
+                    .width(Length::Fill)
+                    .height(Length::Units(CHART_HEIGHT)),
+                );
+        }
+
+        for row in self.rows.iter() {
+            let mut text = Text::new(&row.text).size(15);
+            if !row.is_success {
+                text = text.color(HIGHLIGHT_TEXT_COLOR);
+            }
+            column = column.push(
+                Row::new()
+                    .spacing(10)
+                    .push(Space::new(Length::Units(20), Length::Shrink))
+                    .push(
+                        Text::new(format!("#{}", row.sequence + 1))
+                            .size(15)
+                            .width(Length::Units(50)),
+                    )
+                    .push(text),
+            );
+        }
+
+        Container::new(
+            Scrollable::new(&mut self.scroll_state)
+                .push(Container::new(column).padding(20))
+                .width(Length::Fill),
+        )
+        .style(GreyStyle)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+/// A rolling chart of round trip times, with losses marked
+struct LatencyChart<'a> {
+    samples: &'a VecDeque<Option<Duration>>,
+    cache: &'a Cache,
+}
+
+impl<'a, Message> Program<Message> for LatencyChart<'a> {
+    fn draw(&self, bounds: Rectangle, _cursor: Cursor) -> Vec<Geometry> {
+        let geometry = self.cache.draw(bounds.size(), |frame| {
+            frame.fill_rectangle(
+                Point::ORIGIN,
+                frame.size(),
+                Color::from_rgb8(0x1E, 0x1E, 0x1E),
+            );
+
+            let scale = self
+                .samples
+                .iter()
+                .flatten()
+                .copied()
+                .fold(MIN_CHART_SCALE, Duration::max);
+
+            draw_samples(frame, self.samples, scale);
+
+            frame.fill_text(iced::canvas::Text {
+                content: format!("{} ms", scale.as_millis()),
+                position: Point::new(4.0, 2.0),
+                color: Color::WHITE,
+                size: 12.0,
+                ..Default::default()
+            });
+        });
+
+        vec![geometry]
+    }
+}
+
+/// Draw the round trip times as a line, with the newest at the right edge.
+///
+/// Losses break the line and are drawn as full height bars.
+fn draw_samples(frame: &mut Frame, samples: &VecDeque<Option<Duration>>, scale: Duration) {
+    let Size { width, height } = frame.size();
+    let step = width / (CHART_LEN - 1) as f32;
+    let offset = (CHART_LEN - samples.len()) as f32 * step;
+
+    let line = Path::new(|builder| {
+        let mut is_drawing = false;
+        for (i, sample) in samples.iter().enumerate() {
+            let rtt = match sample {
+                Some(rtt) => rtt,
+                None => {
+                    is_drawing = false;
+                    continue;
+                }
+            };
+            let point = Point::new(
+                offset + i as f32 * step,
+                height - (rtt.as_secs_f32() / scale.as_secs_f32()) * height,
+            );
+
+            if is_drawing {
+                builder.line_to(point);
+            } else {
+                builder.move_to(point);
+                is_drawing = true;
+            }
+        }
+    });
+    frame.stroke(
+        &line,
+        Stroke::default().with_color(RTT_COLOR).with_width(2.0),
+    );
+
+    let losses = Path::new(|builder| {
+        for (i, sample) in samples.iter().enumerate() {
+            if sample.is_none() {
+                let x = offset + i as f32 * step;
+                builder.move_to(Point::new(x, 0.0));
+                builder.line_to(Point::new(x, height));
+            }
+        }
+    });
+    frame.stroke(
+        &losses,
+        Stroke::default().with_color(LOSS_COLOR).with_width(1.0),
+    );
+}
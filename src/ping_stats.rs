@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+/// Running statistics of a ping
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PingStats {
+    pub sent: u64,
+    pub received: u64,
+
+    pub min: Option<Duration>,
+    pub max: Option<Duration>,
+    total: Duration,
+
+    last: Option<Duration>,
+    total_jitter: Duration,
+    num_jitter_samples: u64,
+}
+
+impl PingStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a request, with the round trip time if it was answered.
+    pub fn record(&mut self, rtt: Option<Duration>) {
+        self.sent += 1;
+        let rtt = match rtt {
+            Some(rtt) => rtt,
+            None => return,
+        };
+
+        self.received += 1;
+        self.min = Some(self.min.map_or(rtt, |min| min.min(rtt)));
+        self.max = Some(self.max.map_or(rtt, |max| max.max(rtt)));
+        self.total += rtt;
+
+        if let Some(last) = self.last {
+            self.total_jitter += rtt.max(last) - rtt.min(last);
+            self.num_jitter_samples += 1;
+        }
+        self.last = Some(rtt);
+    }
+
+    /// Get the mean round trip time of answered requests.
+    pub fn average(&self) -> Option<Duration> {
+        if self.received == 0 {
+            return None;
+        }
+        Some(self.total / self.received as u32)
+    }
+
+    /// Get the jitter, the mean difference between consecutive round trip times.
+    pub fn jitter(&self) -> Option<Duration> {
+        if self.num_jitter_samples == 0 {
+            return None;
+        }
+        Some(self.total_jitter / self.num_jitter_samples as u32)
+    }
+
+    /// Get the percentage of requests that were not answered.
+    pub fn loss_percent(&self) -> f64 {
+        if self.sent == 0 {
+            return 0.0;
+        }
+        (self.sent - self.received) as f64 / self.sent as f64 * 100.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn empty() {
+        let stats = PingStats::new();
+        assert_eq!(stats.min, None);
+        assert_eq!(stats.max, None);
+        assert_eq!(stats.average(), None);
+        assert_eq!(stats.jitter(), None);
+        assert_eq!(stats.loss_percent(), 0.0);
+    }
+
+    #[test]
+    fn all_answered() {
+        let mut stats = PingStats::new();
+        for rtt in [10, 20, 15, 35].iter() {
+            stats.record(Some(ms(*rtt)));
+        }
+
+        assert_eq!(stats.sent, 4);
+        assert_eq!(stats.received, 4);
+        assert_eq!(stats.min, Some(ms(10)));
+        assert_eq!(stats.max, Some(ms(35)));
+        assert_eq!(stats.average(), Some(ms(20)));
+        // |20 - 10| + |15 - 20| + |35 - 15| = 35, over 3 samples
+        assert_eq!(stats.jitter(), Some(Duration::from_nanos(11_666_666)));
+        assert_eq!(stats.loss_percent(), 0.0);
+    }
+
+    #[test]
+    fn losses() {
+        let mut stats = PingStats::new();
+        stats.record(Some(ms(10)));
+        stats.record(None);
+        stats.record(None);
+        stats.record(Some(ms(30)));
+
+        assert_eq!(stats.sent, 4);
+        assert_eq!(stats.received, 2);
+        assert_eq!(stats.average(), Some(ms(20)));
+        // Jitter is measured between answered requests.
+        assert_eq!(stats.jitter(), Some(ms(20)));
+        assert_eq!(stats.loss_percent(), 50.0);
+
+        let mut stats = PingStats::new();
+        stats.record(None);
+        assert_eq!(stats.average(), None);
+        assert_eq!(stats.loss_percent(), 100.0);
+    }
+}
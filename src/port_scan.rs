@@ -0,0 +1,547 @@
+use crate::arp_sweep::parse_targets;
+use anyhow::Context;
+use iced::futures::{
+    stream::BoxStream,
+    Stream,
+    StreamExt,
+};
+use std::{
+    collections::HashSet,
+    hash::Hash,
+    io::{
+        ErrorKind,
+        Read,
+        Write,
+    },
+    net::{
+        IpAddr,
+        SocketAddr,
+        TcpStream,
+    },
+    sync::Arc,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+/// The most connections a single scan may make
+pub const MAX_PROBES: usize = 1 << 20;
+
+/// The most bytes of a banner that are kept
+const MAX_BANNER_LEN: usize = 200;
+
+/// Sent to servers that wait for the client to speak first, which gets most http servers to answer
+const HTTP_PROBE: &[u8] = b"HEAD / HTTP/1.0\r\n\r\n";
+
+/// The 10 most common open tcp ports
+pub const TOP_10_PORTS: &[u16] = &[21, 22, 23, 25, 80, 110, 139, 443, 445, 3389];
+
+/// The 100 most common open tcp ports, as listed by nmap
+pub const TOP_100_PORTS: &[u16] = &[
+    7, 9, 13, 21, 22, 23, 25, 26, 37, 53, 79, 80, 81, 88, 106, 110, 111, 113, 119, 135, 139, 143,
+    144, 179, 199, 389, 427, 443, 444, 445, 465, 513, 514, 515, 543, 544, 548, 554, 587, 631, 646,
+    873, 990, 993, 995, 1025, 1026, 1027, 1028, 1029, 1110, 1433, 1720, 1723, 1755, 1900, 2000,
+    2001, 2049, 2121, 2717, 3000, 3128, 3306, 3389, 3986, 4899, 5000, 5009, 5051, 5060, 5101, 5190,
+    5357, 5432, 5631, 5666, 5800, 5900, 6000, 6001, 6646, 7070, 8000, 8008, 8009, 8080, 8081, 8443,
+    8888, 9100, 9999, 10000, 32768, 49152, 49153, 49154, 49155, 49156, 49157,
+];
+
+/// Get the name of the service usually found on a port.
+pub fn service_name(port: u16) -> Option<&'static str> {
+    let name = match port {
+        21 => "ftp",
+        22 => "ssh",
+        23 => "telnet",
+        25 => "smtp",
+        53 => "domain",
+        80 => "http",
+        110 => "pop3",
+        111 => "rpcbind",
+        135 => "msrpc",
+        139 => "netbios-ssn",
+        143 => "imap",
+        443 => "https",
+        445 => "microsoft-ds",
+        465 => "smtps",
+        548 => "afp",
+        554 => "rtsp",
+        587 => "submission",
+        631 => "ipp",
+        993 => "imaps",
+        995 => "pop3s",
+        1433 => "ms-sql-s",
+        1723 => "pptp",
+        3306 => "mysql",
+        3389 => "ms-wbt-server",
+        5432 => "postgresql",
+        5900 => "vnc",
+        8080 => "http-proxy",
+        8443 => "https-alt",
+        9100 => "jetdirect",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Parse a port list, like "top100", "22,80,443" or "8000-8100".
+///
+/// Items are separated by commas and may be "top10", "top100", a port, or an inclusive range.
+/// The ports are returned sorted, without duplicates.
+pub fn parse_ports(input: &str) -> anyhow::Result<Vec<u16>> {
+    let mut ports = Vec::new();
+    for item in input
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        match item.to_ascii_lowercase().as_str() {
+            "top10" => ports.extend_from_slice(TOP_10_PORTS),
+            "top100" => ports.extend_from_slice(TOP_100_PORTS),
+            _ => {
+                if let Some((start, end)) = item.split_once('-') {
+                    let start = parse_port(start)?;
+                    let end = parse_port(end)?;
+                    if end < start {
+                        anyhow::bail!("the end of \"{}\" is before the start", item);
+                    }
+                    ports.extend(start..=end);
+                } else {
+                    ports.push(parse_port(item)?);
+                }
+            }
+        }
+    }
+
+    if ports.is_empty() {
+        anyhow::bail!("no ports");
+    }
+    ports.sort_unstable();
+    ports.dedup();
+
+    Ok(ports)
+}
+
+fn parse_port(input: &str) -> anyhow::Result<u16> {
+    let input = input.trim();
+    let port: u16 = input
+        .parse()
+        .with_context(|| format!("invalid port \"{}\"", input))?;
+    if port == 0 {
+        anyhow::bail!("port 0 cannot be scanned");
+    }
+    Ok(port)
+}
+
+/// Parse the hosts to scan, separated by commas.
+///
+/// Each item may be an ipv4 or ipv6 address, or an ipv4 range or CIDR as accepted by [`parse_targets`].
+pub fn parse_hosts(input: &str) -> anyhow::Result<Vec<IpAddr>> {
+    let mut hosts = Vec::new();
+    for item in input
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+    {
+        match item.parse::<IpAddr>() {
+            Ok(address) => hosts.push(address),
+            Err(_) => hosts.extend(
+                parse_targets(item)
+                    .with_context(|| format!("invalid host \"{}\"", item))?
+                    .into_iter()
+                    .map(IpAddr::V4),
+            ),
+        }
+    }
+
+    if hosts.is_empty() {
+        anyhow::bail!("no hosts");
+    }
+    let mut seen = HashSet::new();
+    hosts.retain(|host| seen.insert(*host));
+
+    Ok(hosts)
+}
+
+/// The settings of a scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanConfig {
+    pub hosts: Vec<IpAddr>,
+    pub ports: Vec<u16>,
+
+    /// The most connections that are attempted at once
+    pub concurrency: usize,
+    /// How long to wait for each connection, and for a banner
+    pub timeout: Duration,
+    pub grab_banners: bool,
+}
+
+impl ScanConfig {
+    /// Get the number of connections this scan will make.
+    pub fn num_probes(&self) -> usize {
+        self.hosts.len().saturating_mul(self.ports.len())
+    }
+}
+
+/// The state of a scanned port
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PortState {
+    /// A connection was accepted
+    Open,
+    /// The connection was refused
+    Closed,
+    /// There was no answer in time, which usually means a firewall dropped the connection
+    Filtered,
+}
+
+impl std::fmt::Display for PortState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => "Open".fmt(f),
+            Self::Closed => "Closed".fmt(f),
+            Self::Filtered => "Filtered".fmt(f),
+        }
+    }
+}
+
+/// The outcome of scanning a single port
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortResult {
+    pub address: SocketAddr,
+
+    /// The state of the port, or why the connection failed for other reasons
+    pub state: Result<PortState, String>,
+    /// The first line a text protocol sent, if any
+    pub banner: Option<String>,
+    pub rtt: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanEvent {
+    Result(PortResult),
+    Finished,
+}
+
+/// Connect to every port of every host, yielding results as they arrive and then [`ScanEvent::Finished`].
+///
+/// Dropping the stream cancels the scan.
+/// Connections that are already running still finish in the background, but their results are discarded.
+pub fn scan(config: ScanConfig) -> impl Stream<Item = ScanEvent> {
+    let ScanConfig {
+        hosts,
+        ports,
+        concurrency,
+        timeout,
+        grab_banners,
+    } = config;
+
+    let ports = Arc::new(ports);
+    iced::futures::stream::iter(hosts)
+        .flat_map(move |host| {
+            let ports = ports.clone();
+            iced::futures::stream::iter(0..ports.len())
+                .map(move |i| SocketAddr::new(host, ports[i]))
+        })
+        .map(move |address| probe(address, timeout, grab_banners))
+        .buffer_unordered(concurrency.max(1))
+        .map(ScanEvent::Result)
+        .chain(iced::futures::stream::once(async { ScanEvent::Finished }))
+}
+
+async fn probe(address: SocketAddr, timeout: Duration, grab_banners: bool) -> PortResult {
+    let task = tokio::task::spawn_blocking(move || probe_blocking(address, timeout, grab_banners));
+    match task.await {
+        Ok(result) => result,
+        Err(e) => PortResult {
+            address,
+            state: Err(format!("tokio task failed to join: {}", e)),
+            banner: None,
+            rtt: Duration::from_millis(0),
+        },
+    }
+}
+
+fn probe_blocking(address: SocketAddr, timeout: Duration, grab_banners: bool) -> PortResult {
+    let start = Instant::now();
+    let result = TcpStream::connect_timeout(&address, timeout);
+    let rtt = start.elapsed();
+
+    let (state, banner) = match result {
+        Ok(mut stream) => {
+            let banner = if grab_banners {
+                grab_banner(&mut stream, timeout)
+            } else {
+                None
+            };
+            (Ok(PortState::Open), banner)
+        }
+        Err(e) if e.kind() == ErrorKind::ConnectionRefused => (Ok(PortState::Closed), None),
+        Err(e) if e.kind() == ErrorKind::TimedOut || e.kind() == ErrorKind::WouldBlock => {
+            (Ok(PortState::Filtered), None)
+        }
+        Err(e) => (Err(e.to_string()), None),
+    };
+
+    PortResult {
+        address,
+        state,
+        banner,
+        rtt,
+    }
+}
+
+/// Read the first line a server sends.
+///
+/// Servers that wait for the client, like http, are sent a `HEAD` request.
+fn grab_banner(stream: &mut TcpStream, timeout: Duration) -> Option<String> {
+    stream.set_read_timeout(Some(timeout)).ok()?;
+    stream.set_write_timeout(Some(timeout)).ok()?;
+
+    let mut buffer = [0; 512];
+    let len = match stream.read(&mut buffer) {
+        Ok(len) => len,
+        Err(e) if e.kind() == ErrorKind::TimedOut || e.kind() == ErrorKind::WouldBlock => {
+            stream.write_all(HTTP_PROBE).ok()?;
+            stream.read(&mut buffer).ok()?
+        }
+        Err(_) => return None,
+    };
+
+    parse_banner(&buffer[..len])
+}
+
+/// Get the first line of a banner, if it is text.
+fn parse_banner(data: &[u8]) -> Option<String> {
+    let line = data.split(|b| *b == b'\n').next()?;
+    let line = std::str::from_utf8(line).ok()?.trim();
+    if line.is_empty() || line.chars().any(|c| c.is_control()) {
+        return None;
+    }
+
+    let mut line = line.to_string();
+    if line.len() > MAX_BANNER_LEN {
+        let mut end = MAX_BANNER_LEN;
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        line.truncate(end);
+    }
+    Some(line)
+}
+
+/// Get a subscription that runs a scan, tagging events with `id`.
+///
+/// Changing `id` starts a new scan, and dropping the subscription cancels it.
+pub fn scan_subscription(id: u64, config: Arc<ScanConfig>) -> iced::Subscription<(u64, ScanEvent)> {
+    iced::Subscription::from_recipe(Scan { id, config })
+}
+
+struct Scan {
+    id: u64,
+    config: Arc<ScanConfig>,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for Scan
+where
+    H: std::hash::Hasher,
+{
+    type Output = (u64, ScanEvent);
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+        self.id.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let id = self.id;
+        scan((*self.config).clone())
+            .map(move |event| (id, event))
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::{
+        Ipv4Addr,
+        TcpListener,
+    };
+
+    /// Listen on a localhost port, answering each connection with `handler` on a background thread.
+    fn listen(handler: fn(TcpStream)) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                handler(stream.unwrap());
+            }
+        });
+        port
+    }
+
+    /// Get a localhost port that nothing listens on.
+    fn closed_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn config(ports: Vec<u16>, grab_banners: bool) -> ScanConfig {
+        ScanConfig {
+            hosts: vec![IpAddr::V4(Ipv4Addr::LOCALHOST)],
+            ports,
+            concurrency: 4,
+            // Windows retries refused connections for a couple of seconds, so this has to be longer than that.
+            timeout: Duration::from_secs(5),
+            grab_banners,
+        }
+    }
+
+    async fn results(config: ScanConfig) -> Vec<PortResult> {
+        let events: Vec<ScanEvent> = scan(config).collect().await;
+        assert_eq!(events.last(), Some(&ScanEvent::Finished));
+
+        let mut results: Vec<PortResult> = events
+            .into_iter()
+            .filter_map(|event| match event {
+                ScanEvent::Result(result) => Some(result),
+                ScanEvent::Finished => None,
+            })
+            .collect();
+        results.sort_by_key(|result| result.address);
+        results
+    }
+
+    #[test]
+    fn parse_port_lists() {
+        assert_eq!(parse_ports("80").unwrap(), vec![80]);
+        assert_eq!(parse_ports("443, 22 ,80").unwrap(), vec![22, 80, 443]);
+        assert_eq!(
+            parse_ports("8000-8003").unwrap(),
+            vec![8000, 8001, 8002, 8003]
+        );
+        assert_eq!(parse_ports("80,79-81").unwrap(), vec![79, 80, 81]);
+        assert_eq!(parse_ports("TOP10").unwrap().len(), 10);
+        assert_eq!(parse_ports("top100").unwrap().len(), 100);
+        assert_eq!(parse_ports("top100,top10").unwrap().len(), 100);
+        assert_eq!(parse_ports("top10,9000").unwrap().len(), 11);
+        assert_eq!(parse_ports("1-65535").unwrap().len(), 65535);
+
+        assert!(parse_ports("").is_err());
+        assert!(parse_ports("0").is_err());
+        assert!(parse_ports("65536").is_err());
+        assert!(parse_ports("90-80").is_err());
+        assert!(parse_ports("top5").is_err());
+    }
+
+    #[test]
+    fn presets_are_sorted_and_unique() {
+        for preset in [TOP_10_PORTS, TOP_100_PORTS].iter() {
+            assert!(preset.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[test]
+    fn parse_host_lists() {
+        assert_eq!(
+            parse_hosts("127.0.0.1, ::1").unwrap(),
+            vec![
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                "::1".parse::<IpAddr>().unwrap()
+            ]
+        );
+        assert_eq!(parse_hosts("10.0.0.1-5,10.0.0.9").unwrap().len(), 6);
+        assert_eq!(parse_hosts("10.0.0.0/30").unwrap().len(), 2);
+
+        assert!(parse_hosts("").is_err());
+        assert!(parse_hosts("10.0.0").is_err());
+    }
+
+    #[test]
+    fn banners() {
+        assert_eq!(
+            parse_banner(b"SSH-2.0-OpenSSH_8.9\r\n"),
+            Some("SSH-2.0-OpenSSH_8.9".to_string())
+        );
+        assert_eq!(
+            parse_banner(b"HTTP/1.0 200 OK\r\nServer: test\r\n\r\n"),
+            Some("HTTP/1.0 200 OK".to_string())
+        );
+        assert_eq!(parse_banner(b"\x16\x03\x01\x00"), None);
+        assert_eq!(parse_banner(b"\r\n"), None);
+        assert_eq!(parse_banner(&[b'a'; 1000]).unwrap().len(), MAX_BANNER_LEN);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn scan_finds_open_and_closed_ports() {
+        let open = listen(|_stream| {});
+        let closed = closed_port();
+
+        let results = results(config(vec![open, closed], false)).await;
+        assert_eq!(results.len(), 2);
+
+        let state = |port| {
+            results
+                .iter()
+                .find(|result| result.address.port() == port)
+                .unwrap()
+                .state
+                .clone()
+        };
+        assert_eq!(state(open), Ok(PortState::Open));
+        assert_eq!(state(closed), Ok(PortState::Closed));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn scan_grabs_greeting_banner() {
+        let port = listen(|mut stream| {
+            stream.write_all(b"220 test ftp ready\r\n").unwrap();
+        });
+
+        let results = results(config(vec![port], true)).await;
+        assert_eq!(results[0].state, Ok(PortState::Open));
+        assert_eq!(results[0].banner.as_deref(), Some("220 test ftp ready"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn scan_grabs_http_banner() {
+        let port = listen(|mut stream| {
+            // Only answer once the client has spoken, like an http server.
+            let mut buffer = [0; 64];
+            let len = stream.read(&mut buffer).unwrap();
+            if buffer[..len].starts_with(b"HEAD") {
+                stream.write_all(b"HTTP/1.0 200 OK\r\n\r\n").unwrap();
+            }
+        });
+
+        let mut config = config(vec![port], true);
+        config.timeout = Duration::from_millis(200);
+        let results = results(config).await;
+        assert_eq!(results[0].banner.as_deref(), Some("HTTP/1.0 200 OK"));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn scan_covers_every_port_one_at_a_time() {
+        let ports = vec![
+            listen(|_stream| {}),
+            listen(|_stream| {}),
+            listen(|_stream| {}),
+        ];
+        let mut config = config(ports.clone(), false);
+        config.concurrency = 1;
+        assert_eq!(config.num_probes(), 3);
+
+        let results = results(config).await;
+        let mut scanned: Vec<u16> = results.iter().map(|result| result.address.port()).collect();
+        scanned.sort_unstable();
+        let mut ports = ports;
+        ports.sort_unstable();
+        assert_eq!(scanned, ports);
+        assert!(results
+            .iter()
+            .all(|result| result.state == Ok(PortState::Open)));
+    }
+}
@@ -0,0 +1,480 @@
+use crate::{
+    port_scan::{
+        parse_hosts,
+        parse_ports,
+        scan_subscription,
+        service_name,
+        PortState,
+        ScanConfig,
+        ScanEvent,
+        MAX_PROBES,
+    },
+    style::{
+        ForegroundGreenButtonStyle,
+        ForegroundGreenTextInputStyle,
+    },
+    GreyStyle,
+};
+use anyhow::Context;
+use iced::{
+    Align,
+    Button,
+    Checkbox,
+    Clipboard,
+    Column,
+    Command,
+    Container,
+    Element,
+    Length,
+    PickList,
+    Row,
+    Scrollable,
+    Subscription,
+    Text,
+    TextInput,
+};
+use log::info;
+use std::{
+    net::{
+        IpAddr,
+        SocketAddr,
+    },
+    sync::Arc,
+    time::Duration,
+};
+
+/// The default number of connections attempted at once
+const DEFAULT_CONCURRENCY: usize = 100;
+
+/// The most connections that may be attempted at once.
+///
+/// Each connection holds a thread from tokio's blocking pool, which has 512 threads by default and is shared with the other tabs.
+const MAX_CONCURRENCY: usize = 256;
+
+/// The default time to wait for each connection, in milliseconds
+const DEFAULT_TIMEOUT_MS: u64 = 1000;
+
+/// A list of ports that can be picked instead of typing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortPreset {
+    Top10,
+    Top100,
+    WellKnown,
+    All,
+}
+
+impl PortPreset {
+    const ALL: &'static [Self] = &[Self::Top10, Self::Top100, Self::WellKnown, Self::All];
+
+    /// Get the port list input for this preset.
+    fn ports_input(self) -> &'static str {
+        match self {
+            Self::Top10 => "top10",
+            Self::Top100 => "top100",
+            Self::WellKnown => "1-1023",
+            Self::All => "1-65535",
+        }
+    }
+}
+
+impl std::fmt::Display for PortPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Top10 => "Top 10".fmt(f),
+            Self::Top100 => "Top 100".fmt(f),
+            Self::WellKnown => "Well-known (1-1023)".fmt(f),
+            Self::All => "All (1-65535)".fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    UpdateHosts(String),
+    UpdatePorts(String),
+    PresetSelected(PortPreset),
+    UpdateConcurrency(String),
+    UpdateTimeout(String),
+    GrabBannersToggled(bool),
+    Start,
+    Cancel,
+    Scan(u64, ScanEvent),
+}
+
+/// A running scan
+struct RunningScan {
+    id: u64,
+    config: Arc<ScanConfig>,
+}
+
+/// An open port
+struct OpenPort {
+    address: SocketAddr,
+    banner: String,
+    rtt: Duration,
+}
+
+pub struct PortScanner {
+    hosts_input: String,
+    ports_input: String,
+    preset: Option<PortPreset>,
+    concurrency_input: String,
+    timeout_input: String,
+    grab_banners: bool,
+
+    running: Option<RunningScan>,
+    next_id: u64,
+
+    open_ports: Vec<OpenPort>,
+    num_done: usize,
+    num_probes: usize,
+    num_closed: usize,
+    num_filtered: usize,
+    num_errors: usize,
+    status: String,
+
+    hosts_input_state: iced::text_input::State,
+    ports_input_state: iced::text_input::State,
+    preset_state: iced::pick_list::State<PortPreset>,
+    concurrency_input_state: iced::text_input::State,
+    timeout_input_state: iced::text_input::State,
+    start_button_state: iced::button::State,
+    scroll_state: iced::scrollable::State,
+}
+
+impl PortScanner {
+    pub fn new() -> Self {
+        Self {
+            hosts_input: String::new(),
+            ports_input: PortPreset::Top100.ports_input().to_string(),
+            preset: Some(PortPreset::Top100),
+            concurrency_input: DEFAULT_CONCURRENCY.to_string(),
+            timeout_input: DEFAULT_TIMEOUT_MS.to_string(),
+            grab_banners: true,
+
+            running: None,
+            next_id: 0,
+
+            open_ports: Vec::new(),
+            num_done: 0,
+            num_probes: 0,
+            num_closed: 0,
+            num_filtered: 0,
+            num_errors: 0,
+            status: String::new(),
+
+            hosts_input_state: iced::text_input::State::new(),
+            ports_input_state: iced::text_input::State::new(),
+            preset_state: iced::pick_list::State::default(),
+            concurrency_input_state: iced::text_input::State::new(),
+            timeout_input_state: iced::text_input::State::new(),
+            start_button_state: iced::button::State::new(),
+            scroll_state: iced::scrollable::State::new(),
+        }
+    }
+
+    /// Set the hosts to scan, like the results of an arp sweep.
+    ///
+    /// This does not start a scan, so that the ports can be picked first.
+    pub fn set_hosts(&mut self, hosts: &[IpAddr]) {
+        self.hosts_input = hosts
+            .iter()
+            .map(|host| host.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        match self.running.as_ref() {
+            Some(running) => scan_subscription(running.id, running.config.clone())
+                .map(|(id, event)| Message::Scan(id, event)),
+            None => Subscription::none(),
+        }
+    }
+
+    /// Validate the inputs.
+    fn parse_config(&self) -> anyhow::Result<ScanConfig> {
+        let hosts = parse_hosts(&self.hosts_input).context("invalid hosts")?;
+        let ports = parse_ports(&self.ports_input).context("invalid ports")?;
+        let concurrency: usize = self
+            .concurrency_input
+            .trim()
+            .parse()
+            .context("invalid concurrency")?;
+        if concurrency == 0 || concurrency > MAX_CONCURRENCY {
+            anyhow::bail!("concurrency must be between 1 and {}", MAX_CONCURRENCY);
+        }
+        let timeout_ms: u64 = self
+            .timeout_input
+            .trim()
+            .parse()
+            .context("invalid timeout")?;
+        if timeout_ms == 0 {
+            anyhow::bail!("timeout must be at least 1 ms");
+        }
+
+        let config = ScanConfig {
+            hosts,
+            ports,
+            concurrency,
+            timeout: Duration::from_millis(timeout_ms),
+            grab_banners: self.grab_banners,
+        };
+        if config.num_probes() > MAX_PROBES {
+            anyhow::bail!(
+                "cannot make more than {} connections in one scan",
+                MAX_PROBES
+            );
+        }
+
+        Ok(config)
+    }
+
+    pub fn update(&mut self, message: Message, _clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::UpdateHosts(hosts_input) => {
+                self.hosts_input = hosts_input;
+            }
+            Message::UpdatePorts(ports_input) => {
+                self.ports_input = ports_input;
+                self.preset = None;
+            }
+            Message::PresetSelected(preset) => {
+                self.ports_input = preset.ports_input().to_string();
+                self.preset = Some(preset);
+            }
+            Message::UpdateConcurrency(concurrency_input) => {
+                self.concurrency_input = concurrency_input;
+            }
+            Message::UpdateTimeout(timeout_input) => {
+                self.timeout_input = timeout_input;
+            }
+            Message::GrabBannersToggled(grab_banners) => {
+                self.grab_banners = grab_banners;
+            }
+            Message::Start => match self.parse_config() {
+                Ok(config) => {
+                    info!(
+                        "Starting port scan of {} host(s) and {} port(s)",
+                        config.hosts.len(),
+                        config.ports.len()
+                    );
+                    self.open_ports.clear();
+                    self.num_done = 0;
+                    self.num_probes = config.num_probes();
+                    self.num_closed = 0;
+                    self.num_filtered = 0;
+                    self.num_errors = 0;
+                    self.status = String::new();
+                    self.running = Some(RunningScan {
+                        id: self.next_id,
+                        config: Arc::new(config),
+                    });
+                    self.next_id += 1;
+                }
+                Err(e) => {
+                    self.status = format!("{:#}", e);
+                }
+            },
+            Message::Cancel => {
+                // Dropping the subscription cancels the scan.
+                self.running = None;
+                self.status = "Cancelled".to_string();
+            }
+            Message::Scan(id, event) => {
+                // Ignore events from scans that were cancelled.
+                if self.running.as_ref().map(|running| running.id) != Some(id) {
+                    return Command::none();
+                }
+
+                match event {
+                    ScanEvent::Result(result) => {
+                        self.num_done += 1;
+                        match result.state {
+                            Ok(PortState::Open) => {
+                                let open_port = OpenPort {
+                                    address: result.address,
+                                    banner: result.banner.unwrap_or_default(),
+                                    rtt: result.rtt,
+                                };
+                                let i = self
+                                    .open_ports
+                                    .binary_search_by_key(&open_port.address, |open_port| {
+                                        open_port.address
+                                    })
+                                    .unwrap_or_else(|i| i);
+                                self.open_ports.insert(i, open_port);
+                            }
+                            Ok(PortState::Closed) => self.num_closed += 1,
+                            Ok(PortState::Filtered) => self.num_filtered += 1,
+                            Err(_) => self.num_errors += 1,
+                        }
+                    }
+                    ScanEvent::Finished => {
+                        info!(
+                            "Finished port scan, found {} open port(s)",
+                            self.open_ports.len()
+                        );
+                        self.running = None;
+                        self.status = "Done".to_string();
+                    }
+                }
+            }
+        }
+
+        Command::none()
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        let start_button = if self.running.is_some() {
+            Button::new(&mut self.start_button_state, Text::new("Cancel").size(15))
+                .style(ForegroundGreenButtonStyle)
+                .on_press(Message::Cancel)
+        } else {
+            Button::new(&mut self.start_button_state, Text::new("Start").size(15))
+                .style(ForegroundGreenButtonStyle)
+                .on_press(Message::Start)
+        };
+
+        let mut column = Column::new()
+            .spacing(10)
+            .push(Text::new("Port Scanner").size(36))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Text::new("Hosts").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.hosts_input_state,
+                            "192.168.1.10, 192.168.1.20-30 or ::1",
+                            &self.hosts_input,
+                            Message::UpdateHosts,
+                        )
+                        .on_submit(Message::Start)
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2),
+                    )
+                    .push(start_button),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Text::new("Ports").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.ports_input_state,
+                            "top100, 22, 8000-8100",
+                            &self.ports_input,
+                            Message::UpdatePorts,
+                        )
+                        .on_submit(Message::Start)
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(250)),
+                    )
+                    .push(PickList::new(
+                        &mut self.preset_state,
+                        PortPreset::ALL,
+                        self.preset,
+                        Message::PresetSelected,
+                    ))
+                    .push(Text::new("Concurrency").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.concurrency_input_state,
+                            "",
+                            &self.concurrency_input,
+                            Message::UpdateConcurrency,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(50)),
+                    )
+                    .push(Text::new("Timeout (ms)").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.timeout_input_state,
+                            "",
+                            &self.timeout_input,
+                            Message::UpdateTimeout,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(60)),
+                    )
+                    .push(Checkbox::new(
+                        self.grab_banners,
+                        "Grab banners",
+                        Message::GrabBannersToggled,
+                    )),
+            );
+
+        let progress = if self.num_probes == 0 {
+            self.status.clone()
+        } else {
+            format!(
+                "Scanned {} of {}: {} open, {} closed, {} filtered, {} failed. {}",
+                self.num_done,
+                self.num_probes,
+                self.open_ports.len(),
+                self.num_closed,
+                self.num_filtered,
+                self.num_errors,
+                self.status
+            )
+        };
+        column = column.push(Text::new(progress).size(15)).push(
+            Row::new()
+                .spacing(10)
+                .push(Text::new("Host").size(15).width(Length::Units(250)))
+                .push(Text::new("Port").size(15).width(Length::Units(60)))
+                .push(Text::new("Service").size(15).width(Length::Units(120)))
+                .push(Text::new("RTT").size(15).width(Length::Units(80)))
+                .push(Text::new("Banner").size(15)),
+        );
+
+        for open_port in self.open_ports.iter() {
+            column = column.push(
+                Row::new()
+                    .spacing(10)
+                    .push(
+                        Text::new(open_port.address.ip().to_string())
+                            .size(15)
+                            .width(Length::Units(250)),
+                    )
+                    .push(
+                        Text::new(open_port.address.port().to_string())
+                            .size(15)
+                            .width(Length::Units(60)),
+                    )
+                    .push(
+                        Text::new(service_name(open_port.address.port()).unwrap_or(""))
+                            .size(15)
+                            .width(Length::Units(120)),
+                    )
+                    .push(
+                        Text::new(format!("{} ms", open_port.rtt.as_millis()))
+                            .size(15)
+                            .width(Length::Units(80)),
+                    )
+                    .push(Text::new(&open_port.banner).size(15)),
+            );
+        }
+
+        Container::new(
+            Scrollable::new(&mut self.scroll_state)
+                .push(Container::new(column).padding(20))
+                .width(Length::Fill),
+        )
+        .style(GreyStyle)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
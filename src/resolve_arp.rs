@@ -1,20 +1,47 @@
 use crate::{
+    adapter_addresses::InterfaceOption,
+    arp_sweep::{
+        parse_targets,
+        sweep_subscription,
+        SweepConfig,
+        SweepEvent,
+    },
+    dhcp::format_relative_time,
     format_mac_address,
+    format_mac_address_to_string,
+    neighbor_table::{
+        best_interface_index,
+        resolve_neighbor,
+    },
+    resolve_history::{
+        HistoryEntry,
+        ResolveHistory,
+    },
     style::{
+        ForegroundGreenButtonStyle,
         ForegroundGreenTextInputStyle,
         GreyStyleCopyTextHack,
+        HIGHLIGHT_TEXT_COLOR,
     },
+    subnet::SubnetInfo,
+    wake_on_lan::WakeDevice,
     GreyStyle,
 };
 use anyhow::Context;
 use iced::{
+    Align,
+    Button,
     Clipboard,
     Column,
     Command,
     Container,
     Element,
     Length,
+    PickList,
     Row,
+    Scrollable,
+    Space,
+    Subscription,
     Text,
     TextInput,
 };
@@ -25,18 +52,148 @@ use log::{
 };
 use std::{
     convert::TryInto,
-    net::Ipv4Addr,
+    net::{
+        IpAddr,
+        Ipv4Addr,
+    },
     sync::Arc,
-    time::Instant,
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+    },
 };
 
+/// The default number of hosts resolved at once by a sweep
+const DEFAULT_CONCURRENCY: usize = 32;
+
+/// The most hosts a sweep may resolve at once
+const MAX_CONCURRENCY: usize = 256;
+
+/// The default time to wait for each host in a sweep, in milliseconds
+const DEFAULT_TIMEOUT_MS: u64 = 2000;
+
+/// A local address to send arp requests from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceOption {
+    /// Let windows pick the source from the route to the target
+    Automatic,
+    Address {
+        interface_name: String,
+        interface_index: u32,
+        address: Ipv4Addr,
+        prefix_length: u8,
+    },
+}
+
+impl SourceOption {
+    fn address(&self) -> Option<Ipv4Addr> {
+        match self {
+            Self::Automatic => None,
+            Self::Address { address, .. } => Some(*address),
+        }
+    }
+
+    fn interface_index(&self) -> Option<u32> {
+        match self {
+            Self::Automatic => None,
+            Self::Address {
+                interface_index, ..
+            } => Some(*interface_index),
+        }
+    }
+
+    /// Get the subnet that this source can send arp requests to.
+    fn subnet(&self) -> Option<SubnetInfo> {
+        match self {
+            Self::Automatic => None,
+            Self::Address {
+                address,
+                prefix_length,
+                ..
+            } => SubnetInfo::new(IpAddr::V4(*address), *prefix_length),
+        }
+    }
+}
+
+/// Get the local ipv4 addresses that can be used as a source, after the automatic option.
+pub fn get_sources() -> Vec<SourceOption> {
+    let mut sources = vec![SourceOption::Automatic];
+    match crate::adapter_addresses::get_adapter_addresses() {
+        Ok(adapters) => {
+            for adapter in adapters.iter() {
+                let interface = InterfaceOption::new(adapter);
+                for address in adapter.unicast_addresses.iter() {
+                    if let IpAddr::V4(ip) = address.address {
+                        sources.push(SourceOption::Address {
+                            interface_name: interface.name.clone(),
+                            interface_index: interface.index,
+                            address: ip,
+                            prefix_length: address.prefix_length,
+                        });
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            warn!("Failed to get adapter addresses: {}", e);
+        }
+    }
+    sources
+}
+
+impl std::fmt::Display for SourceOption {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Automatic => "Automatic".fmt(f),
+            Self::Address {
+                interface_name,
+                address,
+                ..
+            } => write!(f, "{} ({})", interface_name, address),
+        }
+    }
+}
+
+/// A column that sweep results can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Address,
+    HardwareAddress,
+    Vendor,
+    Rtt,
+}
+
 #[derive(Debug, Clone)]
 pub enum Message {
     Nop,
 
     UpdateIp(String),
+    SourceSelected(SourceOption),
     ResolveArp,
-    ResolveArpComplete(Arc<anyhow::Result<(u64, usize)>>),
+    ResolveArpComplete(Arc<ResolveAttempt>),
+
+    RerunEntry(usize),
+    CopyEntry(usize),
+    StartPin(usize),
+    UpdatePinName(String),
+    Pin,
+    Unpin(usize),
+    HistorySaved(Arc<anyhow::Result<()>>),
+
+    /// Handled by the app, which adds the device to the Wake-on-LAN address book
+    AddWakeDevice(WakeDevice),
+
+    UpdateSweepTargets(String),
+    SweepSourceSelected(SourceOption),
+    UpdateSweepConcurrency(String),
+    UpdateSweepTimeout(String),
+    StartSweep,
+    CancelSweep,
+    Sweep(u64, SweepEvent),
+    SortBy(SortColumn),
+    /// Handled by the app, which sends the hosts to the port scanner
+    ScanPorts(Vec<IpAddr>),
 }
 
 pub struct ResolveArp {
@@ -44,55 +201,337 @@ pub struct ResolveArp {
     resolved_mac_state: iced::text_input::State,
 
     ip_address: String,
+    sources: Vec<SourceOption>,
+    source: SourceOption,
+    source_state: iced::pick_list::State<SourceOption>,
+    off_link_warning: String,
     resolved_mac: String,
+    resolved_state: String,
+    resolved_interface: String,
+    resolved_device: Option<WakeDevice>,
+    add_wake_device_button_state: iced::button::State,
+
+    history: ResolveHistory,
+    history_row_states: Vec<HistoryRowState>,
+    /// The entry being pinned, and the name it is being given
+    pinning: Option<(usize, String)>,
+
+    sweep: ArpSweep,
+
+    scroll_state: iced::scrollable::State,
 }
 
 impl ResolveArp {
     pub fn new() -> Self {
+        let history = match ResolveHistory::load() {
+            Ok(history) => history,
+            Err(e) => {
+                info!("Not using a resolve history: {:?}", e);
+                ResolveHistory::default()
+            }
+        };
+        let history_row_states = history
+            .entries
+            .iter()
+            .map(|_| HistoryRowState::new())
+            .collect();
+
         Self {
             text_input_state: iced::text_input::State::new(),
             resolved_mac_state: iced::text_input::State::new(),
 
             ip_address: String::new(),
+            sources: get_sources(),
+            source: SourceOption::Automatic,
+            source_state: iced::pick_list::State::default(),
+            off_link_warning: String::new(),
             resolved_mac: String::new(),
+            resolved_state: String::new(),
+            resolved_interface: String::new(),
+            resolved_device: None,
+            add_wake_device_button_state: iced::button::State::new(),
+
+            history,
+            history_row_states,
+            pinning: None,
+
+            sweep: ArpSweep::new(),
+
+            scroll_state: iced::scrollable::State::new(),
+        }
+    }
+
+    /// Reload the local addresses that can be used as a source.
+    pub fn refresh_sources(&mut self) {
+        self.sources = get_sources();
+
+        // Fall back to automatic if a picked address is gone.
+        if !self.sources.contains(&self.source) {
+            self.source = SourceOption::Automatic;
+        }
+        if !self.sources.contains(&self.sweep.source) {
+            self.sweep.source = SourceOption::Automatic;
         }
+
+        self.update_off_link_warning();
     }
 
-    pub fn update(&mut self, message: Message, _clipboard: &mut Clipboard) -> Command<Message> {
+    /// Warn if the ipv4 target is not on the subnet of the source, or any local subnet for automatic sources.
+    ///
+    /// Arp requests are not routed, so they can only reach hosts on the same link.
+    fn update_off_link_warning(&mut self) {
+        self.off_link_warning.clear();
+
+        let target = match parse_scoped_address(&self.ip_address) {
+            Ok((IpAddr::V4(target), _)) => IpAddr::V4(target),
+            _ => return,
+        };
+
+        let is_on_link = match self.source.subnet() {
+            Some(subnet) => subnet.contains(target),
+            None => self
+                .sources
+                .iter()
+                .filter_map(SourceOption::subnet)
+                .any(|subnet| subnet.contains(target)),
+        };
+        if is_on_link {
+            return;
+        }
+
+        self.off_link_warning = match self.source {
+            SourceOption::Automatic => {
+                format!("{} is not on any local subnet, so ARP will fail", target)
+            }
+            SourceOption::Address { .. } => format!(
+                "{} is not on the subnet of {}, so ARP will fail",
+                target, self.source
+            ),
+        };
+    }
+
+    /// Resolve an address, recording the attempt in the history when it completes.
+    fn resolve(&self, address: String, source: SourceOption) -> Command<Message> {
+        Command::perform(
+            async move {
+                let start = Instant::now();
+                let result = resolve_address(&address, source.clone())
+                    .await
+                    .context("failed to resolve");
+                ResolveAttempt {
+                    address,
+                    source,
+                    rtt: start.elapsed(),
+                    result,
+                }
+            },
+            |attempt| Message::ResolveArpComplete(Arc::new(attempt)),
+        )
+    }
+
+    fn save_history_command(&self) -> Command<Message> {
+        let history = ResolveHistory {
+            entries: self.history.entries.clone(),
+        };
+
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    history.save().context("failed to save resolve history")
+                })
+                .await
+                .context("tokio task panicked")??;
+                Ok(())
+            },
+            |r| Message::HistorySaved(Arc::new(r)),
+        )
+    }
+
+    /// Make a Wake-on-LAN device for a resolved ipv4 address, broadcasting on the subnet it was found on.
+    fn wake_device(
+        &self,
+        target: Ipv4Addr,
+        hardware_address: &str,
+        attempt: &ResolveAttempt,
+        resolution: &Resolution,
+    ) -> WakeDevice {
+        let mut device = WakeDevice::new(target.to_string(), hardware_address.to_string());
+        device.source = attempt.source.address();
+
+        let broadcast = self
+            .sources
+            .iter()
+            .filter(|source| source.interface_index() == Some(resolution.interface_index))
+            .filter_map(SourceOption::subnet)
+            .find(|subnet| subnet.contains(IpAddr::V4(target)))
+            .and_then(|subnet| subnet.broadcast);
+        if let Some(IpAddr::V4(broadcast)) = broadcast {
+            device.broadcast = broadcast;
+        }
+
+        device
+    }
+
+    /// Get the subscriptions of this tab.
+    pub fn subscription(&self) -> Subscription<Message> {
+        self.sweep.subscription()
+    }
+
+    pub fn update(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
         match message {
             Message::Nop => Command::none(),
             Message::UpdateIp(ip_address) => {
                 self.ip_address = ip_address;
+                self.update_off_link_warning();
                 Command::none()
             }
-            Message::ResolveArp => {
-                let ip_address = self.ip_address.clone();
-                Command::perform(
-                    async move { resolve_arp(&ip_address).await.context("failed to resolve") },
-                    |res| Message::ResolveArpComplete(Arc::new(res)),
-                )
+            Message::SourceSelected(source) => {
+                self.source = source;
+                self.update_off_link_warning();
+                Command::none()
             }
-            Message::ResolveArpComplete(res) => {
-                match res.as_ref() {
-                    Ok((mac, len)) => {
-                        self.resolved_mac.clear();
+            Message::ResolveArp => self.resolve(self.ip_address.clone(), self.source.clone()),
+            Message::ResolveArpComplete(attempt) => {
+                self.resolved_mac.clear();
+                self.resolved_state.clear();
+                self.resolved_interface.clear();
+                self.resolved_device = None;
+                let mut entry = HistoryEntry {
+                    address: attempt.address.trim().to_string(),
+                    source: attempt.source.address(),
+                    hardware_address: None,
+                    vendor: None,
+                    interface: None,
+                    rtt: attempt.rtt,
+                    time: SystemTime::now(),
+                    error: None,
+                    pinned_name: None,
+                };
+                match attempt.result.as_ref() {
+                    Ok(resolution) => {
                         if let Err(e) =
-                            format_mac_address(&mut self.resolved_mac, &mac.to_ne_bytes()[..*len])
+                            format_mac_address(&mut self.resolved_mac, &resolution.hardware_address)
                                 .context("failed to format MAC address")
                         {
                             error!("{:?}", e);
                         }
 
-                        if *len == 0 {
+                        if resolution.hardware_address.is_empty() {
                             warn!("mac length is 0");
                         }
+
+                        self.resolved_state = resolution.state.unwrap_or("N/A").to_string();
+                        self.resolved_interface = match resolution.interface_name.as_ref() {
+                            Some(name) => format!("{} ({})", name, resolution.interface_index),
+                            None => resolution.interface_index.to_string(),
+                        };
+
+                        entry.hardware_address = Some(
+                            format_mac_address_to_string(&resolution.hardware_address)
+                                .trim_end()
+                                .to_string(),
+                        );
+                        entry.vendor =
+                            crate::oui::vendor_name(&resolution.hardware_address).map(String::from);
+                        entry.interface = Some(self.resolved_interface.clone());
+
+                        if let (Ok((IpAddr::V4(target), _)), Some(hardware_address)) = (
+                            parse_scoped_address(&attempt.address),
+                            entry.hardware_address.as_ref(),
+                        ) {
+                            self.resolved_device = Some(self.wake_device(
+                                target,
+                                hardware_address,
+                                attempt,
+                                resolution,
+                            ));
+                        }
                     }
                     Err(e) => {
-                        // TODO: Visual Feedback
                         error!("{:?}", e);
+                        self.resolved_state = format!("{:#}", e);
+                        entry.error = Some(format!("{:#}", e));
                     }
                 }
 
+                // Indexes shift when an entry is added, so stop pinning.
+                self.pinning = None;
+                self.history.push(entry);
+                self.history_row_states
+                    .resize_with(self.history.entries.len(), HistoryRowState::new);
+
+                self.save_history_command()
+            }
+            Message::RerunEntry(i) => {
+                let entry = match self.history.entries.get(i) {
+                    Some(entry) => entry,
+                    None => return Command::none(),
+                };
+
+                // Use the same source if it still exists.
+                let source = self
+                    .sources
+                    .iter()
+                    .find(|source| source.address() == entry.source)
+                    .cloned()
+                    .unwrap_or(SourceOption::Automatic);
+
+                self.ip_address = entry.address.clone();
+                self.source = source;
+                self.update_off_link_warning();
+
+                self.resolve(self.ip_address.clone(), self.source.clone())
+            }
+            Message::CopyEntry(i) => {
+                if let Some(entry) = self.history.entries.get(i) {
+                    clipboard.write(entry.to_string());
+                }
+                Command::none()
+            }
+            Message::StartPin(i) => {
+                let name = self
+                    .history
+                    .entries
+                    .get(i)
+                    .and_then(|entry| entry.pinned_name.clone())
+                    .unwrap_or_default();
+                self.pinning = Some((i, name));
+                Command::none()
+            }
+            Message::UpdatePinName(name) => {
+                if let Some((_, pin_name)) = self.pinning.as_mut() {
+                    *pin_name = name;
+                }
+                Command::none()
+            }
+            Message::Pin => {
+                let (i, name) = match self.pinning.take() {
+                    Some(pinning) => pinning,
+                    None => return Command::none(),
+                };
+                let name = name.trim();
+                let name = if name.is_empty() {
+                    None
+                } else {
+                    Some(name.to_string())
+                };
+                self.history.set_pinned_name(i, name);
+                self.save_history_command()
+            }
+            Message::Unpin(i) => {
+                self.pinning = None;
+                self.history.set_pinned_name(i, None);
+                self.save_history_command()
+            }
+            Message::HistorySaved(res) => {
+                if let Err(e) = res.as_ref() {
+                    error!("{:?}", e);
+                }
+                Command::none()
+            }
+            Message::AddWakeDevice(_) | Message::ScanPorts(_) => Command::none(),
+            message => {
+                self.sweep.update(message);
                 Command::none()
             }
         }
@@ -100,13 +539,13 @@ impl ResolveArp {
 
     pub fn view(&mut self) -> Element<Message> {
         let title = Text::new("Resolve ARP").size(36);
-        let column = Column::new()
+        let mut column = Column::new()
             .spacing(10)
             .push(title)
             .push(
                 TextInput::new(
                     &mut self.text_input_state,
-                    "Enter ipv4 address",
+                    "Enter ipv4 or ipv6 address, like fe80::1%12",
                     &self.ip_address,
                     Message::UpdateIp,
                 )
@@ -115,6 +554,23 @@ impl ResolveArp {
                 .size(15)
                 .padding(2),
             )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Text::new("Source").size(15))
+                    .push(PickList::new(
+                        &mut self.source_state,
+                        &self.sources[..],
+                        Some(self.source.clone()),
+                        Message::SourceSelected,
+                    )),
+            )
+            .push(
+                Text::new(&self.off_link_warning)
+                    .size(15)
+                    .color(HIGHLIGHT_TEXT_COLOR),
+            )
             .push(
                 Row::new().push(Text::new("Resolved MAC: ").size(15)).push(
                     TextInput::new(&mut self.resolved_mac_state, "", &self.resolved_mac, |_| {
@@ -123,33 +579,682 @@ impl ResolveArp {
                     .style(GreyStyleCopyTextHack)
                     .size(15),
                 ),
+            )
+            .push(
+                Row::new()
+                    .push(Text::new("State: ").size(15))
+                    .push(Text::new(&self.resolved_state).size(15)),
+            )
+            .push(
+                Row::new()
+                    .push(Text::new("Interface: ").size(15))
+                    .push(Text::new(&self.resolved_interface).size(15)),
+            );
+
+        if let Some(device) = self.resolved_device.as_ref() {
+            column = column.push(
+                Button::new(
+                    &mut self.add_wake_device_button_state,
+                    Text::new("Add to Wake-on-LAN").size(15),
+                )
+                .style(ForegroundGreenButtonStyle)
+                .on_press(Message::AddWakeDevice(device.clone())),
+            );
+        }
+
+        column = column
+            .push(history_view(
+                &self.history,
+                &mut self.history_row_states,
+                &self.pinning,
+            ))
+            .push(self.sweep.view(&self.sources));
+
+        Container::new(
+            Scrollable::new(&mut self.scroll_state)
+                .push(Container::new(column).padding(20))
+                .width(Length::Fill),
+        )
+        .style(GreyStyle)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+/// A finished attempt to resolve an address
+#[derive(Debug)]
+pub struct ResolveAttempt {
+    address: String,
+    source: SourceOption,
+    rtt: Duration,
+    result: anyhow::Result<Resolution>,
+}
+
+/// The widget states of an entry in the resolve history
+struct HistoryRowState {
+    rerun_button_state: iced::button::State,
+    copy_button_state: iced::button::State,
+    pin_button_state: iced::button::State,
+    pin_name_input_state: iced::text_input::State,
+}
+
+impl HistoryRowState {
+    fn new() -> Self {
+        Self {
+            rerun_button_state: iced::button::State::new(),
+            copy_button_state: iced::button::State::new(),
+            pin_button_state: iced::button::State::new(),
+            pin_name_input_state: iced::text_input::State::new(),
+        }
+    }
+
+    fn view<'a>(
+        &'a mut self,
+        i: usize,
+        entry: &HistoryEntry,
+        pin_name: Option<&'a str>,
+        now: SystemTime,
+    ) -> Element<'a, Message> {
+        let mut row = Row::new()
+            .spacing(10)
+            .align_items(Align::Center)
+            .push(Space::new(Length::Units(20), Length::Shrink))
+            .push(
+                Text::new(format_relative_time(entry.time, now))
+                    .size(15)
+                    .width(Length::Units(120)),
+            )
+            .push(Text::new(entry.to_string()).size(15).width(Length::Fill))
+            .push(
+                Button::new(&mut self.rerun_button_state, Text::new("Re-run").size(15))
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::RerunEntry(i)),
+            )
+            .push(
+                Button::new(&mut self.copy_button_state, Text::new("Copy").size(15))
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::CopyEntry(i)),
+            );
+
+        row = match (pin_name, entry.pinned_name.is_some()) {
+            (Some(pin_name), _) => row
+                .push(
+                    TextInput::new(
+                        &mut self.pin_name_input_state,
+                        "Device name",
+                        pin_name,
+                        Message::UpdatePinName,
+                    )
+                    .on_submit(Message::Pin)
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(150)),
+                )
+                .push(
+                    Button::new(&mut self.pin_button_state, Text::new("Save").size(15))
+                        .style(ForegroundGreenButtonStyle)
+                        .on_press(Message::Pin),
+                ),
+            (None, true) => row.push(
+                Button::new(&mut self.pin_button_state, Text::new("Unpin").size(15))
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::Unpin(i)),
+            ),
+            (None, false) => row.push(
+                Button::new(&mut self.pin_button_state, Text::new("Pin").size(15))
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::StartPin(i)),
+            ),
+        };
+
+        row.into()
+    }
+}
+
+/// Show pinned devices, then the rest of the resolve history.
+fn history_view<'a>(
+    history: &ResolveHistory,
+    row_states: &'a mut [HistoryRowState],
+    pinning: &'a Option<(usize, String)>,
+) -> Element<'a, Message> {
+    let now = SystemTime::now();
+    let mut pinned_column = Column::new()
+        .spacing(5)
+        .push(Text::new("Pinned Devices").size(24));
+    let mut history_column = Column::new().spacing(5).push(Text::new("History").size(24));
+    let mut num_pinned = 0;
+
+    for (i, (entry, row_state)) in history
+        .entries
+        .iter()
+        .zip(row_states.iter_mut())
+        .enumerate()
+    {
+        let pin_name = match pinning {
+            Some((pin_i, pin_name)) if *pin_i == i => Some(pin_name.as_str()),
+            _ => None,
+        };
+        let row = row_state.view(i, entry, pin_name, now);
+        if entry.pinned_name.is_some() {
+            num_pinned += 1;
+            pinned_column = pinned_column.push(row);
+        } else {
+            history_column = history_column.push(row);
+        }
+    }
+
+    let mut column = Column::new().spacing(10);
+    if num_pinned > 0 {
+        column = column.push(pinned_column);
+    }
+    if history.entries.len() > num_pinned {
+        column = column.push(history_column);
+    }
+    column.into()
+}
+
+/// A running sweep
+struct RunningSweep {
+    id: u64,
+    config: Arc<SweepConfig>,
+}
+
+/// A host that answered a sweep
+struct SweepRow {
+    address: Ipv4Addr,
+    hardware_address: Vec<u8>,
+    hardware_address_text: String,
+    vendor: &'static str,
+    rtt: Duration,
+}
+
+/// Resolves every host in a range, showing the ones that answer
+struct ArpSweep {
+    targets_input: String,
+    source: SourceOption,
+    concurrency_input: String,
+    timeout_input: String,
+
+    running: Option<RunningSweep>,
+    next_id: u64,
+
+    rows: Vec<SweepRow>,
+    num_done: usize,
+    num_targets: usize,
+    status: String,
+
+    sort_column: SortColumn,
+    sort_descending: bool,
+
+    targets_input_state: iced::text_input::State,
+    source_state: iced::pick_list::State<SourceOption>,
+    concurrency_input_state: iced::text_input::State,
+    timeout_input_state: iced::text_input::State,
+    start_button_state: iced::button::State,
+    scan_ports_button_state: iced::button::State,
+    sort_button_states: [iced::button::State; 4],
+}
+
+impl ArpSweep {
+    fn new() -> Self {
+        Self {
+            targets_input: String::new(),
+            source: SourceOption::Automatic,
+            concurrency_input: DEFAULT_CONCURRENCY.to_string(),
+            timeout_input: DEFAULT_TIMEOUT_MS.to_string(),
+
+            running: None,
+            next_id: 0,
+
+            rows: Vec::new(),
+            num_done: 0,
+            num_targets: 0,
+            status: String::new(),
+
+            sort_column: SortColumn::Address,
+            sort_descending: false,
+
+            targets_input_state: iced::text_input::State::new(),
+            source_state: iced::pick_list::State::default(),
+            concurrency_input_state: iced::text_input::State::new(),
+            timeout_input_state: iced::text_input::State::new(),
+            start_button_state: iced::button::State::new(),
+            scan_ports_button_state: iced::button::State::new(),
+            sort_button_states: Default::default(),
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        match self.running.as_ref() {
+            Some(running) => sweep_subscription(running.id, running.config.clone())
+                .map(|(id, event)| Message::Sweep(id, event)),
+            None => Subscription::none(),
+        }
+    }
+
+    /// Validate the inputs.
+    fn parse_config(&self) -> anyhow::Result<SweepConfig> {
+        let targets = parse_targets(&self.targets_input).context("invalid targets")?;
+        let concurrency: usize = self
+            .concurrency_input
+            .trim()
+            .parse()
+            .context("invalid concurrency")?;
+        if concurrency == 0 || concurrency > MAX_CONCURRENCY {
+            anyhow::bail!("concurrency must be between 1 and {}", MAX_CONCURRENCY);
+        }
+        let timeout_ms: u64 = self
+            .timeout_input
+            .trim()
+            .parse()
+            .context("invalid timeout")?;
+        if timeout_ms == 0 {
+            anyhow::bail!("timeout must be at least 1 ms");
+        }
+
+        Ok(SweepConfig {
+            targets,
+            source: self.source.address(),
+            concurrency,
+            timeout: Duration::from_millis(timeout_ms),
+        })
+    }
+
+    fn update(&mut self, message: Message) {
+        match message {
+            Message::UpdateSweepTargets(targets_input) => {
+                self.targets_input = targets_input;
+            }
+            Message::SweepSourceSelected(source) => {
+                // Default to sweeping the subnet of the source.
+                if let SourceOption::Address {
+                    address,
+                    prefix_length,
+                    ..
+                } = &source
+                {
+                    if self.targets_input.trim().is_empty() {
+                        if let Some(info) = SubnetInfo::new(IpAddr::V4(*address), *prefix_length) {
+                            self.targets_input = format!("{}/{}", info.network, prefix_length);
+                        }
+                    }
+                }
+                self.source = source;
+            }
+            Message::UpdateSweepConcurrency(concurrency_input) => {
+                self.concurrency_input = concurrency_input;
+            }
+            Message::UpdateSweepTimeout(timeout_input) => {
+                self.timeout_input = timeout_input;
+            }
+            Message::StartSweep => match self.parse_config() {
+                Ok(config) => {
+                    info!(
+                        "Starting arp sweep of {} host(s) from {}",
+                        config.targets.len(),
+                        self.source
+                    );
+                    self.rows.clear();
+                    self.num_done = 0;
+                    self.num_targets = config.targets.len();
+                    self.status = String::new();
+                    self.running = Some(RunningSweep {
+                        id: self.next_id,
+                        config: Arc::new(config),
+                    });
+                    self.next_id += 1;
+                }
+                Err(e) => {
+                    self.status = format!("{:#}", e);
+                }
+            },
+            Message::CancelSweep => {
+                // Dropping the subscription cancels the sweep.
+                self.running = None;
+                self.status = "Cancelled".to_string();
+            }
+            Message::Sweep(id, event) => {
+                // Ignore events from sweeps that were cancelled.
+                if self.running.as_ref().map(|running| running.id) != Some(id) {
+                    return;
+                }
+
+                match event {
+                    SweepEvent::Result(result) => {
+                        self.num_done += 1;
+                        if let Ok(hardware_address) = result.result {
+                            let hardware_address_text =
+                                format_mac_address_to_string(&hardware_address)
+                                    .trim_end()
+                                    .to_string();
+                            let vendor =
+                                crate::oui::vendor_name(&hardware_address).unwrap_or_default();
+                            self.rows.push(SweepRow {
+                                address: result.address,
+                                hardware_address,
+                                hardware_address_text,
+                                vendor,
+                                rtt: result.rtt,
+                            });
+                            self.sort_rows();
+                        }
+                    }
+                    SweepEvent::Finished => {
+                        info!("Finished arp sweep, found {} host(s)", self.rows.len());
+                        self.running = None;
+                        self.status = "Done".to_string();
+                    }
+                }
+            }
+            Message::SortBy(sort_column) => {
+                if self.sort_column == sort_column {
+                    self.sort_descending = !self.sort_descending;
+                } else {
+                    self.sort_column = sort_column;
+                    self.sort_descending = false;
+                }
+                self.sort_rows();
+            }
+            Message::Nop
+            | Message::UpdateIp(_)
+            | Message::SourceSelected(_)
+            | Message::ResolveArp
+            | Message::ResolveArpComplete(_)
+            | Message::RerunEntry(_)
+            | Message::CopyEntry(_)
+            | Message::StartPin(_)
+            | Message::UpdatePinName(_)
+            | Message::Pin
+            | Message::Unpin(_)
+            | Message::HistorySaved(_)
+            | Message::AddWakeDevice(_)
+            | Message::ScanPorts(_) => {}
+        }
+    }
+
+    fn sort_rows(&mut self) {
+        let sort_column = self.sort_column;
+        self.rows.sort_by(|a, b| match sort_column {
+            SortColumn::Address => a.address.cmp(&b.address),
+            SortColumn::HardwareAddress => a.hardware_address.cmp(&b.hardware_address),
+            SortColumn::Vendor => a.vendor.cmp(b.vendor),
+            SortColumn::Rtt => a.rtt.cmp(&b.rtt),
+        });
+        if self.sort_descending {
+            self.rows.reverse();
+        }
+    }
+
+    fn view<'a>(&'a mut self, sources: &'a [SourceOption]) -> Element<'a, Message> {
+        let mut column = Column::new()
+            .spacing(10)
+            .push(Text::new("ARP Sweep").size(24));
+
+        let start_button = if self.running.is_some() {
+            Button::new(&mut self.start_button_state, Text::new("Cancel").size(15))
+                .style(ForegroundGreenButtonStyle)
+                .on_press(Message::CancelSweep)
+        } else {
+            Button::new(&mut self.start_button_state, Text::new("Start").size(15))
+                .style(ForegroundGreenButtonStyle)
+                .on_press(Message::StartSweep)
+        };
+
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(
+                    TextInput::new(
+                        &mut self.targets_input_state,
+                        "192.168.1.0/24 or 192.168.1.10-50",
+                        &self.targets_input,
+                        Message::UpdateSweepTargets,
+                    )
+                    .on_submit(Message::StartSweep)
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(250)),
+                )
+                .push(Text::new("Source").size(15))
+                .push(PickList::new(
+                    &mut self.source_state,
+                    sources,
+                    Some(self.source.clone()),
+                    Message::SweepSourceSelected,
+                ))
+                .push(Text::new("Concurrency").size(15))
+                .push(
+                    TextInput::new(
+                        &mut self.concurrency_input_state,
+                        "",
+                        &self.concurrency_input,
+                        Message::UpdateSweepConcurrency,
+                    )
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(50)),
+                )
+                .push(Text::new("Timeout (ms)").size(15))
+                .push(
+                    TextInput::new(
+                        &mut self.timeout_input_state,
+                        "",
+                        &self.timeout_input,
+                        Message::UpdateSweepTimeout,
+                    )
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(60)),
+                )
+                .push(start_button),
+        );
+
+        let progress = if self.num_targets == 0 {
+            self.status.clone()
+        } else {
+            format!(
+                "Swept {} of {}, found {}. {}",
+                self.num_done,
+                self.num_targets,
+                self.rows.len(),
+                self.status
+            )
+        };
+        column = column.push(Text::new(progress).size(15));
+
+        if !self.rows.is_empty() {
+            let hosts = self
+                .rows
+                .iter()
+                .map(|row| IpAddr::V4(row.address))
+                .collect();
+            column = column.push(
+                Button::new(
+                    &mut self.scan_ports_button_state,
+                    Text::new("Scan Ports").size(15),
+                )
+                .style(ForegroundGreenButtonStyle)
+                .on_press(Message::ScanPorts(hosts)),
+            );
+        }
+
+        let [address_state, hardware_address_state, vendor_state, rtt_state] =
+            &mut self.sort_button_states;
+        let sort_column = self.sort_column;
+        let sort_descending = self.sort_descending;
+        let header_button = |state, label: &str, column: SortColumn, width: u16| {
+            let label = if column == sort_column {
+                format!("{} {}", label, if sort_descending { "v" } else { "^" })
+            } else {
+                label.to_string()
+            };
+            Button::new(state, Text::new(label).size(15))
+                .style(ForegroundGreenButtonStyle)
+                .width(Length::Units(width))
+                .on_press(Message::SortBy(column))
+        };
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .push(header_button(address_state, "IP", SortColumn::Address, 150))
+                .push(header_button(
+                    hardware_address_state,
+                    "MAC",
+                    SortColumn::HardwareAddress,
+                    200,
+                ))
+                .push(header_button(
+                    vendor_state,
+                    "Vendor",
+                    SortColumn::Vendor,
+                    200,
+                ))
+                .push(header_button(rtt_state, "RTT", SortColumn::Rtt, 100)),
+        );
+
+        for row in self.rows.iter() {
+            column = column.push(
+                Row::new()
+                    .spacing(10)
+                    .push(
+                        Text::new(row.address.to_string())
+                            .size(15)
+                            .width(Length::Units(150)),
+                    )
+                    .push(
+                        Text::new(&row.hardware_address_text)
+                            .size(15)
+                            .width(Length::Units(200)),
+                    )
+                    .push(Text::new(row.vendor).size(15).width(Length::Units(200)))
+                    .push(
+                        Text::new(format!("{} ms", row.rtt.as_millis()))
+                            .size(15)
+                            .width(Length::Units(100)),
+                    ),
             );
+        }
 
-        Container::new(Container::new(column).padding(20))
-            .style(GreyStyle)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .into()
+        column.into()
     }
 }
 
-pub async fn resolve_arp(ip_address: &str) -> anyhow::Result<(u64, usize)> {
-    let ip = ip_address
-        .parse::<Ipv4Addr>()
-        .context("invalid ipv4 address")?;
+/// The link-layer address of a resolved neighbor
+#[derive(Debug)]
+pub struct Resolution {
+    pub hardware_address: Vec<u8>,
+
+    /// The neighbor cache state, only known for addresses resolved with `ResolveIpNetEntry2`
+    pub state: Option<&'static str>,
+
+    pub interface_index: u32,
+    pub interface_name: Option<String>,
+}
+
+/// Parse an ipv4 or ipv6 address, with an optional zone index for ipv6 like `fe80::1%12`.
+pub fn parse_scoped_address(input: &str) -> anyhow::Result<(IpAddr, Option<u32>)> {
+    let input = input.trim();
+    let (address, scope_id) = match input.split_once('%') {
+        Some((address, scope_id)) => (address, Some(scope_id)),
+        None => (input, None),
+    };
+
+    let address: IpAddr = address.parse().context("invalid ip address")?;
+    let scope_id = scope_id
+        .map(|scope_id| {
+            if address.is_ipv4() {
+                anyhow::bail!("ipv4 addresses cannot have a zone index");
+            }
+            scope_id.parse::<u32>().context("invalid zone index")
+        })
+        .transpose()?;
+
+    Ok((address, scope_id))
+}
+
+/// Resolve the link-layer address of an ipv4 address with arp, or an ipv6 address with neighbor solicitation.
+///
+/// The source is only used for ipv4 addresses.
+pub async fn resolve_address(input: &str, source: SourceOption) -> anyhow::Result<Resolution> {
+    let (address, scope_id) = parse_scoped_address(input)?;
 
     let start = Instant::now();
-    let response = tokio::task::spawn_blocking(move || iphlpapi::send_arp(ip, None))
-        .await
-        .context("tokio task failed to join")?
-        .context("failed to resolve arp");
+    let resolution =
+        tokio::task::spawn_blocking(move || resolve_address_blocking(address, scope_id, source))
+            .await
+            .context("tokio task failed to join")??;
+
+    info!("Resolved `{}` in {:?}", input.trim(), start.elapsed());
+
+    Ok(resolution)
+}
 
-    info!("Executed `send_arp({})` in {:?}", ip, start.elapsed());
+fn resolve_address_blocking(
+    address: IpAddr,
+    scope_id: Option<u32>,
+    source: SourceOption,
+) -> anyhow::Result<Resolution> {
+    let interface_index = match (address, scope_id, &source) {
+        (_, Some(scope_id), _) => scope_id,
+        (IpAddr::V6(_), None, SourceOption::Address { .. }) => {
+            anyhow::bail!("source addresses can only be used with ipv4 targets");
+        }
+        (
+            IpAddr::V4(_),
+            None,
+            SourceOption::Address {
+                interface_index, ..
+            },
+        ) => *interface_index,
+        (IpAddr::V6(ip), None, _) if ip.segments()[0] & 0xffc0 == 0xfe80 => {
+            anyhow::bail!("link-local addresses need a zone index, like `{}%12`", ip);
+        }
+        (address, None, _) => best_interface_index(address)
+            .context("failed to find an interface to reach the address")?,
+    };
 
-    let (mac, mac_len) = response?;
-    let mac_len: usize = mac_len
-        .try_into()
-        .context("mac_len cannot fit in a usize")?;
+    let (hardware_address, state) = match address {
+        IpAddr::V4(ip) => {
+            let (mac, mac_len) =
+                iphlpapi::send_arp(ip, source.address()).context("failed to resolve arp")?;
+            let mac_len: usize = mac_len
+                .try_into()
+                .context("mac_len cannot fit in a usize")?;
+            let mac = mac.to_ne_bytes();
+            let hardware_address = mac.get(..mac_len).context("mac_len is too large")?.to_vec();
+            (hardware_address, None)
+        }
+        IpAddr::V6(_) => {
+            let neighbor =
+                resolve_neighbor(address, interface_index).context("failed to resolve neighbor")?;
+            let state = neighbor.state_name();
+            (neighbor.physical_address, Some(state))
+        }
+    };
+
+    let interface_name = match crate::adapter_addresses::get_adapter_addresses() {
+        Ok(adapters) => adapters
+            .iter()
+            .find(|adapter| {
+                adapter.if_index == interface_index || adapter.ipv6_if_index == interface_index
+            })
+            .map(|adapter| adapter.friendly_name.clone()),
+        Err(e) => {
+            warn!("Failed to get adapter addresses: {}", e);
+            None
+        }
+    };
 
-    Ok((mac, mac_len))
+    Ok(Resolution {
+        hardware_address,
+        state,
+        interface_index,
+        interface_name,
+    })
 }
@@ -0,0 +1,110 @@
+use crate::UserSettings;
+use anyhow::Context;
+use std::{
+    net::Ipv4Addr,
+    path::PathBuf,
+    time::{
+        Duration,
+        SystemTime,
+    },
+};
+
+/// The name of the history file in the data dir
+const HISTORY_FILE_NAME: &str = "resolve_history.json";
+
+/// The most unpinned entries that are kept
+const MAX_UNPINNED_ENTRIES: usize = 200;
+
+/// A past attempt to resolve an address
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HistoryEntry {
+    /// The address as it was entered, including any zone index
+    pub address: String,
+    pub source: Option<Ipv4Addr>,
+
+    pub hardware_address: Option<String>,
+    pub vendor: Option<String>,
+    pub interface: Option<String>,
+    pub rtt: Duration,
+    pub time: SystemTime,
+
+    /// Why the resolution failed, if it did
+    pub error: Option<String>,
+
+    /// The device name, if this entry is pinned
+    pub pinned_name: Option<String>,
+}
+
+impl std::fmt::Display for HistoryEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(name) = self.pinned_name.as_ref() {
+            write!(f, "{}: ", name)?;
+        }
+
+        write!(f, "{}", self.address)?;
+
+        match (self.hardware_address.as_ref(), self.error.as_ref()) {
+            (Some(hardware_address), _) => write!(f, " is at {}", hardware_address)?,
+            (None, Some(error)) => write!(f, " failed: {}", error)?,
+            (None, None) => {}
+        }
+
+        if let Some(vendor) = self.vendor.as_ref() {
+            write!(f, " ({})", vendor)?;
+        }
+
+        if let Some(interface) = self.interface.as_ref() {
+            write!(f, " via {}", interface)?;
+        }
+
+        write!(f, " in {} ms", self.rtt.as_millis())
+    }
+}
+
+/// The persisted list of resolution attempts, newest first
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct ResolveHistory {
+    pub entries: Vec<HistoryEntry>,
+}
+
+impl ResolveHistory {
+    pub fn path() -> anyhow::Result<PathBuf> {
+        Ok(UserSettings::data_dir()?.join(HISTORY_FILE_NAME))
+    }
+
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::path()?;
+        let data = std::fs::read_to_string(path).context("failed to read data")?;
+        serde_json::from_str(&data).context("failed to deserialize data")
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        std::fs::create_dir_all(UserSettings::data_dir()?).context("failed to create data dir")?;
+
+        let path = Self::path()?;
+        let data = serde_json::to_string_pretty(self).context("failed to serialize")?;
+        std::fs::write(path, data).context("failed to write")?;
+        Ok(())
+    }
+
+    /// Add an entry, dropping the oldest unpinned entries if there are too many.
+    pub fn push(&mut self, entry: HistoryEntry) {
+        self.entries.insert(0, entry);
+
+        let mut num_unpinned = 0;
+        self.entries.retain(|entry| {
+            if entry.pinned_name.is_some() {
+                return true;
+            }
+            num_unpinned += 1;
+            num_unpinned <= MAX_UNPINNED_ENTRIES
+        });
+    }
+
+    /// Pin an entry as a named device, or unpin it with `None`.
+    pub fn set_pinned_name(&mut self, index: usize, name: Option<String>) {
+        if let Some(entry) = self.entries.get_mut(index) {
+            entry.pinned_name = name;
+        }
+    }
+}
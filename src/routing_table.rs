@@ -0,0 +1,761 @@
+use crate::{
+    adapter_addresses::InterfaceOption,
+    ip_config::AddressConfig,
+    sockaddr::{
+        ip_addr_to_sockaddr_inet,
+        sockaddr_inet_to_ip_addr,
+    },
+    style::{
+        ForegroundGreenButtonStyle,
+        ForegroundGreenTextInputStyle,
+    },
+    GreyStyle,
+};
+use anyhow::Context;
+use iced::{
+    Align,
+    Button,
+    Clipboard,
+    Column,
+    Command,
+    Container,
+    Element,
+    Length,
+    PickList,
+    Row,
+    Scrollable,
+    Space,
+    Text,
+    TextInput,
+};
+use log::{
+    error,
+    info,
+    warn,
+};
+use std::{
+    collections::HashMap,
+    net::{
+        IpAddr,
+        Ipv4Addr,
+        Ipv6Addr,
+    },
+    sync::Arc,
+    time::Instant,
+};
+use winapi::shared::{
+    netioapi::{
+        CreateIpForwardEntry2,
+        DeleteIpForwardEntry2,
+        FreeMibTable,
+        GetIpForwardEntry2,
+        GetIpForwardTable2,
+        InitializeIpForwardEntry,
+        SetIpForwardEntry2,
+        MIB_IPFORWARD_ROW2,
+        PMIB_IPFORWARD_TABLE2,
+    },
+    nldef::{
+        MIB_IPPROTO_BBN,
+        MIB_IPPROTO_BGP,
+        MIB_IPPROTO_DHCP,
+        MIB_IPPROTO_DVMRP,
+        MIB_IPPROTO_EGP,
+        MIB_IPPROTO_ES_IS,
+        MIB_IPPROTO_GGP,
+        MIB_IPPROTO_HELLO,
+        MIB_IPPROTO_ICMP,
+        MIB_IPPROTO_IDPR,
+        MIB_IPPROTO_IS_IS,
+        MIB_IPPROTO_LOCAL,
+        MIB_IPPROTO_NETMGMT,
+        MIB_IPPROTO_NT_AUTOSTATIC,
+        MIB_IPPROTO_NT_STATIC,
+        MIB_IPPROTO_NT_STATIC_NON_DOD,
+        MIB_IPPROTO_OSPF,
+        MIB_IPPROTO_OTHER,
+        MIB_IPPROTO_RIP,
+        MIB_IPPROTO_RPL,
+    },
+    winerror::NO_ERROR,
+    ws2def::AF_UNSPEC,
+};
+
+/// An entry in the forwarding table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    pub destination: AddressConfig,
+    pub next_hop: IpAddr,
+    pub interface_luid: u64,
+    pub interface_index: u32,
+    pub metric: u32,
+    pub protocol: u32,
+}
+
+impl Route {
+    /// Whether this route was added manually, and so may be edited.
+    pub fn is_static(&self) -> bool {
+        self.protocol == MIB_IPPROTO_NETMGMT
+    }
+
+    /// Get a human readable name for the protocol that added this route.
+    pub fn protocol_name(&self) -> &'static str {
+        match self.protocol {
+            MIB_IPPROTO_OTHER => "Other",
+            MIB_IPPROTO_LOCAL => "Local",
+            MIB_IPPROTO_NETMGMT => "Static",
+            MIB_IPPROTO_ICMP => "ICMP",
+            MIB_IPPROTO_EGP => "EGP",
+            MIB_IPPROTO_GGP => "GGP",
+            MIB_IPPROTO_HELLO => "Hello",
+            MIB_IPPROTO_RIP => "RIP",
+            MIB_IPPROTO_IS_IS => "IS-IS",
+            MIB_IPPROTO_ES_IS => "ES-IS",
+            MIB_IPPROTO_BBN => "BBN",
+            MIB_IPPROTO_OSPF => "OSPF",
+            MIB_IPPROTO_BGP => "BGP",
+            MIB_IPPROTO_IDPR => "IDPR",
+            MIB_IPPROTO_RPL => "RPL",
+            MIB_IPPROTO_DHCP => "DHCP",
+            MIB_IPPROTO_DVMRP => "DVMRP",
+            MIB_IPPROTO_NT_AUTOSTATIC => "Auto Static",
+            MIB_IPPROTO_NT_STATIC => "NT Static",
+            MIB_IPPROTO_NT_STATIC_NON_DOD => "NT Static (Non-DOD)",
+            _ => "Unknown",
+        }
+    }
+
+    fn from_raw(row: &MIB_IPFORWARD_ROW2) -> Option<Self> {
+        Some(Self {
+            destination: AddressConfig {
+                address: sockaddr_inet_to_ip_addr(&row.DestinationPrefix.Prefix)?,
+                prefix_length: row.DestinationPrefix.PrefixLength,
+            },
+            next_hop: sockaddr_inet_to_ip_addr(&row.NextHop)?,
+            interface_luid: row.InterfaceLuid.Value,
+            interface_index: row.InterfaceIndex,
+            metric: row.Metric,
+            protocol: row.Protocol,
+        })
+    }
+}
+
+/// Get the ipv4 and ipv6 forwarding tables.
+pub fn get_routes() -> std::io::Result<Vec<Route>> {
+    let mut table: PMIB_IPFORWARD_TABLE2 = std::ptr::null_mut();
+    let code = unsafe { GetIpForwardTable2(AF_UNSPEC as u16, &mut table) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+
+    let routes = unsafe {
+        let rows =
+            std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize);
+        let routes = rows.iter().filter_map(Route::from_raw).collect();
+        FreeMibTable(table.cast());
+        routes
+    };
+
+    Ok(routes)
+}
+
+/// Make a forwarding row that identifies a route.
+fn route_row(luid: u64, destination: &AddressConfig, next_hop: IpAddr) -> MIB_IPFORWARD_ROW2 {
+    let mut row: MIB_IPFORWARD_ROW2 = unsafe { std::mem::zeroed() };
+    unsafe {
+        InitializeIpForwardEntry(&mut row);
+    }
+    row.InterfaceLuid.Value = luid;
+    row.DestinationPrefix.Prefix = ip_addr_to_sockaddr_inet(destination.address);
+    row.DestinationPrefix.PrefixLength = destination.prefix_length;
+    row.NextHop = ip_addr_to_sockaddr_inet(next_hop);
+    row.Protocol = MIB_IPPROTO_NETMGMT;
+    row
+}
+
+/// Add a static route.
+///
+/// Use an unspecified next hop for an on-link route.
+pub fn add_route(
+    luid: u64,
+    destination: &AddressConfig,
+    next_hop: IpAddr,
+    metric: u32,
+) -> std::io::Result<()> {
+    let mut row = route_row(luid, destination, next_hop);
+    row.Metric = metric;
+
+    let code = unsafe { CreateIpForwardEntry2(&row) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+    Ok(())
+}
+
+/// Delete a route.
+pub fn delete_route(
+    luid: u64,
+    destination: &AddressConfig,
+    next_hop: IpAddr,
+) -> std::io::Result<()> {
+    let row = route_row(luid, destination, next_hop);
+    let code = unsafe { DeleteIpForwardEntry2(&row) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+    Ok(())
+}
+
+/// Change the metric of a route.
+pub fn set_route_metric(
+    luid: u64,
+    destination: &AddressConfig,
+    next_hop: IpAddr,
+    metric: u32,
+) -> std::io::Result<()> {
+    // Set replaces every field, so start from the existing route.
+    let mut row = route_row(luid, destination, next_hop);
+    let code = unsafe { GetIpForwardEntry2(&mut row) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+
+    row.Metric = metric;
+    let code = unsafe { SetIpForwardEntry2(&row) };
+    if code != NO_ERROR {
+        return Err(std::io::Error::from_raw_os_error(code as i32));
+    }
+    Ok(())
+}
+
+/// Get the default route destination for the family of `address`, like "0.0.0.0/0".
+pub fn default_destination(address: IpAddr) -> AddressConfig {
+    let address = match address {
+        IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+    AddressConfig {
+        address,
+        prefix_length: 0,
+    }
+}
+
+/// An action on the routing table
+#[derive(Debug, Clone)]
+enum RouteAction {
+    Add {
+        luid: u64,
+        destination: AddressConfig,
+        next_hop: IpAddr,
+        metric: u32,
+    },
+    Delete(Route),
+    SetMetric(Route, u32),
+}
+
+impl RouteAction {
+    /// Run this action on a blocking thread.
+    async fn run(self) -> anyhow::Result<()> {
+        tokio::task::spawn_blocking(move || {
+            match &self {
+                Self::Add {
+                    luid,
+                    destination,
+                    next_hop,
+                    metric,
+                } => add_route(*luid, destination, *next_hop, *metric)
+                    .context("failed to add route")?,
+                Self::Delete(route) => {
+                    delete_route(route.interface_luid, &route.destination, route.next_hop)
+                        .context("failed to delete route")?
+                }
+                Self::SetMetric(route, metric) => set_route_metric(
+                    route.interface_luid,
+                    &route.destination,
+                    route.next_hop,
+                    *metric,
+                )
+                .context("failed to set route metric")?,
+            }
+            info!("Executed route action {:?}", self);
+
+            Ok(())
+        })
+        .await
+        .context("tokio task failed to join")?
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Refresh,
+
+    DeleteRoute(usize),
+    UpdateMetricInput(usize, String),
+    SetMetric(usize),
+
+    UpdateDestinationInput(String),
+    UpdateNextHopInput(String),
+    InterfaceSelected(InterfaceOption),
+    UpdateNewMetricInput(String),
+    AddRoute,
+
+    ActionComplete(Arc<anyhow::Result<()>>),
+
+    Nop,
+}
+
+pub struct RoutingTable {
+    routes: Result<Vec<RouteState>, String>,
+    interfaces: Vec<InterfaceOption>,
+
+    /// The route that the user has to confirm deletion of
+    pending_delete: Option<usize>,
+    is_busy: bool,
+    status: String,
+
+    destination_input: String,
+    next_hop_input: String,
+    interface: Option<InterfaceOption>,
+    new_metric_input: String,
+
+    scroll_state: iced::scrollable::State,
+    refresh_button_state: iced::button::State,
+    destination_input_state: iced::text_input::State,
+    next_hop_input_state: iced::text_input::State,
+    interface_state: iced::pick_list::State<InterfaceOption>,
+    new_metric_input_state: iced::text_input::State,
+    add_button_state: iced::button::State,
+}
+
+impl RoutingTable {
+    pub fn new() -> Self {
+        let mut ret = Self {
+            routes: Ok(Vec::new()),
+            interfaces: Vec::new(),
+
+            pending_delete: None,
+            is_busy: false,
+            status: String::new(),
+
+            destination_input: String::new(),
+            next_hop_input: String::new(),
+            interface: None,
+            new_metric_input: String::new(),
+
+            scroll_state: iced::scrollable::State::new(),
+            refresh_button_state: iced::button::State::new(),
+            destination_input_state: iced::text_input::State::new(),
+            next_hop_input_state: iced::text_input::State::new(),
+            interface_state: iced::pick_list::State::default(),
+            new_metric_input_state: iced::text_input::State::new(),
+            add_button_state: iced::button::State::new(),
+        };
+        ret.refresh_routes();
+        ret
+    }
+
+    pub fn refresh_routes(&mut self) {
+        let start = Instant::now();
+
+        // Interfaces are shown with the same names as in the adapter info tab.
+        self.interfaces = match crate::adapter_addresses::get_adapter_addresses() {
+            Ok(adapters) => adapters.iter().map(InterfaceOption::new).collect(),
+            Err(e) => {
+                warn!("Failed to get adapter addresses: {}", e);
+                Vec::new()
+            }
+        };
+        let names: HashMap<u64, &str> = self
+            .interfaces
+            .iter()
+            .map(|interface| (interface.luid, interface.name.as_str()))
+            .collect();
+
+        self.routes = get_routes()
+            .map(|mut routes| {
+                routes.sort_by(|a, b| {
+                    (
+                        a.destination.address.is_ipv6(),
+                        a.destination.address,
+                        a.metric,
+                    )
+                        .cmp(&(
+                            b.destination.address.is_ipv6(),
+                            b.destination.address,
+                            b.metric,
+                        ))
+                });
+                routes
+                    .into_iter()
+                    .map(|route| {
+                        let interface_name = names.get(&route.interface_luid).map_or_else(
+                            || format!("Index {}", route.interface_index),
+                            |name| name.to_string(),
+                        );
+                        RouteState::new(route, interface_name)
+                    })
+                    .collect()
+            })
+            .map_err(|e| e.to_string());
+        self.pending_delete = None;
+
+        info!("Got routes in {:?}", start.elapsed());
+    }
+
+    fn run_action(&mut self, action: RouteAction) -> Command<Message> {
+        if self.is_busy {
+            return Command::none();
+        }
+        self.is_busy = true;
+        self.status = "Working...".to_string();
+
+        Command::perform(action.run(), |result| {
+            Message::ActionComplete(Arc::new(result))
+        })
+    }
+
+    /// Validate the new route inputs.
+    fn parse_new_route(&self) -> anyhow::Result<RouteAction> {
+        // `Message::AddRoute` already replaced a destination with host bits set by its network, so this only fails on an invalid prefix.
+        let destination = self
+            .destination_input
+            .parse::<AddressConfig>()
+            .context("invalid destination")?
+            .network()
+            .context("invalid destination prefix length")?;
+
+        // An empty next hop makes an on-link route.
+        let next_hop_input = self.next_hop_input.trim();
+        let next_hop = if next_hop_input.is_empty() {
+            default_destination(destination.address).address
+        } else {
+            next_hop_input
+                .parse::<IpAddr>()
+                .context("invalid next hop")?
+        };
+        if next_hop.is_ipv4() != destination.address.is_ipv4() {
+            anyhow::bail!("the next hop and destination must be the same ip version");
+        }
+
+        let interface = self.interface.as_ref().context("no interface selected")?;
+
+        let new_metric_input = self.new_metric_input.trim();
+        let metric = if new_metric_input.is_empty() {
+            0
+        } else {
+            new_metric_input.parse().context("invalid metric")?
+        };
+
+        Ok(RouteAction::Add {
+            luid: interface.luid,
+            destination,
+            next_hop,
+            metric,
+        })
+    }
+
+    pub fn update(&mut self, message: Message, _clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::Refresh => {
+                self.refresh_routes();
+                Command::none()
+            }
+            Message::DeleteRoute(i) => {
+                // The first press arms the button, the second deletes.
+                if self.pending_delete != Some(i) {
+                    self.pending_delete = Some(i);
+                    return Command::none();
+                }
+                self.pending_delete = None;
+
+                match self.routes.as_ref().ok().and_then(|routes| routes.get(i)) {
+                    Some(route_state) => {
+                        let action = RouteAction::Delete(route_state.route.clone());
+                        self.run_action(action)
+                    }
+                    None => Command::none(),
+                }
+            }
+            Message::UpdateMetricInput(i, metric_input) => {
+                if let Some(route_state) = self
+                    .routes
+                    .as_mut()
+                    .ok()
+                    .and_then(|routes| routes.get_mut(i))
+                {
+                    route_state.metric_input = metric_input;
+                }
+                Command::none()
+            }
+            Message::SetMetric(i) => {
+                let route_state = match self.routes.as_ref().ok().and_then(|routes| routes.get(i)) {
+                    Some(route_state) => route_state,
+                    None => return Command::none(),
+                };
+
+                match route_state.metric_input.trim().parse::<u32>() {
+                    Ok(metric) => {
+                        let action = RouteAction::SetMetric(route_state.route.clone(), metric);
+                        self.run_action(action)
+                    }
+                    Err(e) => {
+                        self.status = format!("Invalid metric: {}", e);
+                        Command::none()
+                    }
+                }
+            }
+            Message::UpdateDestinationInput(destination_input) => {
+                self.destination_input = destination_input;
+                Command::none()
+            }
+            Message::UpdateNextHopInput(next_hop_input) => {
+                self.next_hop_input = next_hop_input;
+                Command::none()
+            }
+            Message::InterfaceSelected(interface) => {
+                self.interface = Some(interface);
+                Command::none()
+            }
+            Message::UpdateNewMetricInput(new_metric_input) => {
+                self.new_metric_input = new_metric_input;
+                Command::none()
+            }
+            Message::AddRoute => {
+                // Routes are to networks, so a destination with host bits set is normalized and has to be confirmed.
+                if let Some((destination, network)) = self
+                    .destination_input
+                    .parse::<AddressConfig>()
+                    .ok()
+                    .and_then(|destination| Some((destination, destination.network()?)))
+                {
+                    if network != destination {
+                        self.destination_input = network.to_string();
+                        self.status = format!(
+                            "{} has host bits set, press Add again to add {}",
+                            destination, network
+                        );
+                        return Command::none();
+                    }
+                }
+
+                match self.parse_new_route() {
+                    Ok(action) => self.run_action(action),
+                    Err(e) => {
+                        self.status = format!("{:#}", e);
+                        Command::none()
+                    }
+                }
+            }
+            Message::ActionComplete(result) => {
+                self.is_busy = false;
+                match result.as_ref() {
+                    Ok(()) => {
+                        self.status = "Done".to_string();
+                    }
+                    Err(e) => {
+                        error!("{:?}", e);
+                        self.status = format!("{:#}", e);
+                    }
+                }
+                self.refresh_routes();
+                Command::none()
+            }
+            Message::Nop => Command::none(),
+        }
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        let title = Text::new("Routing Table").size(36);
+        let mut column = Column::new().spacing(10).push(title);
+
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(
+                    TextInput::new(
+                        &mut self.destination_input_state,
+                        "10.0.0.0/24",
+                        &self.destination_input,
+                        Message::UpdateDestinationInput,
+                    )
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(150)),
+                )
+                .push(
+                    TextInput::new(
+                        &mut self.next_hop_input_state,
+                        "Next hop (on-link if empty)",
+                        &self.next_hop_input,
+                        Message::UpdateNextHopInput,
+                    )
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(200)),
+                )
+                .push(PickList::new(
+                    &mut self.interface_state,
+                    &self.interfaces[..],
+                    self.interface.clone(),
+                    Message::InterfaceSelected,
+                ))
+                .push(
+                    TextInput::new(
+                        &mut self.new_metric_input_state,
+                        "Metric",
+                        &self.new_metric_input,
+                        Message::UpdateNewMetricInput,
+                    )
+                    .on_submit(Message::AddRoute)
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(60)),
+                )
+                .push(
+                    Button::new(&mut self.add_button_state, Text::new("Add Route").size(15))
+                        .style(ForegroundGreenButtonStyle)
+                        .on_press(Message::AddRoute),
+                ),
+        );
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(
+                    Button::new(
+                        &mut self.refresh_button_state,
+                        Text::new("Refresh").size(15),
+                    )
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::Refresh),
+                )
+                .push(Text::new(&self.status).size(15)),
+        );
+
+        match self.routes.as_mut() {
+            Ok(routes) => {
+                let pending_delete = self.pending_delete;
+                let is_busy = self.is_busy;
+                for (i, route_state) in routes.iter_mut().enumerate() {
+                    column = column.push(route_state.view(i, pending_delete == Some(i), is_busy));
+                }
+            }
+            Err(e) => {
+                column = column.push(Text::new(format!("Failed to get routes: {}", e)));
+            }
+        }
+
+        Container::new(
+            Scrollable::new(&mut self.scroll_state)
+                .push(Container::new(column).padding(20))
+                .width(Length::Fill),
+        )
+        .style(GreyStyle)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+struct RouteState {
+    route: Route,
+    interface_name: String,
+
+    metric_input: String,
+
+    metric_input_state: iced::text_input::State,
+    set_metric_button_state: iced::button::State,
+    delete_button_state: iced::button::State,
+}
+
+impl RouteState {
+    fn new(route: Route, interface_name: String) -> Self {
+        Self {
+            metric_input: route.metric.to_string(),
+            route,
+            interface_name,
+
+            metric_input_state: iced::text_input::State::new(),
+            set_metric_button_state: iced::button::State::new(),
+            delete_button_state: iced::button::State::new(),
+        }
+    }
+
+    fn view(&mut self, i: usize, is_pending_delete: bool, is_busy: bool) -> Element<Message> {
+        let route = &self.route;
+        let mut row = Row::new()
+            .spacing(10)
+            .align_items(Align::Center)
+            .push(
+                Text::new(route.destination.to_string())
+                    .size(15)
+                    .width(Length::Units(250)),
+            )
+            .push(
+                Text::new(route.next_hop.to_string())
+                    .size(15)
+                    .width(Length::Units(200)),
+            )
+            .push(
+                Text::new(&self.interface_name)
+                    .size(15)
+                    .width(Length::Units(200)),
+            )
+            .push(
+                Text::new(route.protocol_name())
+                    .size(15)
+                    .width(Length::Units(80)),
+            );
+
+        if route.is_static() {
+            let mut set_metric_button = Button::new(
+                &mut self.set_metric_button_state,
+                Text::new("Set Metric").size(15),
+            )
+            .style(ForegroundGreenButtonStyle);
+            let delete_text = if is_pending_delete {
+                "Confirm Delete"
+            } else {
+                "Delete"
+            };
+            let mut delete_button = Button::new(
+                &mut self.delete_button_state,
+                Text::new(delete_text).size(15),
+            )
+            .style(ForegroundGreenButtonStyle);
+            if !is_busy {
+                set_metric_button = set_metric_button.on_press(Message::SetMetric(i));
+                delete_button = delete_button.on_press(Message::DeleteRoute(i));
+            }
+
+            row = row
+                .push(
+                    TextInput::new(
+                        &mut self.metric_input_state,
+                        "Metric",
+                        &self.metric_input,
+                        move |metric_input| Message::UpdateMetricInput(i, metric_input),
+                    )
+                    .on_submit(Message::SetMetric(i))
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(60)),
+                )
+                .push(set_metric_button)
+                .push(delete_button);
+        } else {
+            row = row.push(Text::new(format!("Metric {}", route.metric)).size(15));
+        }
+
+        Row::new()
+            .push(Space::new(Length::Units(20), Length::Shrink))
+            .push(row)
+            .into()
+    }
+}
@@ -1,4 +1,26 @@
 use crate::{
+    dns_config::{
+        parse_servers,
+        validate_suffix,
+        DnsPreset,
+    },
+    format_mac_address_to_string,
+    if_stats::SampleInterval,
+    log_file::{
+        LogFileFormat,
+        LogFileSettings,
+        LogFileSink,
+    },
+    log_filter::{
+        LogFilter,
+        LogFilterOverride,
+        DEFAULT_LOG_FILTER,
+    },
+    spoof_detector::KnownNeighbor,
+    style::{
+        ForegroundGreenButtonStyle,
+        ForegroundGreenTextInputStyle,
+    },
     ConsoleHandle,
     ConsoleHandleType,
     ConsoleModeFlags,
@@ -7,6 +29,8 @@ use crate::{
 };
 use anyhow::Context;
 use iced::{
+    Align,
+    Button,
     Checkbox,
     Clipboard,
     Column,
@@ -14,29 +38,98 @@ use iced::{
     Container,
     Element,
     Length,
+    PickList,
+    Row,
     Scrollable,
+    Space,
     Text,
+    TextInput,
 };
 use log::{
     error,
     info,
     warn,
 };
-use std::sync::Arc;
+use macaddr::MacAddr;
+use std::{
+    net::IpAddr,
+    path::PathBuf,
+    sync::Arc,
+};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     ConsoleToggled(bool),
-    DebugToggled(bool),
+    UpdateLogFilterInput(String),
+    ApplyLogFilter,
+    StatsIntervalSelected(SampleInterval),
+
+    RemoveDnsPreset(usize),
+    UpdateDnsPresetName(String),
+    UpdateDnsPresetServers(String),
+    UpdateDnsPresetSuffix(String),
+    AddDnsPreset,
+
+    RemoveKnownNeighbor(usize),
+    UpdateKnownNeighborAddress(String),
+    UpdateKnownNeighborHardwareAddress(String),
+    AddKnownNeighbor,
+
+    LogFileToggled(bool),
+    LogFileFormatSelected(LogFileFormat),
+    UpdateLogFileMaxSize(String),
+    UpdateLogFileMaxAge(String),
+    UpdateLogFileRetention(String),
+    ApplyLogFileLimits,
 
     SaveResult(Arc<anyhow::Result<()>>),
 }
 
 pub struct Settings {
     console: Option<ConsoleWindow>,
-    debug: bool,
+    log_filter: String,
+    log_filter_override: Option<LogFilterOverride>,
+    stats_interval: SampleInterval,
+    dns_presets: Vec<DnsPreset>,
+    known_neighbors: Vec<KnownNeighbor>,
+    log_file: LogFileSettings,
+    log_dir: Result<PathBuf, String>,
+
+    dns_preset_name: String,
+    dns_preset_servers: String,
+    dns_preset_suffix: String,
+    dns_preset_errors: Vec<String>,
+
+    known_neighbor_address: String,
+    known_neighbor_hardware_address: String,
+    known_neighbor_error: Option<String>,
+
+    log_filter_input: String,
+    log_filter_error: Option<String>,
+
+    log_file_max_size: String,
+    log_file_max_age: String,
+    log_file_retention: String,
+    log_file_error: Option<String>,
 
     scroll_state: iced::scrollable::State,
+    log_filter_input_state: iced::text_input::State,
+    apply_log_filter_button_state: iced::button::State,
+    stats_interval_state: iced::pick_list::State<SampleInterval>,
+    remove_dns_preset_button_states: Vec<iced::button::State>,
+    dns_preset_name_state: iced::text_input::State,
+    dns_preset_servers_state: iced::text_input::State,
+    dns_preset_suffix_state: iced::text_input::State,
+    add_dns_preset_button_state: iced::button::State,
+    remove_known_neighbor_button_states: Vec<iced::button::State>,
+    known_neighbor_address_state: iced::text_input::State,
+    known_neighbor_hardware_address_state: iced::text_input::State,
+    add_known_neighbor_button_state: iced::button::State,
+    log_file_format_state: iced::pick_list::State<LogFileFormat>,
+    log_file_max_size_state: iced::text_input::State,
+    log_file_max_age_state: iced::text_input::State,
+    log_file_retention_state: iced::text_input::State,
+    apply_log_file_limits_button_state: iced::button::State,
 }
 
 impl Settings {
@@ -55,9 +148,49 @@ impl Settings {
 
         Settings {
             console,
-            debug: false,
+            log_filter: DEFAULT_LOG_FILTER.to_string(),
+            log_filter_override: None,
+            stats_interval: SampleInterval::default(),
+            dns_presets: DnsPreset::defaults(),
+            known_neighbors: Vec::new(),
+            log_file: LogFileSettings::new(),
+            log_dir: crate::UserSettings::log_dir().map_err(|e| format!("{:#}", e)),
+
+            dns_preset_name: String::new(),
+            dns_preset_servers: String::new(),
+            dns_preset_suffix: String::new(),
+            dns_preset_errors: Vec::new(),
+
+            known_neighbor_address: String::new(),
+            known_neighbor_hardware_address: String::new(),
+            known_neighbor_error: None,
+
+            log_filter_input: DEFAULT_LOG_FILTER.to_string(),
+            log_filter_error: None,
+
+            log_file_max_size: String::new(),
+            log_file_max_age: String::new(),
+            log_file_retention: String::new(),
+            log_file_error: None,
 
             scroll_state: iced::scrollable::State::new(),
+            log_filter_input_state: iced::text_input::State::new(),
+            apply_log_filter_button_state: iced::button::State::new(),
+            stats_interval_state: iced::pick_list::State::default(),
+            remove_dns_preset_button_states: Vec::new(),
+            dns_preset_name_state: iced::text_input::State::new(),
+            dns_preset_servers_state: iced::text_input::State::new(),
+            dns_preset_suffix_state: iced::text_input::State::new(),
+            add_dns_preset_button_state: iced::button::State::new(),
+            remove_known_neighbor_button_states: Vec::new(),
+            known_neighbor_address_state: iced::text_input::State::new(),
+            known_neighbor_hardware_address_state: iced::text_input::State::new(),
+            add_known_neighbor_button_state: iced::button::State::new(),
+            log_file_format_state: iced::pick_list::State::default(),
+            log_file_max_size_state: iced::text_input::State::new(),
+            log_file_max_age_state: iced::text_input::State::new(),
+            log_file_retention_state: iced::text_input::State::new(),
+            apply_log_file_limits_button_state: iced::button::State::new(),
         }
     }
 
@@ -74,21 +207,172 @@ impl Settings {
         }
     }
 
-    /// Set whether debug info is printed.
-    pub fn set_debug(&mut self, debug: bool) {
-        // TODO: Move to logger so that all data is in one place.
-        if debug {
-            log::set_max_level(log::LevelFilter::Debug);
+    /// Set the saved log filter spec, and apply it unless `log_filter_override` is given.
+    ///
+    /// An invalid override falls back to the saved spec, and an invalid saved spec falls back to the default.
+    pub fn set_log_filter(
+        &mut self,
+        log_filter: String,
+        log_filter_override: Option<LogFilterOverride>,
+    ) {
+        self.log_filter_input = log_filter.clone();
+        self.log_filter = log_filter;
+
+        if let Some(log_filter_override) = log_filter_override {
+            match log_filter_override.spec.parse::<LogFilter>() {
+                Ok(filter) => {
+                    info!(
+                        "Using log filter '{}' from {}",
+                        filter, log_filter_override.source
+                    );
+                    crate::logger::set_filter(filter);
+                    self.log_filter_override = Some(log_filter_override);
+                    return;
+                }
+                Err(e) => {
+                    warn!(
+                        "Ignoring log filter from {}: {:#}",
+                        log_filter_override.source, e
+                    );
+                }
+            }
+        }
+
+        match self.log_filter.parse::<LogFilter>() {
+            Ok(filter) => crate::logger::set_filter(filter),
+            Err(e) => {
+                warn!("Invalid saved log filter, using the default: {:#}", e);
+                self.log_filter_error = Some(format!("{:#}", e));
+                crate::logger::set_filter(LogFilter::default());
+            }
+        }
+    }
+
+    /// Set how often interface statistics are sampled.
+    pub fn set_stats_interval(&mut self, stats_interval: SampleInterval) {
+        self.stats_interval = stats_interval;
+    }
+
+    /// Get how often interface statistics are sampled.
+    pub fn stats_interval(&self) -> SampleInterval {
+        self.stats_interval
+    }
+
+    /// Set the dns presets.
+    pub fn set_dns_presets(&mut self, dns_presets: Vec<DnsPreset>) {
+        self.dns_presets = dns_presets;
+    }
+
+    /// Get the dns presets.
+    pub fn dns_presets(&self) -> &[DnsPreset] {
+        &self.dns_presets
+    }
+
+    /// Set the known-good neighbors used to detect spoofing.
+    pub fn set_known_neighbors(&mut self, known_neighbors: Vec<KnownNeighbor>) {
+        self.known_neighbors = known_neighbors;
+    }
+
+    /// Get the known-good neighbors used to detect spoofing.
+    pub fn known_neighbors(&self) -> &[KnownNeighbor] {
+        &self.known_neighbors
+    }
+
+    /// Set how logs are written to files, and start or stop writing them.
+    pub fn set_log_file(&mut self, log_file: LogFileSettings) {
+        self.log_file_max_size = log_file.max_size_mb.to_string();
+        self.log_file_max_age = log_file.max_age_hours.to_string();
+        self.log_file_retention = log_file.retention.to_string();
+        self.log_file = log_file;
+        self.apply_log_file();
+    }
+
+    /// Hand the log file settings to the logger.
+    fn apply_log_file(&mut self) {
+        self.log_file_error = None;
+        let sink = if self.log_file.enabled {
+            match self.log_dir.as_ref() {
+                Ok(log_dir) => Some(LogFileSink::new(log_dir.clone(), self.log_file.clone())),
+                Err(e) => {
+                    self.log_file_error = Some(e.clone());
+                    None
+                }
+            }
         } else {
-            log::set_max_level(log::LevelFilter::Info);
+            None
+        };
+        crate::logger::set_file_sink(sink);
+    }
+
+    /// Validate the log file limit inputs, getting the max size, max age and retention.
+    fn parse_log_file_limits(&self) -> Result<(u64, u64, usize), String> {
+        let max_size_mb: u64 = self
+            .log_file_max_size
+            .trim()
+            .parse()
+            .map_err(|e| format!("invalid max size: {}", e))?;
+        if max_size_mb == 0 {
+            return Err("max size must be at least 1 MB".to_string());
+        }
+        let max_age_hours: u64 = self
+            .log_file_max_age
+            .trim()
+            .parse()
+            .map_err(|e| format!("invalid max age: {}", e))?;
+        if max_age_hours == 0 {
+            return Err("max age must be at least 1 hour".to_string());
         }
-        self.debug = debug;
+        let retention: usize = self
+            .log_file_retention
+            .trim()
+            .parse()
+            .map_err(|e| format!("invalid number of files: {}", e))?;
+        if retention == 0 {
+            return Err("at least 1 file must be kept".to_string());
+        }
+
+        Ok((max_size_mb, max_age_hours, retention))
+    }
+
+    /// Validate the known neighbor inputs.
+    fn parse_known_neighbor(&self) -> Result<KnownNeighbor, String> {
+        let address: IpAddr = self
+            .known_neighbor_address
+            .trim()
+            .parse()
+            .map_err(|e| format!("invalid address: {}", e))?;
+        if self
+            .known_neighbors
+            .iter()
+            .any(|known_neighbor| known_neighbor.address == address)
+        {
+            return Err(format!("{} is already known", address));
+        }
+
+        let hardware_address = match self.known_neighbor_hardware_address.trim().parse() {
+            Ok(MacAddr::V6(hardware_address)) => hardware_address.into_array().to_vec(),
+            Ok(MacAddr::V8(hardware_address)) => hardware_address.into_array().to_vec(),
+            Err(e) => return Err(format!("invalid hardware address: {}", e)),
+        };
+
+        Ok(KnownNeighbor {
+            address,
+            hardware_address: format_mac_address_to_string(&hardware_address)
+                .trim_end()
+                .to_string(),
+        })
     }
 
     pub fn save_settings_command(&self) -> Command<Message> {
         let data = crate::UserSettings {
             console: self.console.as_ref().map_or(true, |c| c.is_visible()),
-            debug: self.debug,
+            debug: false,
+            log_filter: self.log_filter.clone(),
+            log_filter_override: None,
+            stats_interval_ms: self.stats_interval.as_millis(),
+            dns_presets: self.dns_presets.clone(),
+            known_neighbors: self.known_neighbors.clone(),
+            log_file: self.log_file.clone(),
         };
 
         Command::perform(
@@ -111,10 +395,151 @@ impl Settings {
                 self.set_console(show);
                 self.save_settings_command()
             }
-            Message::DebugToggled(debug) => {
-                self.set_debug(debug);
+            Message::UpdateLogFilterInput(log_filter_input) => {
+                self.log_filter_input = log_filter_input;
+                Command::none()
+            }
+            Message::ApplyLogFilter => match self.log_filter_input.parse::<LogFilter>() {
+                Ok(filter) => {
+                    info!("Using log filter '{}'", filter);
+                    crate::logger::set_filter(filter);
+                    self.log_filter = self.log_filter_input.trim().to_string();
+                    self.log_filter_override = None;
+                    self.log_filter_error = None;
+                    self.save_settings_command()
+                }
+                Err(e) => {
+                    self.log_filter_error = Some(format!("{:#}", e));
+                    Command::none()
+                }
+            },
+            Message::StatsIntervalSelected(stats_interval) => {
+                self.set_stats_interval(stats_interval);
+                self.save_settings_command()
+            }
+            Message::RemoveDnsPreset(i) => {
+                if i >= self.dns_presets.len() {
+                    return Command::none();
+                }
+                self.dns_presets.remove(i);
+                self.save_settings_command()
+            }
+            Message::UpdateDnsPresetName(name) => {
+                self.dns_preset_name = name;
+                Command::none()
+            }
+            Message::UpdateDnsPresetServers(servers) => {
+                self.dns_preset_servers = servers;
+                Command::none()
+            }
+            Message::UpdateDnsPresetSuffix(suffix) => {
+                self.dns_preset_suffix = suffix;
+                Command::none()
+            }
+            Message::AddDnsPreset => {
+                self.dns_preset_errors.clear();
+
+                let name = self.dns_preset_name.trim().to_string();
+                if name.is_empty() {
+                    self.dns_preset_errors.push("name is empty".to_string());
+                } else if self.dns_presets.iter().any(|preset| preset.name == name) {
+                    self.dns_preset_errors
+                        .push(format!("a preset named '{}' already exists", name));
+                }
+                let servers = match parse_servers(&self.dns_preset_servers) {
+                    Ok(servers) if servers.is_empty() => {
+                        self.dns_preset_errors.push("no servers".to_string());
+                        Vec::new()
+                    }
+                    Ok(servers) => servers,
+                    Err(errors) => {
+                        self.dns_preset_errors.extend(errors);
+                        Vec::new()
+                    }
+                };
+                let suffix = self.dns_preset_suffix.trim().to_string();
+                if let Err(e) = validate_suffix(&suffix) {
+                    self.dns_preset_errors.push(e);
+                }
+
+                if !self.dns_preset_errors.is_empty() {
+                    return Command::none();
+                }
+
+                self.dns_presets.push(DnsPreset {
+                    name,
+                    servers,
+                    suffix,
+                });
+                self.dns_preset_name.clear();
+                self.dns_preset_servers.clear();
+                self.dns_preset_suffix.clear();
+
+                self.save_settings_command()
+            }
+            Message::RemoveKnownNeighbor(i) => {
+                if i >= self.known_neighbors.len() {
+                    return Command::none();
+                }
+                self.known_neighbors.remove(i);
+                self.save_settings_command()
+            }
+            Message::UpdateKnownNeighborAddress(address) => {
+                self.known_neighbor_address = address;
+                Command::none()
+            }
+            Message::UpdateKnownNeighborHardwareAddress(hardware_address) => {
+                self.known_neighbor_hardware_address = hardware_address;
+                Command::none()
+            }
+            Message::AddKnownNeighbor => match self.parse_known_neighbor() {
+                Ok(known_neighbor) => {
+                    self.known_neighbor_error = None;
+                    self.known_neighbors.push(known_neighbor);
+                    self.known_neighbor_address.clear();
+                    self.known_neighbor_hardware_address.clear();
+                    self.save_settings_command()
+                }
+                Err(e) => {
+                    self.known_neighbor_error = Some(e);
+                    Command::none()
+                }
+            },
+            Message::LogFileToggled(enabled) => {
+                self.log_file.enabled = enabled;
+                self.apply_log_file();
+                self.save_settings_command()
+            }
+            Message::LogFileFormatSelected(format) => {
+                self.log_file.format = format;
+                self.apply_log_file();
                 self.save_settings_command()
             }
+            Message::UpdateLogFileMaxSize(max_size) => {
+                self.log_file_max_size = max_size;
+                Command::none()
+            }
+            Message::UpdateLogFileMaxAge(max_age) => {
+                self.log_file_max_age = max_age;
+                Command::none()
+            }
+            Message::UpdateLogFileRetention(retention) => {
+                self.log_file_retention = retention;
+                Command::none()
+            }
+            Message::ApplyLogFileLimits => match self.parse_log_file_limits() {
+                Ok((max_size_mb, max_age_hours, retention)) => {
+                    self.log_file.max_size_mb = max_size_mb;
+                    self.log_file.max_age_hours = max_age_hours;
+                    self.log_file.retention = retention;
+                    self.apply_log_file();
+                    self.save_settings_command()
+                }
+                Err(e) => {
+                    self.log_file_error = Some(e);
+                    Command::none()
+                }
+            },
             Message::SaveResult(r) => {
                 match r.as_ref() {
                     Ok(()) => {
@@ -142,7 +567,319 @@ impl Settings {
             ));
         }
 
-        column = column.push(Checkbox::new(self.debug, "Debug", Message::DebugToggled));
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(Text::new("Log Filter"))
+                .push(
+                    TextInput::new(
+                        &mut self.log_filter_input_state,
+                        DEFAULT_LOG_FILTER,
+                        &self.log_filter_input,
+                        Message::UpdateLogFilterInput,
+                    )
+                    .on_submit(Message::ApplyLogFilter)
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(300)),
+                )
+                .push(
+                    Button::new(
+                        &mut self.apply_log_filter_button_state,
+                        Text::new("Apply").size(15),
+                    )
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::ApplyLogFilter),
+                ),
+        );
+        column = column.push(
+            Row::new()
+                .push(Space::new(Length::Units(20), Length::Shrink))
+                .push(
+                    Text::new(
+                        "Like hekk=debug,netcon=trace,iced=warn. A bare level applies to all other targets.",
+                    )
+                    .size(15),
+                ),
+        );
+        if let Some(log_filter_override) = self.log_filter_override.as_ref() {
+            column = column.push(
+                Row::new()
+                    .push(Space::new(Length::Units(20), Length::Shrink))
+                    .push(
+                        Text::new(format!(
+                            "Using '{}' from {} for this session. Applying a filter here replaces it.",
+                            log_filter_override.spec, log_filter_override.source
+                        ))
+                        .size(15),
+                    ),
+            );
+        }
+        if let Some(error) = self.log_filter_error.as_ref() {
+            column = column.push(Text::new(format!("Error: {}", error)).size(15));
+        }
+
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .push(Text::new("Statistics Interval"))
+                .push(PickList::new(
+                    &mut self.stats_interval_state,
+                    SampleInterval::ALL,
+                    Some(self.stats_interval),
+                    Message::StatsIntervalSelected,
+                )),
+        );
+
+        column = column.push(Text::new("DNS Presets"));
+        self.remove_dns_preset_button_states
+            .resize(self.dns_presets.len(), iced::button::State::new());
+        for (i, (preset, button_state)) in self
+            .dns_presets
+            .iter()
+            .zip(self.remove_dns_preset_button_states.iter_mut())
+            .enumerate()
+        {
+            let servers = preset
+                .servers
+                .iter()
+                .map(|server| server.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let mut text = format!("{}: {}", preset.name, servers);
+            if !preset.suffix.is_empty() {
+                text.push_str(&format!(" ({})", preset.suffix));
+            }
+
+            column = column.push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Space::new(Length::Units(20), Length::Shrink))
+                    .push(Text::new(text).size(15))
+                    .push(
+                        Button::new(button_state, Text::new("Remove").size(15))
+                            .style(ForegroundGreenButtonStyle)
+                            .on_press(Message::RemoveDnsPreset(i)),
+                    ),
+            );
+        }
+
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(Space::new(Length::Units(20), Length::Shrink))
+                .push(
+                    TextInput::new(
+                        &mut self.dns_preset_name_state,
+                        "Name",
+                        &self.dns_preset_name,
+                        Message::UpdateDnsPresetName,
+                    )
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(100)),
+                )
+                .push(
+                    TextInput::new(
+                        &mut self.dns_preset_servers_state,
+                        "Servers",
+                        &self.dns_preset_servers,
+                        Message::UpdateDnsPresetServers,
+                    )
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(200)),
+                )
+                .push(
+                    TextInput::new(
+                        &mut self.dns_preset_suffix_state,
+                        "Suffix (optional)",
+                        &self.dns_preset_suffix,
+                        Message::UpdateDnsPresetSuffix,
+                    )
+                    .on_submit(Message::AddDnsPreset)
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(150)),
+                )
+                .push(
+                    Button::new(
+                        &mut self.add_dns_preset_button_state,
+                        Text::new("Add").size(15),
+                    )
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::AddDnsPreset),
+                ),
+        );
+        column = self.dns_preset_errors.iter().fold(column, |column, error| {
+            column.push(Text::new(format!("Error: {}", error)).size(15))
+        });
+
+        column = column.push(Text::new("Known Neighbors"));
+        self.remove_known_neighbor_button_states
+            .resize(self.known_neighbors.len(), iced::button::State::new());
+        for (i, (known_neighbor, button_state)) in self
+            .known_neighbors
+            .iter()
+            .zip(self.remove_known_neighbor_button_states.iter_mut())
+            .enumerate()
+        {
+            column = column.push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Space::new(Length::Units(20), Length::Shrink))
+                    .push(
+                        Text::new(format!(
+                            "{} is at {}",
+                            known_neighbor.address, known_neighbor.hardware_address
+                        ))
+                        .size(15),
+                    )
+                    .push(
+                        Button::new(button_state, Text::new("Remove").size(15))
+                            .style(ForegroundGreenButtonStyle)
+                            .on_press(Message::RemoveKnownNeighbor(i)),
+                    ),
+            );
+        }
+
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .align_items(Align::Center)
+                .push(Space::new(Length::Units(20), Length::Shrink))
+                .push(
+                    TextInput::new(
+                        &mut self.known_neighbor_address_state,
+                        "IP Address",
+                        &self.known_neighbor_address,
+                        Message::UpdateKnownNeighborAddress,
+                    )
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(150)),
+                )
+                .push(
+                    TextInput::new(
+                        &mut self.known_neighbor_hardware_address_state,
+                        "MAC Address",
+                        &self.known_neighbor_hardware_address,
+                        Message::UpdateKnownNeighborHardwareAddress,
+                    )
+                    .on_submit(Message::AddKnownNeighbor)
+                    .style(ForegroundGreenTextInputStyle)
+                    .size(15)
+                    .padding(2)
+                    .width(Length::Units(150)),
+                )
+                .push(
+                    Button::new(
+                        &mut self.add_known_neighbor_button_state,
+                        Text::new("Add").size(15),
+                    )
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::AddKnownNeighbor),
+                ),
+        );
+        if let Some(error) = self.known_neighbor_error.as_ref() {
+            column = column.push(Text::new(format!("Error: {}", error)).size(15));
+        }
+
+        column = column
+            .push(Text::new("Log Files"))
+            .push(Checkbox::new(
+                self.log_file.enabled,
+                "Write logs to files",
+                Message::LogFileToggled,
+            ))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Space::new(Length::Units(20), Length::Shrink))
+                    .push(Text::new("Format").size(15))
+                    .push(PickList::new(
+                        &mut self.log_file_format_state,
+                        LogFileFormat::ALL,
+                        Some(self.log_file.format),
+                        Message::LogFileFormatSelected,
+                    )),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Space::new(Length::Units(20), Length::Shrink))
+                    .push(Text::new("Max Size (MB)").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.log_file_max_size_state,
+                            "",
+                            &self.log_file_max_size,
+                            Message::UpdateLogFileMaxSize,
+                        )
+                        .on_submit(Message::ApplyLogFileLimits)
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(60)),
+                    )
+                    .push(Text::new("Max Age (hours)").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.log_file_max_age_state,
+                            "",
+                            &self.log_file_max_age,
+                            Message::UpdateLogFileMaxAge,
+                        )
+                        .on_submit(Message::ApplyLogFileLimits)
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(60)),
+                    )
+                    .push(Text::new("Files Kept").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.log_file_retention_state,
+                            "",
+                            &self.log_file_retention,
+                            Message::UpdateLogFileRetention,
+                        )
+                        .on_submit(Message::ApplyLogFileLimits)
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(60)),
+                    )
+                    .push(
+                        Button::new(
+                            &mut self.apply_log_file_limits_button_state,
+                            Text::new("Apply").size(15),
+                        )
+                        .style(ForegroundGreenButtonStyle)
+                        .on_press(Message::ApplyLogFileLimits),
+                    ),
+            );
+        if let Ok(log_dir) = self.log_dir.as_ref() {
+            column = column.push(
+                Row::new()
+                    .push(Space::new(Length::Units(20), Length::Shrink))
+                    .push(Text::new(format!("Folder: {}", log_dir.display())).size(15)),
+            );
+        }
+        if let Some(error) = self.log_file_error.as_ref() {
+            column = column.push(Text::new(format!("Error: {}", error)).size(15));
+        }
 
         Container::new(
             Scrollable::new(&mut self.scroll_state)
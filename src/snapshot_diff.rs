@@ -0,0 +1,383 @@
+use crate::export::AdapterRecord;
+use std::{
+    collections::{
+        HashMap,
+        VecDeque,
+    },
+    time::SystemTime,
+};
+
+/// The number of diffs that are kept
+const HISTORY_LEN: usize = 10;
+
+/// A change to a single field of an adapter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldChange {
+    Changed {
+        field: &'static str,
+        old: String,
+        new: String,
+    },
+    Added {
+        field: &'static str,
+        value: String,
+    },
+    Removed {
+        field: &'static str,
+        value: String,
+    },
+}
+
+impl std::fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Changed { field, old, new } => write!(f, "~ {}: {} -> {}", field, old, new),
+            Self::Added { field, value } => write!(f, "+ {}: {}", field, value),
+            Self::Removed { field, value } => write!(f, "- {}: {}", field, value),
+        }
+    }
+}
+
+/// A change to an adapter between two snapshots
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdapterChange {
+    Added {
+        name: String,
+        friendly_name: String,
+    },
+    Removed {
+        name: String,
+        friendly_name: String,
+    },
+    Changed {
+        name: String,
+        friendly_name: String,
+        fields: Vec<FieldChange>,
+    },
+}
+
+impl AdapterChange {
+    /// Get the name of the changed adapter, like "{<guid>}".
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Added { name, .. } | Self::Removed { name, .. } | Self::Changed { name, .. } => {
+                name
+            }
+        }
+    }
+
+    /// Get the changed fields.
+    ///
+    /// This is empty for added and removed adapters.
+    pub fn fields(&self) -> &[FieldChange] {
+        match self {
+            Self::Changed { fields, .. } => fields,
+            Self::Added { .. } | Self::Removed { .. } => &[],
+        }
+    }
+}
+
+impl std::fmt::Display for AdapterChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Added { friendly_name, .. } => write!(f, "+ Adapter {}", friendly_name),
+            Self::Removed { friendly_name, .. } => write!(f, "- Adapter {}", friendly_name),
+            Self::Changed { friendly_name, .. } => write!(f, "~ Adapter {}", friendly_name),
+        }
+    }
+}
+
+/// The changes between two snapshots of the adapter list
+#[derive(Debug, Clone)]
+pub struct SnapshotDiff {
+    /// When the newer snapshot was taken
+    pub time: SystemTime,
+    pub changes: Vec<AdapterChange>,
+}
+
+impl SnapshotDiff {
+    /// Compare two snapshots, matching adapters by name.
+    pub fn new(old: &[AdapterRecord], new: &[AdapterRecord], time: SystemTime) -> Self {
+        let mut old_records: HashMap<&str, &AdapterRecord> = old
+            .iter()
+            .map(|record| (record.name.as_str(), record))
+            .collect();
+
+        let mut changes = Vec::new();
+        for record in new {
+            match old_records.remove(record.name.as_str()) {
+                Some(old_record) => {
+                    let fields = diff_record(old_record, record);
+                    if !fields.is_empty() {
+                        changes.push(AdapterChange::Changed {
+                            name: record.name.clone(),
+                            friendly_name: record.friendly_name.clone(),
+                            fields,
+                        });
+                    }
+                }
+                None => changes.push(AdapterChange::Added {
+                    name: record.name.clone(),
+                    friendly_name: record.friendly_name.clone(),
+                }),
+            }
+        }
+
+        // Iterate the old list rather than the map so removals keep a stable order.
+        for record in old {
+            if old_records.contains_key(record.name.as_str()) {
+                changes.push(AdapterChange::Removed {
+                    name: record.name.clone(),
+                    friendly_name: record.friendly_name.clone(),
+                });
+            }
+        }
+
+        Self { time, changes }
+    }
+
+    /// Whether nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Get the change of the adapter with the given name.
+    pub fn get(&self, name: &str) -> Option<&AdapterChange> {
+        self.changes.iter().find(|change| change.name() == name)
+    }
+}
+
+/// Get the changes to the fields of an adapter.
+fn diff_record(old: &AdapterRecord, new: &AdapterRecord) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    for ((field, old), (_, new)) in scalar_fields(old).into_iter().zip(scalar_fields(new)) {
+        if old != new {
+            changes.push(FieldChange::Changed { field, old, new });
+        }
+    }
+
+    diff_list(
+        &mut changes,
+        "IP Address",
+        &old.ip_addresses,
+        &new.ip_addresses,
+    );
+    diff_list(&mut changes, "Gateway", &old.gateways, &new.gateways);
+    diff_list(
+        &mut changes,
+        "DNS Server",
+        &old.dns_servers,
+        &new.dns_servers,
+    );
+
+    changes
+}
+
+/// Get the fields of a record that are compared by value.
+fn scalar_fields(record: &AdapterRecord) -> Vec<(&'static str, String)> {
+    vec![
+        ("Friendly Name", record.friendly_name.clone()),
+        ("Description", record.description.clone()),
+        ("Interface Index", record.if_index.to_string()),
+        ("Hardware Address", record.hardware_address.clone()),
+        ("Interface Type", record.if_type.clone()),
+        ("Status", record.oper_status.clone()),
+        ("MTU", record.mtu.to_string()),
+        ("DHCP Enabled", record.dhcp_enabled.to_string()),
+        (
+            "DHCP Server",
+            record
+                .dhcp_server
+                .clone()
+                .unwrap_or_else(|| "None".to_string()),
+        ),
+        ("DNS Suffix", record.dns_suffix.clone()),
+    ]
+}
+
+/// Compare lists where order does not matter.
+fn diff_list(changes: &mut Vec<FieldChange>, field: &'static str, old: &[String], new: &[String]) {
+    for value in old.iter().filter(|value| !new.contains(value)) {
+        changes.push(FieldChange::Removed {
+            field,
+            value: value.clone(),
+        });
+    }
+    for value in new.iter().filter(|value| !old.contains(value)) {
+        changes.push(FieldChange::Added {
+            field,
+            value: value.clone(),
+        });
+    }
+}
+
+/// The most recent non-empty diffs, with one selected for viewing
+#[derive(Debug, Default)]
+pub struct DiffHistory {
+    /// Newest first
+    diffs: VecDeque<SnapshotDiff>,
+    selected: usize,
+}
+
+impl DiffHistory {
+    /// Add a diff, selecting it.
+    ///
+    /// Empty diffs are ignored so refreshes that change nothing do not push out useful history.
+    pub fn push(&mut self, diff: SnapshotDiff) {
+        if diff.is_empty() {
+            return;
+        }
+
+        self.diffs.push_front(diff);
+        self.diffs.truncate(HISTORY_LEN);
+        self.selected = 0;
+    }
+
+    /// Get the selected diff.
+    pub fn selected(&self) -> Option<&SnapshotDiff> {
+        self.diffs.get(self.selected)
+    }
+
+    /// Get the position of the selected diff, counting from the newest, and the number of diffs.
+    pub fn position(&self) -> (usize, usize) {
+        (self.selected + 1, self.diffs.len())
+    }
+
+    /// Select the next older diff, if any.
+    pub fn select_older(&mut self) {
+        if self.selected + 1 < self.diffs.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Select the next newer diff, if any.
+    pub fn select_newer(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn clear(&mut self) {
+        self.diffs.clear();
+        self.selected = 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(name: &str, friendly_name: &str) -> AdapterRecord {
+        AdapterRecord {
+            name: name.to_string(),
+            friendly_name: friendly_name.to_string(),
+            description: "Intel(R) Ethernet Connection I219-V".to_string(),
+            combo_index: Some(7),
+            if_index: 12,
+            luid: 0x0006_0000_0100_0000,
+            hardware_address: "00:1A:2B:3C:4D:5E".to_string(),
+            if_type: "Ethernet".to_string(),
+            oper_status: "Up".to_string(),
+            mtu: 1500,
+            dhcp_enabled: true,
+            dhcp_server: Some("192.168.1.1".to_string()),
+            dns_suffix: "home.lan".to_string(),
+
+            ip_addresses: vec!["192.168.1.20/24".to_string()],
+            gateways: vec!["192.168.1.1".to_string()],
+            dns_servers: vec!["192.168.1.1".to_string()],
+        }
+    }
+
+    fn diff(old: &[AdapterRecord], new: &[AdapterRecord]) -> SnapshotDiff {
+        SnapshotDiff::new(old, new, SystemTime::UNIX_EPOCH)
+    }
+
+    #[test]
+    fn identical() {
+        let records = vec![record("{A}", "Ethernet"), record("{B}", "Wi-Fi")];
+        assert!(diff(&records, &records).is_empty());
+    }
+
+    #[test]
+    fn added_adapter() {
+        let old = vec![record("{A}", "Ethernet")];
+        let new = vec![record("{A}", "Ethernet"), record("{B}", "Wi-Fi")];
+        assert_eq!(
+            diff(&old, &new).changes,
+            vec![AdapterChange::Added {
+                name: "{B}".to_string(),
+                friendly_name: "Wi-Fi".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn removed_adapter() {
+        let old = vec![record("{A}", "Ethernet"), record("{B}", "Wi-Fi")];
+        let new = vec![record("{B}", "Wi-Fi")];
+        assert_eq!(
+            diff(&old, &new).changes,
+            vec![AdapterChange::Removed {
+                name: "{A}".to_string(),
+                friendly_name: "Ethernet".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn changed_scalar_fields() {
+        let old = vec![record("{A}", "Ethernet")];
+        let mut new = old.clone();
+        new[0].mtu = 9000;
+        new[0].dhcp_server = None;
+
+        let diff = diff(&old, &new);
+        assert_eq!(
+            diff.get("{A}").unwrap().fields(),
+            [
+                FieldChange::Changed {
+                    field: "MTU",
+                    old: "1500".to_string(),
+                    new: "9000".to_string(),
+                },
+                FieldChange::Changed {
+                    field: "DHCP Server",
+                    old: "192.168.1.1".to_string(),
+                    new: "None".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn changed_address_lists() {
+        let old = vec![record("{A}", "Ethernet")];
+        let mut new = old.clone();
+        new[0].ip_addresses = vec!["fe80::1/64".to_string(), "192.168.1.20/24".to_string()];
+        new[0].gateways.clear();
+        new[0].dns_servers = vec!["1.1.1.1".to_string()];
+
+        let diff = diff(&old, &new);
+        assert_eq!(
+            diff.get("{A}").unwrap().fields(),
+            [
+                FieldChange::Added {
+                    field: "IP Address",
+                    value: "fe80::1/64".to_string(),
+                },
+                FieldChange::Removed {
+                    field: "Gateway",
+                    value: "192.168.1.1".to_string(),
+                },
+                FieldChange::Removed {
+                    field: "DNS Server",
+                    value: "192.168.1.1".to_string(),
+                },
+                FieldChange::Added {
+                    field: "DNS Server",
+                    value: "1.1.1.1".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    convert::TryFrom,
+    net::{
+        IpAddr,
+        Ipv4Addr,
+        Ipv6Addr,
+    },
+};
+use winapi::shared::{
+    ws2def::{
+        AF_INET,
+        AF_INET6,
+        SOCKADDR_IN,
+        SOCKET_ADDRESS,
+    },
+    ws2ipdef::{
+        SOCKADDR_IN6,
+        SOCKADDR_INET,
+    },
+};
+
+/// Convert a `SOCKET_ADDRESS` into an [`IpAddr`].
+///
+/// Returns `None` if the address is null or not an ipv4 or ipv6 address.
+///
+/// # Safety
+/// `address.lpSockaddr` must be null or point to a valid sockaddr of at least `address.iSockaddrLength` bytes.
+pub unsafe fn socket_address_to_ip_addr(address: &SOCKET_ADDRESS) -> Option<IpAddr> {
+    let sockaddr = address.lpSockaddr;
+    if sockaddr.is_null() {
+        return None;
+    }
+    let len = usize::try_from(address.iSockaddrLength).ok()?;
+
+    match i32::from((*sockaddr).sa_family) {
+        AF_INET if len >= std::mem::size_of::<SOCKADDR_IN>() => {
+            let sockaddr = &*(sockaddr as *const SOCKADDR_IN);
+            Some(IpAddr::V4(in_addr_to_ipv4_addr(sockaddr)))
+        }
+        AF_INET6 if len >= std::mem::size_of::<SOCKADDR_IN6>() => {
+            let sockaddr = &*(sockaddr as *const SOCKADDR_IN6);
+            Some(IpAddr::V6(Ipv6Addr::from(*sockaddr.sin6_addr.u.Byte())))
+        }
+        _ => None,
+    }
+}
+
+/// Get the [`Ipv4Addr`] from a `SOCKADDR_IN`.
+pub fn in_addr_to_ipv4_addr(sockaddr: &SOCKADDR_IN) -> Ipv4Addr {
+    // The address is stored in network byte order, so the bytes are already in the right order.
+    Ipv4Addr::from(unsafe { sockaddr.sin_addr.S_un.S_addr() }.to_ne_bytes())
+}
+
+/// Convert a `SOCKADDR_INET` into an [`IpAddr`].
+///
+/// Returns `None` if the family is not ipv4 or ipv6.
+pub fn sockaddr_inet_to_ip_addr(sockaddr: &SOCKADDR_INET) -> Option<IpAddr> {
+    unsafe {
+        match i32::from(*sockaddr.si_family()) {
+            AF_INET => Some(IpAddr::V4(in_addr_to_ipv4_addr(sockaddr.Ipv4()))),
+            AF_INET6 => Some(IpAddr::V6(Ipv6Addr::from(
+                *sockaddr.Ipv6().sin6_addr.u.Byte(),
+            ))),
+            _ => None,
+        }
+    }
+}
+
+/// Convert an [`IpAddr`] into a `SOCKADDR_INET`.
+///
+/// The port, flow info and scope id are zeroed.
+pub fn ip_addr_to_sockaddr_inet(address: IpAddr) -> SOCKADDR_INET {
+    let mut ret: SOCKADDR_INET = unsafe { std::mem::zeroed() };
+    match address {
+        IpAddr::V4(address) => unsafe {
+            let sockaddr = ret.Ipv4_mut();
+            sockaddr.sin_family = AF_INET as u16;
+            *sockaddr.sin_addr.S_un.S_addr_mut() = u32::from_ne_bytes(address.octets());
+        },
+        IpAddr::V6(address) => unsafe {
+            let sockaddr = ret.Ipv6_mut();
+            sockaddr.sin6_family = AF_INET6 as u16;
+            *sockaddr.sin6_addr.u.Byte_mut() = address.octets();
+        },
+    }
+    ret
+}
@@ -0,0 +1,498 @@
+use crate::format_mac_address_to_string;
+use iced::futures::{
+    future,
+    stream,
+    Stream,
+    StreamExt,
+};
+use std::{
+    collections::{
+        BTreeSet,
+        HashMap,
+        HashSet,
+    },
+    net::IpAddr,
+};
+
+/// A known-good mapping of an address to a hardware address
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct KnownNeighbor {
+    pub address: IpAddr,
+
+    /// The hardware address, formatted like "00-11-22-33-44-55"
+    pub hardware_address: String,
+}
+
+/// A hardware address seen for an address, like an entry of the neighbor cache
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Observation {
+    /// The interface the address was seen on
+    pub interface_index: u32,
+    pub address: IpAddr,
+    pub hardware_address: Vec<u8>,
+}
+
+/// A sign of arp or neighbor discovery spoofing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Alert {
+    /// An address moved to a different hardware address
+    HardwareAddressChanged {
+        address: IpAddr,
+        old: Vec<u8>,
+        new: Vec<u8>,
+    },
+
+    /// A default gateway moved to a different hardware address
+    GatewayChanged {
+        address: IpAddr,
+        old: Vec<u8>,
+        new: Vec<u8>,
+    },
+
+    /// An address does not have its known-good hardware address
+    UnexpectedHardwareAddress {
+        address: IpAddr,
+        expected: Vec<u8>,
+        actual: Vec<u8>,
+    },
+
+    /// One hardware address claims several ipv4 addresses
+    SharedHardwareAddress {
+        hardware_address: Vec<u8>,
+        addresses: Vec<IpAddr>,
+    },
+}
+
+impl std::fmt::Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mac = |address: &[u8]| format_mac_address_to_string(address).trim_end().to_string();
+        match self {
+            Self::HardwareAddressChanged { address, old, new } => {
+                write!(f, "{} changed from {} to {}", address, mac(old), mac(new))
+            }
+            Self::GatewayChanged { address, old, new } => write!(
+                f,
+                "Gateway {} changed from {} to {}",
+                address,
+                mac(old),
+                mac(new)
+            ),
+            Self::UnexpectedHardwareAddress {
+                address,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} is at {}, but is known to be at {}",
+                address,
+                mac(actual),
+                mac(expected)
+            ),
+            Self::SharedHardwareAddress {
+                hardware_address,
+                addresses,
+            } => {
+                let addresses = addresses
+                    .iter()
+                    .map(|address| address.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "{} claims {}", mac(hardware_address), addresses)
+            }
+        }
+    }
+}
+
+/// Watches observations for hardware addresses that change or are shared.
+///
+/// Alerts are raised once per change, not on every observation.
+/// Each interface is tracked on its own, as separate links may reuse the same addresses, like "fe80::1".
+#[derive(Debug, Default)]
+pub struct Detector {
+    known: HashMap<IpAddr, Vec<u8>>,
+    gateways: HashSet<IpAddr>,
+
+    /// The last hardware address seen for each interface index and address
+    last_seen: HashMap<(u32, IpAddr), Vec<u8>>,
+
+    /// The addresses claimed by each interface index and hardware address
+    owners: HashMap<(u32, Vec<u8>), BTreeSet<IpAddr>>,
+}
+
+impl Detector {
+    pub fn new(known: HashMap<IpAddr, Vec<u8>>, gateways: HashSet<IpAddr>) -> Self {
+        Self {
+            known,
+            gateways,
+
+            last_seen: HashMap::new(),
+            owners: HashMap::new(),
+        }
+    }
+
+    /// Process an observation, returning any alerts it raises.
+    pub fn observe(&mut self, observation: Observation) -> Vec<Alert> {
+        let Observation {
+            interface_index,
+            address,
+            hardware_address,
+        } = observation;
+        let mut alerts = Vec::new();
+
+        // Incomplete and multicast entries say nothing about who owns an address.
+        if !is_unicast_address(address) || !is_unicast_hardware_address(&hardware_address) {
+            return alerts;
+        }
+
+        let old = self
+            .last_seen
+            .insert((interface_index, address), hardware_address.clone());
+        if old.as_ref() == Some(&hardware_address) {
+            return alerts;
+        }
+
+        match (self.known.get(&address), old.as_ref()) {
+            (Some(expected), _) => {
+                if *expected != hardware_address {
+                    alerts.push(Alert::UnexpectedHardwareAddress {
+                        address,
+                        expected: expected.clone(),
+                        actual: hardware_address.clone(),
+                    });
+                }
+            }
+            (None, Some(old)) if self.gateways.contains(&address) => {
+                alerts.push(Alert::GatewayChanged {
+                    address,
+                    old: old.clone(),
+                    new: hardware_address.clone(),
+                });
+            }
+            (None, Some(old)) => {
+                alerts.push(Alert::HardwareAddressChanged {
+                    address,
+                    old: old.clone(),
+                    new: hardware_address.clone(),
+                });
+            }
+            (None, None) => {}
+        }
+
+        if let Some(old) = old {
+            let old_key = (interface_index, old);
+            if let Some(owners) = self.owners.get_mut(&old_key) {
+                owners.remove(&address);
+                if owners.is_empty() {
+                    self.owners.remove(&old_key);
+                }
+            }
+        }
+
+        // Hosts commonly have several ipv6 addresses, so only ipv4 addresses are expected to be unique.
+        if address.is_ipv4() {
+            let known = &self.known;
+            let owners = self
+                .owners
+                .entry((interface_index, hardware_address.clone()))
+                .or_default();
+            owners.insert(address);
+
+            let is_known = owners
+                .iter()
+                .all(|owner| known.get(owner) == Some(&hardware_address));
+            if owners.len() > 1 && !is_known {
+                alerts.push(Alert::SharedHardwareAddress {
+                    hardware_address,
+                    addresses: owners.iter().copied().collect(),
+                });
+            }
+        }
+
+        alerts
+    }
+}
+
+/// Run a detector over a stream of observations, yielding the alerts they raise.
+pub fn detect<S>(detector: Detector, observations: S) -> impl Stream<Item = Alert>
+where
+    S: Stream<Item = Observation>,
+{
+    observations
+        .scan(detector, |detector, observation| {
+            future::ready(Some(stream::iter(detector.observe(observation))))
+        })
+        .flatten()
+}
+
+/// Parse known-good mappings, skipping and reporting invalid hardware addresses.
+pub fn parse_known_neighbors(
+    known_neighbors: &[KnownNeighbor],
+) -> (HashMap<IpAddr, Vec<u8>>, Vec<String>) {
+    let mut known = HashMap::new();
+    let mut errors = Vec::new();
+    for known_neighbor in known_neighbors {
+        match known_neighbor.hardware_address.parse::<macaddr::MacAddr>() {
+            Ok(macaddr::MacAddr::V6(hardware_address)) => {
+                known.insert(
+                    known_neighbor.address,
+                    hardware_address.into_array().to_vec(),
+                );
+            }
+            Ok(macaddr::MacAddr::V8(hardware_address)) => {
+                known.insert(
+                    known_neighbor.address,
+                    hardware_address.into_array().to_vec(),
+                );
+            }
+            Err(e) => errors.push(format!(
+                "invalid hardware address for {}: {}",
+                known_neighbor.address, e
+            )),
+        }
+    }
+    (known, errors)
+}
+
+fn is_unicast_address(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            !address.is_unspecified() && !address.is_multicast() && !address.is_broadcast()
+        }
+        IpAddr::V6(address) => !address.is_unspecified() && !address.is_multicast(),
+    }
+}
+
+fn is_unicast_hardware_address(hardware_address: &[u8]) -> bool {
+    match hardware_address.first() {
+        Some(first) => first & 0x01 == 0 && hardware_address.iter().any(|b| *b != 0),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ROUTER: [u8; 6] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55];
+    const LAPTOP: [u8; 6] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x66];
+    const ATTACKER: [u8; 6] = [0x02, 0xDE, 0xAD, 0xBE, 0xEF, 0x01];
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    fn observation(address: &str, hardware_address: &[u8]) -> Observation {
+        Observation {
+            interface_index: 1,
+            address: ip(address),
+            hardware_address: hardware_address.to_vec(),
+        }
+    }
+
+    async fn run(detector: Detector, observations: Vec<Observation>) -> Vec<Alert> {
+        detect(detector, stream::iter(observations)).collect().await
+    }
+
+    fn gateway_detector() -> Detector {
+        Detector::new(
+            HashMap::new(),
+            [ip("192.168.1.1")].iter().copied().collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn steady_network_is_quiet() {
+        // Three polls of the same table
+        let mut observations = Vec::new();
+        for _ in 0..3 {
+            observations.push(observation("192.168.1.1", &ROUTER));
+            observations.push(observation("192.168.1.20", &LAPTOP));
+            observations.push(observation("fe80::1", &ROUTER));
+            observations.push(observation("fe80::2", &ROUTER));
+        }
+        assert!(run(gateway_detector(), observations).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn ignores_incomplete_and_multicast_entries() {
+        let observations = vec![
+            observation("192.168.1.20", &LAPTOP),
+            observation("192.168.1.20", &[0; 6]),
+            observation("192.168.1.20", &[]),
+            observation("192.168.1.20", &LAPTOP),
+            observation("224.0.0.22", &[0x01, 0x00, 0x5E, 0x00, 0x00, 0x16]),
+            observation("239.255.255.250", &[0x01, 0x00, 0x5E, 0x7F, 0xFF, 0xFA]),
+            observation("255.255.255.255", &[0xFF; 6]),
+            observation("192.168.1.255", &[0xFF; 6]),
+            observation("ff02::1", &[0x33, 0x33, 0x00, 0x00, 0x00, 0x01]),
+        ];
+        assert!(run(gateway_detector(), observations).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn detects_flip() {
+        let observations = vec![
+            observation("192.168.1.20", &LAPTOP),
+            observation("192.168.1.20", &ATTACKER),
+            observation("192.168.1.20", &ATTACKER),
+            observation("192.168.1.20", &LAPTOP),
+        ];
+        let alerts = run(gateway_detector(), observations).await;
+        assert_eq!(
+            alerts,
+            vec![
+                Alert::HardwareAddressChanged {
+                    address: ip("192.168.1.20"),
+                    old: LAPTOP.to_vec(),
+                    new: ATTACKER.to_vec(),
+                },
+                Alert::HardwareAddressChanged {
+                    address: ip("192.168.1.20"),
+                    old: ATTACKER.to_vec(),
+                    new: LAPTOP.to_vec(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn detects_gateway_takeover() {
+        // A classic mitm: the attacker claims the gateway while keeping its own address.
+        let observations = vec![
+            observation("192.168.1.1", &ROUTER),
+            observation("192.168.1.66", &ATTACKER),
+            observation("192.168.1.1", &ATTACKER),
+        ];
+        let alerts = run(gateway_detector(), observations).await;
+        assert_eq!(
+            alerts,
+            vec![
+                Alert::GatewayChanged {
+                    address: ip("192.168.1.1"),
+                    old: ROUTER.to_vec(),
+                    new: ATTACKER.to_vec(),
+                },
+                Alert::SharedHardwareAddress {
+                    hardware_address: ATTACKER.to_vec(),
+                    addresses: vec![ip("192.168.1.1"), ip("192.168.1.66")],
+                },
+            ]
+        );
+        assert_eq!(
+            alerts[0].to_string(),
+            "Gateway 192.168.1.1 changed from 00-11-22-33-44-55 to 02-DE-AD-BE-EF-01"
+        );
+    }
+
+    #[tokio::test]
+    async fn detects_shared_hardware_address_once_per_claim() {
+        let observations = vec![
+            observation("192.168.1.20", &LAPTOP),
+            observation("192.168.1.21", &LAPTOP),
+            observation("192.168.1.20", &LAPTOP),
+            observation("192.168.1.21", &LAPTOP),
+            observation("192.168.1.22", &LAPTOP),
+        ];
+        let alerts = run(gateway_detector(), observations).await;
+        assert_eq!(
+            alerts,
+            vec![
+                Alert::SharedHardwareAddress {
+                    hardware_address: LAPTOP.to_vec(),
+                    addresses: vec![ip("192.168.1.20"), ip("192.168.1.21")],
+                },
+                Alert::SharedHardwareAddress {
+                    hardware_address: LAPTOP.to_vec(),
+                    addresses: vec![ip("192.168.1.20"), ip("192.168.1.21"), ip("192.168.1.22")],
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn moved_address_releases_old_hardware_address() {
+        let observations = vec![
+            observation("192.168.1.20", &LAPTOP),
+            observation("192.168.1.20", &ROUTER),
+            // The laptop now only claims .21, so this is not shared.
+            observation("192.168.1.21", &LAPTOP),
+        ];
+        let alerts = run(gateway_detector(), observations).await;
+        assert_eq!(alerts.len(), 1);
+        assert!(matches!(alerts[0], Alert::HardwareAddressChanged { .. }));
+    }
+
+    #[tokio::test]
+    async fn interfaces_are_separate() {
+        // A host-only network reusing the address of the real gateway, polled twice
+        let on_interface = |interface_index, address, hardware_address: &[u8]| Observation {
+            interface_index,
+            ..observation(address, hardware_address)
+        };
+        let mut observations = Vec::new();
+        for _ in 0..2 {
+            observations.push(on_interface(1, "192.168.1.1", &ROUTER));
+            observations.push(on_interface(2, "192.168.1.1", &LAPTOP));
+            observations.push(on_interface(1, "fe80::1", &ROUTER));
+            observations.push(on_interface(2, "fe80::1", &LAPTOP));
+            observations.push(on_interface(2, "192.168.1.20", &ROUTER));
+        }
+        assert!(run(gateway_detector(), observations).await.is_empty());
+
+        // A change on one interface is still caught.
+        let observations = vec![
+            on_interface(1, "192.168.1.1", &ROUTER),
+            on_interface(2, "192.168.1.1", &LAPTOP),
+            on_interface(2, "192.168.1.1", &ATTACKER),
+        ];
+        assert_eq!(
+            run(gateway_detector(), observations).await,
+            vec![Alert::GatewayChanged {
+                address: ip("192.168.1.1"),
+                old: LAPTOP.to_vec(),
+                new: ATTACKER.to_vec(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn checks_known_neighbors() {
+        let (known, errors) = parse_known_neighbors(&[
+            KnownNeighbor {
+                address: ip("192.168.1.1"),
+                hardware_address: "00-11-22-33-44-55".to_string(),
+            },
+            KnownNeighbor {
+                address: ip("192.168.1.2"),
+                hardware_address: "00:11:22:33:44:55".to_string(),
+            },
+            KnownNeighbor {
+                address: ip("192.168.1.3"),
+                hardware_address: "not a mac".to_string(),
+            },
+        ]);
+        assert_eq!(known.len(), 2);
+        assert_eq!(errors.len(), 1);
+
+        let detector = Detector::new(known, [ip("192.168.1.1")].iter().copied().collect());
+        let observations = vec![
+            // Known to share a hardware address, so this is fine.
+            observation("192.168.1.1", &ROUTER),
+            observation("192.168.1.2", &ROUTER),
+            // Spoofed
+            observation("192.168.1.1", &ATTACKER),
+            observation("192.168.1.1", &ATTACKER),
+            // Back to normal
+            observation("192.168.1.1", &ROUTER),
+        ];
+        let alerts = run(detector, observations).await;
+        assert_eq!(
+            alerts,
+            vec![Alert::UnexpectedHardwareAddress {
+                address: ip("192.168.1.1"),
+                expected: ROUTER.to_vec(),
+                actual: ATTACKER.to_vec(),
+            }]
+        );
+    }
+}
@@ -1,3 +1,11 @@
+/// The text color of things that changed since the last refresh
+pub const HIGHLIGHT_TEXT_COLOR: iced::Color = iced::Color {
+    r: 1.0,
+    g: 0.84,
+    b: 0.3,
+    a: 1.0,
+};
+
 pub struct GreyStyle;
 
 impl iced::container::StyleSheet for GreyStyle {
@@ -0,0 +1,412 @@
+use anyhow::Context;
+use std::net::{
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+};
+
+/// The calculated properties of the subnet an address is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubnetInfo {
+    pub address: IpAddr,
+    pub prefix_length: u8,
+
+    pub netmask: IpAddr,
+    pub wildcard: IpAddr,
+    pub network: IpAddr,
+
+    /// Only ipv4 subnets of /30 or larger have a broadcast address.
+    pub broadcast: Option<IpAddr>,
+
+    pub first_host: IpAddr,
+    pub last_host: IpAddr,
+
+    /// The number of usable host addresses.
+    ///
+    /// This is `None` only for `::/0`, which has 2^128 addresses.
+    pub host_count: Option<u128>,
+}
+
+impl SubnetInfo {
+    /// Calculate the subnet of `address` with the given prefix length.
+    ///
+    /// Returns `None` if the prefix length is too long for the address family.
+    pub fn new(address: IpAddr, prefix_length: u8) -> Option<Self> {
+        let (bits, width) = address_to_bits(address);
+        if prefix_length > width {
+            return None;
+        }
+
+        let is_v4 = address.is_ipv4();
+        let host_bits = u32::from(width - prefix_length);
+        let all_ones = width_mask(width);
+        let mask = u128::MAX.checked_shl(host_bits).unwrap_or(0) & all_ones;
+        let wildcard = !mask & all_ones;
+        let network = bits & mask;
+        let last = network | wildcard;
+
+        let (broadcast, first_host, last_host, host_count) = if is_v4 {
+            match prefix_length {
+                // Point to point links use both addresses, see RFC 3021.
+                31 => (None, network, last, Some(2)),
+                32 => (None, network, network, Some(1)),
+                _ => (
+                    Some(last),
+                    network + 1,
+                    last - 1,
+                    Some((1u128 << host_bits) - 2),
+                ),
+            }
+        } else {
+            // Ipv6 has no broadcast address, so every address is usable.
+            (None, network, last, 1u128.checked_shl(host_bits))
+        };
+
+        Some(Self {
+            address,
+            prefix_length,
+
+            netmask: bits_to_address(mask, is_v4),
+            wildcard: bits_to_address(wildcard, is_v4),
+            network: bits_to_address(network, is_v4),
+
+            broadcast: broadcast.map(|broadcast| bits_to_address(broadcast, is_v4)),
+
+            first_host: bits_to_address(first_host, is_v4),
+            last_host: bits_to_address(last_host, is_v4),
+
+            host_count,
+        })
+    }
+
+    /// Parse an address with a prefix length or mask and calculate its subnet.
+    ///
+    /// Accepts "192.168.1.2/24", "192.168.1.2/255.255.255.0", "192.168.1.2 255.255.255.0" and "fe80::1/64".
+    pub fn parse(input: &str) -> anyhow::Result<Self> {
+        let input = input.trim();
+        let (address, suffix) = input
+            .split_once('/')
+            .or_else(|| input.split_once(char::is_whitespace))
+            .context("missing prefix length or mask, like /24")?;
+        let address: IpAddr = address.trim().parse().context("invalid address")?;
+        let suffix = suffix.trim();
+
+        let prefix_length = match suffix.parse::<u8>() {
+            Ok(prefix_length) => prefix_length,
+            Err(_) => {
+                let mask: Ipv4Addr = suffix.parse().context("invalid prefix length or mask")?;
+                if !address.is_ipv4() {
+                    anyhow::bail!("ipv6 addresses need a prefix length, not a mask");
+                }
+                prefix_length_from_mask(mask).context("mask is not contiguous")?
+            }
+        };
+
+        Self::new(address, prefix_length).with_context(|| {
+            format!(
+                "prefix length must be at most {}",
+                address_to_bits(address).1
+            )
+        })
+    }
+
+    /// Check if an address is in this subnet.
+    pub fn contains(&self, address: IpAddr) -> bool {
+        Self::new(address, self.prefix_length).map_or(false, |info| info.network == self.network)
+    }
+
+    /// Format the host count, using a power of two when it is too large to be readable.
+    pub fn format_host_count(&self) -> String {
+        match self.host_count {
+            Some(host_count) if host_count <= u128::from(u32::MAX) => host_count.to_string(),
+            _ => {
+                let host_bits = address_to_bits(self.address).1 - self.prefix_length;
+                format!("2^{}", host_bits)
+            }
+        }
+    }
+}
+
+/// Get the ipv4 mask for a prefix length, like 255.255.255.0 for 24.
+///
+/// Returns `None` if the prefix length is greater than 32.
+pub fn mask_from_prefix_length(prefix_length: u8) -> Option<Ipv4Addr> {
+    if prefix_length > 32 {
+        return None;
+    }
+
+    let mask = u32::MAX
+        .checked_shl(32 - u32::from(prefix_length))
+        .unwrap_or(0);
+    Some(Ipv4Addr::from(mask))
+}
+
+/// Get the prefix length of an ipv4 mask, like 24 for 255.255.255.0.
+///
+/// Returns `None` if the mask has gaps, like 255.0.255.0.
+pub fn prefix_length_from_mask(mask: Ipv4Addr) -> Option<u8> {
+    let mask = u32::from(mask);
+    let prefix_length = mask.leading_ones();
+    if mask.checked_shl(prefix_length).unwrap_or(0) != 0 {
+        return None;
+    }
+
+    Some(prefix_length as u8)
+}
+
+/// Get the bits of an address and its width.
+fn address_to_bits(address: IpAddr) -> (u128, u8) {
+    match address {
+        IpAddr::V4(address) => (u128::from(u32::from(address)), 32),
+        IpAddr::V6(address) => (u128::from(address), 128),
+    }
+}
+
+fn bits_to_address(bits: u128, is_v4: bool) -> IpAddr {
+    if is_v4 {
+        IpAddr::V4(Ipv4Addr::from(bits as u32))
+    } else {
+        IpAddr::V6(Ipv6Addr::from(bits))
+    }
+}
+
+/// Get a value with the low `width` bits set.
+fn width_mask(width: u8) -> u128 {
+    u128::MAX.checked_shr(128 - u32::from(width)).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn v4(address: &str) -> IpAddr {
+        IpAddr::V4(address.parse().unwrap())
+    }
+
+    fn v6(address: &str) -> IpAddr {
+        IpAddr::V6(address.parse().unwrap())
+    }
+
+    #[test]
+    fn mask_prefix_length_round_trip() {
+        for prefix_length in 0..=32 {
+            let mask = mask_from_prefix_length(prefix_length).unwrap();
+            assert_eq!(prefix_length_from_mask(mask), Some(prefix_length));
+        }
+        assert_eq!(mask_from_prefix_length(33), None);
+    }
+
+    #[test]
+    fn known_masks() {
+        assert_eq!(mask_from_prefix_length(0), Some(Ipv4Addr::new(0, 0, 0, 0)));
+        assert_eq!(
+            mask_from_prefix_length(8),
+            Some(Ipv4Addr::new(255, 0, 0, 0))
+        );
+        assert_eq!(
+            mask_from_prefix_length(20),
+            Some(Ipv4Addr::new(255, 255, 240, 0))
+        );
+        assert_eq!(
+            mask_from_prefix_length(32),
+            Some(Ipv4Addr::new(255, 255, 255, 255))
+        );
+    }
+
+    #[test]
+    fn non_contiguous_masks() {
+        assert_eq!(prefix_length_from_mask(Ipv4Addr::new(255, 0, 255, 0)), None);
+        assert_eq!(prefix_length_from_mask(Ipv4Addr::new(0, 0, 0, 1)), None);
+        assert_eq!(
+            prefix_length_from_mask(Ipv4Addr::new(255, 255, 255, 253)),
+            None
+        );
+    }
+
+    #[test]
+    fn v4_every_prefix_length() {
+        let address = v4("172.16.200.77");
+        for prefix_length in 0..=32u8 {
+            let info = SubnetInfo::new(address, prefix_length).unwrap();
+            let host_bits = 32 - u32::from(prefix_length);
+
+            assert_eq!(
+                info.netmask,
+                IpAddr::V4(mask_from_prefix_length(prefix_length).unwrap())
+            );
+            let mask = u32::from(mask_from_prefix_length(prefix_length).unwrap());
+            assert_eq!(info.wildcard, IpAddr::V4(Ipv4Addr::from(!mask)));
+            assert_eq!(info.network, IpAddr::V4(Ipv4Addr::from(0xAC10_C84D & mask)));
+
+            match prefix_length {
+                31 => assert_eq!(info.host_count, Some(2)),
+                32 => assert_eq!(info.host_count, Some(1)),
+                _ => assert_eq!(info.host_count, Some((1u128 << host_bits) - 2)),
+            }
+            assert_eq!(info.broadcast.is_some(), prefix_length <= 30);
+            assert!(info.first_host <= info.last_host);
+        }
+        assert_eq!(SubnetInfo::new(address, 33), None);
+    }
+
+    #[test]
+    fn v4_slash_24() {
+        let info = SubnetInfo::new(v4("192.168.1.42"), 24).unwrap();
+        assert_eq!(info.netmask, v4("255.255.255.0"));
+        assert_eq!(info.wildcard, v4("0.0.0.255"));
+        assert_eq!(info.network, v4("192.168.1.0"));
+        assert_eq!(info.broadcast, Some(v4("192.168.1.255")));
+        assert_eq!(info.first_host, v4("192.168.1.1"));
+        assert_eq!(info.last_host, v4("192.168.1.254"));
+        assert_eq!(info.host_count, Some(254));
+    }
+
+    #[test]
+    fn v4_unaligned() {
+        let info = SubnetInfo::new(v4("10.1.2.200"), 27).unwrap();
+        assert_eq!(info.netmask, v4("255.255.255.224"));
+        assert_eq!(info.wildcard, v4("0.0.0.31"));
+        assert_eq!(info.network, v4("10.1.2.192"));
+        assert_eq!(info.broadcast, Some(v4("10.1.2.223")));
+        assert_eq!(info.first_host, v4("10.1.2.193"));
+        assert_eq!(info.last_host, v4("10.1.2.222"));
+        assert_eq!(info.host_count, Some(30));
+    }
+
+    #[test]
+    fn v4_slash_30() {
+        let info = SubnetInfo::new(v4("10.0.0.5"), 30).unwrap();
+        assert_eq!(info.network, v4("10.0.0.4"));
+        assert_eq!(info.broadcast, Some(v4("10.0.0.7")));
+        assert_eq!(info.first_host, v4("10.0.0.5"));
+        assert_eq!(info.last_host, v4("10.0.0.6"));
+        assert_eq!(info.host_count, Some(2));
+    }
+
+    #[test]
+    fn v4_point_to_point() {
+        let info = SubnetInfo::new(v4("10.0.0.1"), 31).unwrap();
+        assert_eq!(info.network, v4("10.0.0.0"));
+        assert_eq!(info.broadcast, None);
+        assert_eq!(info.first_host, v4("10.0.0.0"));
+        assert_eq!(info.last_host, v4("10.0.0.1"));
+        assert_eq!(info.host_count, Some(2));
+    }
+
+    #[test]
+    fn v4_single_host() {
+        let info = SubnetInfo::new(v4("10.0.0.1"), 32).unwrap();
+        assert_eq!(info.netmask, v4("255.255.255.255"));
+        assert_eq!(info.wildcard, v4("0.0.0.0"));
+        assert_eq!(info.network, v4("10.0.0.1"));
+        assert_eq!(info.broadcast, None);
+        assert_eq!(info.first_host, v4("10.0.0.1"));
+        assert_eq!(info.last_host, v4("10.0.0.1"));
+        assert_eq!(info.host_count, Some(1));
+    }
+
+    #[test]
+    fn v4_everything() {
+        let info = SubnetInfo::new(v4("1.2.3.4"), 0).unwrap();
+        assert_eq!(info.netmask, v4("0.0.0.0"));
+        assert_eq!(info.wildcard, v4("255.255.255.255"));
+        assert_eq!(info.network, v4("0.0.0.0"));
+        assert_eq!(info.broadcast, Some(v4("255.255.255.255")));
+        assert_eq!(info.first_host, v4("0.0.0.1"));
+        assert_eq!(info.last_host, v4("255.255.255.254"));
+        assert_eq!(info.host_count, Some(u128::from(u32::MAX) - 1));
+    }
+
+    #[test]
+    fn v6_slash_64() {
+        let info = SubnetInfo::new(v6("2001:db8:1:2:aaaa:bbbb:cccc:dddd"), 64).unwrap();
+        assert_eq!(info.netmask, v6("ffff:ffff:ffff:ffff::"));
+        assert_eq!(info.wildcard, v6("::ffff:ffff:ffff:ffff"));
+        assert_eq!(info.network, v6("2001:db8:1:2::"));
+        assert_eq!(info.broadcast, None);
+        assert_eq!(info.first_host, v6("2001:db8:1:2::"));
+        assert_eq!(info.last_host, v6("2001:db8:1:2:ffff:ffff:ffff:ffff"));
+        assert_eq!(info.host_count, Some(1 << 64));
+        assert_eq!(info.format_host_count(), "2^64");
+    }
+
+    #[test]
+    fn v6_every_prefix_length() {
+        let address = v6("fe80::1234:5678:9abc:def0");
+        for prefix_length in 0..=128u8 {
+            let info = SubnetInfo::new(address, prefix_length).unwrap();
+            let host_bits = 128 - u32::from(prefix_length);
+            let mask = u128::MAX.checked_shl(host_bits).unwrap_or(0);
+
+            assert_eq!(info.netmask, IpAddr::V6(Ipv6Addr::from(mask)));
+            assert_eq!(info.wildcard, IpAddr::V6(Ipv6Addr::from(!mask)));
+            assert_eq!(info.host_count, 1u128.checked_shl(host_bits));
+            assert_eq!(info.broadcast, None);
+            assert_eq!(info.first_host, info.network);
+        }
+        assert_eq!(SubnetInfo::new(address, 129), None);
+    }
+
+    #[test]
+    fn v6_extremes() {
+        let info = SubnetInfo::new(v6("2001:db8::1"), 128).unwrap();
+        assert_eq!(info.network, v6("2001:db8::1"));
+        assert_eq!(info.last_host, v6("2001:db8::1"));
+        assert_eq!(info.host_count, Some(1));
+        assert_eq!(info.format_host_count(), "1");
+
+        let info = SubnetInfo::new(v6("2001:db8::1"), 0).unwrap();
+        assert_eq!(info.network, v6("::"));
+        assert_eq!(
+            info.last_host,
+            v6("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff")
+        );
+        assert_eq!(info.host_count, None);
+        assert_eq!(info.format_host_count(), "2^128");
+    }
+
+    #[test]
+    fn contains() {
+        let info = SubnetInfo::new(v4("192.168.1.2"), 24).unwrap();
+        assert!(info.contains(v4("192.168.1.0")));
+        assert!(info.contains(v4("192.168.1.255")));
+        assert!(!info.contains(v4("192.168.2.1")));
+        assert!(!info.contains(v6("::ffff:192.168.1.3")));
+
+        let info = SubnetInfo::new(v6("fe80::1"), 64).unwrap();
+        assert!(info.contains(v6("fe80::abcd")));
+        assert!(!info.contains(v6("fe81::1")));
+        assert!(!info.contains(v4("192.168.1.2")));
+    }
+
+    #[test]
+    fn parse_formats() {
+        let expected = SubnetInfo::new(v4("192.168.1.2"), 24).unwrap();
+        assert_eq!(SubnetInfo::parse("192.168.1.2/24").unwrap(), expected);
+        assert_eq!(SubnetInfo::parse(" 192.168.1.2 / 24 ").unwrap(), expected);
+        assert_eq!(
+            SubnetInfo::parse("192.168.1.2/255.255.255.0").unwrap(),
+            expected
+        );
+        assert_eq!(
+            SubnetInfo::parse("192.168.1.2 255.255.255.0").unwrap(),
+            expected
+        );
+        assert_eq!(
+            SubnetInfo::parse("fe80::1/64").unwrap(),
+            SubnetInfo::new(v6("fe80::1"), 64).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(SubnetInfo::parse("").is_err());
+        assert!(SubnetInfo::parse("192.168.1.2").is_err());
+        assert!(SubnetInfo::parse("192.168.1/24").is_err());
+        assert!(SubnetInfo::parse("192.168.1.2/33").is_err());
+        assert!(SubnetInfo::parse("192.168.1.2/255.0.255.0").is_err());
+        assert!(SubnetInfo::parse("192.168.1.2/abc").is_err());
+        assert!(SubnetInfo::parse("fe80::1/129").is_err());
+        assert!(SubnetInfo::parse("fe80::1/255.255.255.0").is_err());
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    style::{
+        ForegroundGreenTextInputStyle,
+        GreyStyleCopyTextHack,
+    },
+    subnet::SubnetInfo,
+    GreyStyle,
+};
+use iced::{
+    Clipboard,
+    Column,
+    Command,
+    Container,
+    Element,
+    Length,
+    Row,
+    Text,
+    TextInput,
+};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Nop,
+
+    UpdateInput(String),
+}
+
+pub struct SubnetCalculator {
+    input: String,
+
+    /// The calculated values, or the reason the input is invalid
+    result: Result<Vec<ResultRow>, String>,
+
+    input_state: iced::text_input::State,
+}
+
+impl SubnetCalculator {
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+
+            result: Ok(Vec::new()),
+
+            input_state: iced::text_input::State::new(),
+        }
+    }
+
+    fn calculate(&mut self) {
+        if self.input.trim().is_empty() {
+            self.result = Ok(Vec::new());
+            return;
+        }
+
+        self.result = SubnetInfo::parse(&self.input)
+            .map(|info| {
+                let mut rows = vec![ResultRow::new("Address", info.address.to_string())];
+                if info.address.is_ipv4() {
+                    rows.push(ResultRow::new("Mask", info.netmask.to_string()));
+                }
+                rows.push(ResultRow::new(
+                    "Prefix Length",
+                    info.prefix_length.to_string(),
+                ));
+                rows.push(ResultRow::new("Wildcard", info.wildcard.to_string()));
+                rows.push(ResultRow::new(
+                    "Network",
+                    format!("{}/{}", info.network, info.prefix_length),
+                ));
+                if let Some(broadcast) = info.broadcast {
+                    rows.push(ResultRow::new("Broadcast", broadcast.to_string()));
+                }
+                rows.push(ResultRow::new("First Host", info.first_host.to_string()));
+                rows.push(ResultRow::new("Last Host", info.last_host.to_string()));
+                rows.push(ResultRow::new("Host Count", info.format_host_count()));
+                rows
+            })
+            .map_err(|e| format!("{:#}", e));
+    }
+
+    pub fn update(&mut self, message: Message, _clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::Nop => Command::none(),
+            Message::UpdateInput(input) => {
+                self.input = input;
+                self.calculate();
+                Command::none()
+            }
+        }
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        let title = Text::new("Subnet Calculator").size(36);
+        let mut column = Column::new().spacing(10).push(title).push(
+            TextInput::new(
+                &mut self.input_state,
+                "192.168.1.2/24, 192.168.1.2 255.255.255.0 or 2001:db8::1/64",
+                &self.input,
+                Message::UpdateInput,
+            )
+            .style(ForegroundGreenTextInputStyle)
+            .size(15)
+            .padding(2),
+        );
+
+        match self.result.as_mut() {
+            Ok(rows) => {
+                for row in rows.iter_mut() {
+                    column = column.push(row.view());
+                }
+            }
+            Err(e) => {
+                column = column.push(Text::new(e.as_str()).size(15));
+            }
+        }
+
+        Container::new(Container::new(column).padding(20))
+            .style(GreyStyle)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+struct ResultRow {
+    label: &'static str,
+    value: String,
+    state: iced::text_input::State,
+}
+
+impl ResultRow {
+    fn new(label: &'static str, value: String) -> Self {
+        Self {
+            label,
+            value,
+            state: iced::text_input::State::new(),
+        }
+    }
+
+    fn view(&mut self) -> Element<Message> {
+        Row::new()
+            .push(
+                Text::new(format!("{}: ", self.label))
+                    .size(15)
+                    .width(Length::Units(120)),
+            )
+            .push(
+                TextInput::new(&mut self.state, "", &self.value, |_| Message::Nop)
+                    .style(GreyStyleCopyTextHack)
+                    .size(15),
+            )
+            .into()
+    }
+}
@@ -0,0 +1,88 @@
+use winreg::{
+    enums::{
+        HKEY_LOCAL_MACHINE,
+        KEY_READ,
+        KEY_WRITE,
+    },
+    RegKey,
+};
+
+/// The tcpip registry settings of an interface
+#[derive(Debug)]
+pub struct TcpipInterface {
+    key: RegKey,
+}
+
+impl TcpipInterface {
+    pub const TCPIP_INTERFACES_KEY_STR: &'static str =
+        "SYSTEM\\CurrentControlSet\\Services\\Tcpip\\Parameters\\Interfaces";
+    pub const ENABLE_DHCP_KEY: &'static str = "EnableDHCP";
+    pub const NAME_SERVER_KEY: &'static str = "NameServer";
+    pub const DHCP_NAME_SERVER_KEY: &'static str = "DhcpNameServer";
+    pub const DOMAIN_KEY: &'static str = "Domain";
+
+    /// Open the settings of the adapter with the given name.
+    ///
+    /// Adapter names have the form {<guid>}, the same as returned by `RegistryAdapter::get_name`.
+    /// You need admin access for this to work properly.
+    pub fn open(adapter_name: &str) -> std::io::Result<Self> {
+        Self::open_with_flags(adapter_name, KEY_READ | KEY_WRITE)
+    }
+
+    /// Open a read-only view of the settings of the adapter with the given name.
+    pub fn open_read_only(adapter_name: &str) -> std::io::Result<Self> {
+        Self::open_with_flags(adapter_name, KEY_READ)
+    }
+
+    fn open_with_flags(adapter_name: &str, flags: u32) -> std::io::Result<Self> {
+        let key = RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey_with_flags(
+            format!("{}\\{}", Self::TCPIP_INTERFACES_KEY_STR, adapter_name),
+            flags,
+        )?;
+        Ok(Self { key })
+    }
+
+    /// Set whether dhcp is enabled.
+    pub fn set_enable_dhcp(&self, enable: bool) -> std::io::Result<()> {
+        self.key
+            .set_value(Self::ENABLE_DHCP_KEY, &u32::from(enable))
+    }
+
+    /// Get the statically configured dns servers.
+    ///
+    /// This is empty if the dns servers come from dhcp.
+    pub fn get_name_server(&self) -> std::io::Result<String> {
+        self.get_string_or_empty(Self::NAME_SERVER_KEY)
+    }
+
+    /// Set the statically configured dns servers, as a comma separated list.
+    ///
+    /// Pass an empty string to use the dns servers from dhcp.
+    pub fn set_name_server(&self, name_server: &str) -> std::io::Result<()> {
+        self.key.set_value(Self::NAME_SERVER_KEY, &name_server)
+    }
+
+    /// Get the dns servers from dhcp.
+    pub fn get_dhcp_name_server(&self) -> std::io::Result<String> {
+        self.get_string_or_empty(Self::DHCP_NAME_SERVER_KEY)
+    }
+
+    /// Get the statically configured connection-specific dns suffix.
+    pub fn get_domain(&self) -> std::io::Result<String> {
+        self.get_string_or_empty(Self::DOMAIN_KEY)
+    }
+
+    /// Set the statically configured connection-specific dns suffix.
+    pub fn set_domain(&self, domain: &str) -> std::io::Result<()> {
+        self.key.set_value(Self::DOMAIN_KEY, &domain)
+    }
+
+    /// Get a string value, treating a missing value as empty.
+    fn get_string_or_empty(&self, name: &str) -> std::io::Result<String> {
+        match self.key.get_value(name) {
+            Ok(value) => Ok(value),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(e),
+        }
+    }
+}
@@ -0,0 +1,871 @@
+use crate::{
+    export::{
+        save_export,
+        ExportFormat,
+        ExportRecord,
+    },
+    format_mac_address_to_string,
+    icmp::{
+        send_echo,
+        EchoOptions,
+    },
+    neighbor_table::get_neighbors,
+    resolve_arp::parse_scoped_address,
+    sockaddr::ip_addr_to_sockaddr_inet,
+    style::{
+        ForegroundGreenButtonStyle,
+        ForegroundGreenTextInputStyle,
+        HIGHLIGHT_TEXT_COLOR,
+    },
+    GreyStyle,
+};
+use anyhow::Context;
+use iced::{
+    futures::{
+        stream::BoxStream,
+        Stream,
+        StreamExt,
+    },
+    Align,
+    Button,
+    Checkbox,
+    Clipboard,
+    Column,
+    Command,
+    Container,
+    Element,
+    Length,
+    PickList,
+    Row,
+    Scrollable,
+    Subscription,
+    Text,
+    TextInput,
+};
+use log::{
+    error,
+    info,
+    warn,
+};
+use std::{
+    ffi::OsString,
+    hash::Hash,
+    net::IpAddr,
+    os::windows::ffi::OsStringExt,
+    path::PathBuf,
+    sync::Arc,
+    time::Duration,
+};
+use winapi::{
+    shared::{
+        ws2def::{
+            NI_MAXHOST,
+            NI_NAMEREQD,
+            SOCKADDR_IN,
+        },
+        ws2ipdef::{
+            SOCKADDR_IN6,
+            SOCKADDR_INET,
+        },
+    },
+    um::{
+        winsock2::{
+            WSACleanup,
+            WSAStartup,
+            WSADATA,
+        },
+        ws2tcpip::GetNameInfoW,
+    },
+};
+
+const DEFAULT_MAX_HOPS: u8 = 30;
+const DEFAULT_PROBES: u8 = 3;
+const DEFAULT_TIMEOUT_MS: u64 = 1000;
+
+/// The most probes that may be sent per hop
+const MAX_PROBES: u8 = 10;
+
+/// The size of the data in each probe
+const PROBE_SIZE: u16 = 32;
+
+/// `WSAHOST_NOT_FOUND`, returned when an address has no name
+const WSAHOST_NOT_FOUND: i32 = 11001;
+
+/// `WSANO_DATA`, returned when an address has no name record
+const WSANO_DATA: i32 = 11004;
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    UpdateTarget(String),
+    UpdateMaxHops(String),
+    UpdateProbes(String),
+    UpdateTimeout(String),
+    ResolveNamesToggled(bool),
+    Start,
+    Stop,
+    Trace(u64, TraceEvent),
+
+    ExportFormatSelected(ExportFormat),
+    CopyExport,
+    SaveExport,
+    SaveExportComplete(Arc<anyhow::Result<PathBuf>>),
+}
+
+/// The settings of a trace
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TraceConfig {
+    pub target: IpAddr,
+    pub scope_id: Option<u32>,
+    pub max_hops: u8,
+    pub probes: u8,
+    pub timeout: Duration,
+    pub resolve_names: bool,
+}
+
+/// A router on the way to the target, or the target itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hop {
+    pub ttl: u8,
+
+    /// The address that answered, or `None` if no probe was answered
+    pub address: Option<IpAddr>,
+
+    /// The round trip time of each probe, `None` if it was lost
+    pub rtts: Vec<Option<Duration>>,
+    pub name: Option<String>,
+
+    /// The hardware address of an on-link first hop, from the neighbor table
+    pub hardware_address: Option<String>,
+    pub vendor: Option<&'static str>,
+
+    /// Whether the target answered, ending the trace
+    pub is_destination: bool,
+
+    /// The last error that was not a timeout, like an unreachable destination
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum TraceEvent {
+    Hop(Hop),
+    Finished,
+}
+
+/// Probe a single hop, blocking the current thread.
+fn probe_hop(config: &TraceConfig, ttl: u8) -> Hop {
+    let options = EchoOptions {
+        size: PROBE_SIZE,
+        ttl,
+        timeout: config.timeout,
+    };
+
+    let mut hop = Hop {
+        ttl,
+        address: None,
+        rtts: Vec::with_capacity(config.probes.into()),
+        name: None,
+        hardware_address: None,
+        vendor: None,
+        is_destination: false,
+        error: None,
+    };
+    for _ in 0..config.probes {
+        match send_echo(config.target, config.scope_id, options) {
+            Ok(reply) if reply.is_success() || reply.is_ttl_expired() => {
+                hop.address = hop.address.or(reply.from);
+                hop.is_destination |= reply.is_success();
+                hop.rtts.push(Some(reply.rtt));
+            }
+            Ok(reply) => {
+                if !reply.is_timed_out() {
+                    hop.address = hop.address.or(reply.from);
+                    hop.error = Some(reply.status_name());
+                }
+                hop.rtts.push(None);
+            }
+            Err(e) => {
+                hop.error = Some(e.to_string());
+                hop.rtts.push(None);
+            }
+        }
+    }
+
+    let address = match hop.address {
+        Some(address) => address,
+        None => return hop,
+    };
+
+    if config.resolve_names {
+        match lookup_name(address) {
+            Ok(name) => hop.name = name,
+            Err(e) => warn!("Failed to look up the name of {}: {}", address, e),
+        }
+    }
+
+    // Only the first hop can be on-link.
+    if ttl == 1 {
+        match get_neighbors() {
+            Ok(neighbors) => {
+                let neighbor = neighbors.into_iter().find(|neighbor| {
+                    neighbor.address == address && !neighbor.physical_address.is_empty()
+                });
+                if let Some(neighbor) = neighbor {
+                    hop.hardware_address = Some(
+                        format_mac_address_to_string(&neighbor.physical_address)
+                            .trim_end()
+                            .to_string(),
+                    );
+                    hop.vendor = crate::oui::vendor_name(&neighbor.physical_address);
+                }
+            }
+            Err(e) => warn!("Failed to get neighbors: {}", e),
+        }
+    }
+
+    hop
+}
+
+/// Probe each hop in turn, yielding each and then [`TraceEvent::Finished`].
+///
+/// The trace ends when the target answers, a hop reports an error like an unreachable destination, or `max_hops` is reached.
+pub fn trace(config: TraceConfig) -> impl Stream<Item = TraceEvent> {
+    let config = Arc::new(config);
+    iced::futures::stream::unfold(Some(1), move |ttl| {
+        let config = config.clone();
+        async move {
+            let ttl = ttl.filter(|ttl| *ttl <= config.max_hops)?;
+
+            let task_config = config.clone();
+            let hop = match tokio::task::spawn_blocking(move || probe_hop(&task_config, ttl)).await
+            {
+                Ok(hop) => hop,
+                Err(e) => {
+                    error!("tokio task failed to join: {}", e);
+                    return None;
+                }
+            };
+
+            let is_done = hop.is_destination || (hop.error.is_some() && hop.address.is_some());
+            let next_ttl = if is_done { None } else { ttl.checked_add(1) };
+            Some((TraceEvent::Hop(hop), next_ttl))
+        }
+    })
+    .chain(iced::futures::stream::once(async { TraceEvent::Finished }))
+}
+
+/// Get a subscription that runs a trace with `config`, tagging events with `id`.
+///
+/// Changing `id` starts a new trace, and dropping the subscription stops it.
+pub fn trace_subscription(id: u64, config: Arc<TraceConfig>) -> Subscription<(u64, TraceEvent)> {
+    Subscription::from_recipe(Trace { id, config })
+}
+
+struct Trace {
+    id: u64,
+    config: Arc<TraceConfig>,
+}
+
+impl<H, I> iced_native::subscription::Recipe<H, I> for Trace
+where
+    H: std::hash::Hasher,
+{
+    type Output = (u64, TraceEvent);
+
+    fn hash(&self, state: &mut H) {
+        std::any::TypeId::of::<Self>().hash(state);
+        self.id.hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: BoxStream<'static, I>) -> BoxStream<'static, Self::Output> {
+        let id = self.id;
+        trace((*self.config).clone())
+            .map(move |event| (id, event))
+            .boxed()
+    }
+}
+
+/// Look up the name of an address with reverse dns, blocking the current thread.
+///
+/// Returns `None` if the address has no name.
+pub fn lookup_name(address: IpAddr) -> std::io::Result<Option<String>> {
+    let mut wsa_data: WSADATA = unsafe { std::mem::zeroed() };
+    let code = unsafe { WSAStartup(0x0202, &mut wsa_data) };
+    if code != 0 {
+        return Err(std::io::Error::from_raw_os_error(code));
+    }
+
+    let sockaddr = ip_addr_to_sockaddr_inet(address);
+    let sockaddr_len = match address {
+        IpAddr::V4(_) => std::mem::size_of::<SOCKADDR_IN>(),
+        IpAddr::V6(_) => std::mem::size_of::<SOCKADDR_IN6>(),
+    };
+    let mut name = [0u16; NI_MAXHOST as usize];
+    let code = unsafe {
+        GetNameInfoW(
+            (&sockaddr as *const SOCKADDR_INET).cast(),
+            sockaddr_len as i32,
+            name.as_mut_ptr(),
+            name.len() as u32,
+            std::ptr::null_mut(),
+            0,
+            NI_NAMEREQD,
+        )
+    };
+    let result = if code == 0 {
+        let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+        Ok(Some(
+            OsString::from_wide(&name[..len])
+                .to_string_lossy()
+                .into_owned(),
+        ))
+    } else {
+        let error = std::io::Error::last_os_error();
+        match error.raw_os_error() {
+            Some(WSAHOST_NOT_FOUND) | Some(WSANO_DATA) => Ok(None),
+            _ => Err(error),
+        }
+    };
+
+    unsafe {
+        WSACleanup();
+    }
+
+    result
+}
+
+/// A flattened view of a hop, for exporting.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct HopRecord {
+    pub ttl: u8,
+    pub address: Option<String>,
+    pub name: Option<String>,
+
+    /// Round trip times in milliseconds, `None` for lost probes
+    pub rtts_ms: Vec<Option<u128>>,
+    pub hardware_address: Option<String>,
+    pub vendor: Option<String>,
+    pub error: Option<String>,
+}
+
+impl HopRecord {
+    pub fn new(hop: &Hop) -> Self {
+        Self {
+            ttl: hop.ttl,
+            address: hop.address.map(|address| address.to_string()),
+            name: hop.name.clone(),
+            rtts_ms: hop
+                .rtts
+                .iter()
+                .map(|rtt| rtt.map(|rtt| rtt.as_millis()))
+                .collect(),
+            hardware_address: hop.hardware_address.clone(),
+            vendor: hop.vendor.map(String::from),
+            error: hop.error.clone(),
+        }
+    }
+}
+
+impl ExportRecord for HopRecord {
+    const COLUMNS: &'static [&'static str] = &[
+        "Hop",
+        "Address",
+        "Name",
+        "RTTs",
+        "Hardware Address",
+        "Vendor",
+        "Error",
+    ];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.ttl.to_string(),
+            self.address.clone().unwrap_or_else(|| "*".to_string()),
+            self.name.clone().unwrap_or_default(),
+            format_rtts(self.rtts_ms.iter().copied()),
+            self.hardware_address.clone().unwrap_or_default(),
+            self.vendor.clone().unwrap_or_default(),
+            self.error.clone().unwrap_or_default(),
+        ]
+    }
+}
+
+/// Format round trip times in milliseconds like "1 ms, *, 3 ms", with lost probes as "*".
+fn format_rtts(rtts_ms: impl Iterator<Item = Option<u128>>) -> String {
+    rtts_ms
+        .map(|rtt| rtt.map_or_else(|| "*".to_string(), |rtt| format!("{} ms", rtt)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// A running trace
+struct RunningTrace {
+    id: u64,
+    config: Arc<TraceConfig>,
+}
+
+pub struct Traceroute {
+    target_input: String,
+    max_hops_input: String,
+    probes_input: String,
+    timeout_input: String,
+    resolve_names: bool,
+
+    running: Option<RunningTrace>,
+    next_id: u64,
+
+    hops: Vec<Hop>,
+    status: String,
+
+    export_format: ExportFormat,
+    export_status: String,
+
+    target_input_state: iced::text_input::State,
+    max_hops_input_state: iced::text_input::State,
+    probes_input_state: iced::text_input::State,
+    timeout_input_state: iced::text_input::State,
+    start_button_state: iced::button::State,
+    export_format_state: iced::pick_list::State<ExportFormat>,
+    copy_export_button_state: iced::button::State,
+    save_export_button_state: iced::button::State,
+    scroll_state: iced::scrollable::State,
+}
+
+impl Traceroute {
+    pub fn new() -> Self {
+        Self {
+            target_input: String::new(),
+            max_hops_input: DEFAULT_MAX_HOPS.to_string(),
+            probes_input: DEFAULT_PROBES.to_string(),
+            timeout_input: DEFAULT_TIMEOUT_MS.to_string(),
+            resolve_names: true,
+
+            running: None,
+            next_id: 0,
+
+            hops: Vec::new(),
+            status: String::new(),
+
+            export_format: ExportFormat::default(),
+            export_status: String::new(),
+
+            target_input_state: iced::text_input::State::new(),
+            max_hops_input_state: iced::text_input::State::new(),
+            probes_input_state: iced::text_input::State::new(),
+            timeout_input_state: iced::text_input::State::new(),
+            start_button_state: iced::button::State::new(),
+            export_format_state: iced::pick_list::State::default(),
+            copy_export_button_state: iced::button::State::new(),
+            save_export_button_state: iced::button::State::new(),
+            scroll_state: iced::scrollable::State::new(),
+        }
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        match self.running.as_ref() {
+            Some(running) => trace_subscription(running.id, running.config.clone())
+                .map(|(id, event)| Message::Trace(id, event)),
+            None => Subscription::none(),
+        }
+    }
+
+    /// Validate the inputs.
+    fn parse_config(&self) -> anyhow::Result<TraceConfig> {
+        let (target, scope_id) =
+            parse_scoped_address(&self.target_input).context("invalid target")?;
+        let max_hops: u8 = self
+            .max_hops_input
+            .trim()
+            .parse()
+            .context("invalid max hops")?;
+        if max_hops == 0 {
+            anyhow::bail!("max hops must be at least 1");
+        }
+        let probes: u8 = self.probes_input.trim().parse().context("invalid probes")?;
+        if probes == 0 || probes > MAX_PROBES {
+            anyhow::bail!("probes must be between 1 and {}", MAX_PROBES);
+        }
+        let timeout_ms: u64 = self
+            .timeout_input
+            .trim()
+            .parse()
+            .context("invalid timeout")?;
+        if timeout_ms == 0 {
+            anyhow::bail!("timeout must be at least 1 ms");
+        }
+
+        Ok(TraceConfig {
+            target,
+            scope_id,
+            max_hops,
+            probes,
+            timeout: Duration::from_millis(timeout_ms),
+            resolve_names: self.resolve_names,
+        })
+    }
+
+    /// Export the hops in the selected format.
+    fn export(&self) -> anyhow::Result<String> {
+        if self.hops.is_empty() {
+            anyhow::bail!("no hops to export");
+        }
+        let records: Vec<HopRecord> = self.hops.iter().map(HopRecord::new).collect();
+        self.export_format.export(&records)
+    }
+
+    pub fn update(&mut self, message: Message, clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::UpdateTarget(target_input) => {
+                self.target_input = target_input;
+            }
+            Message::UpdateMaxHops(max_hops_input) => {
+                self.max_hops_input = max_hops_input;
+            }
+            Message::UpdateProbes(probes_input) => {
+                self.probes_input = probes_input;
+            }
+            Message::UpdateTimeout(timeout_input) => {
+                self.timeout_input = timeout_input;
+            }
+            Message::ResolveNamesToggled(resolve_names) => {
+                self.resolve_names = resolve_names;
+            }
+            Message::Start => match self.parse_config() {
+                Ok(config) => {
+                    info!("Tracing the route to {}", self.target_input.trim());
+                    self.hops.clear();
+                    self.status = "Tracing...".to_string();
+                    self.running = Some(RunningTrace {
+                        id: self.next_id,
+                        config: Arc::new(config),
+                    });
+                    self.next_id += 1;
+                }
+                Err(e) => {
+                    self.status = format!("{:#}", e);
+                }
+            },
+            Message::Stop => {
+                // Dropping the subscription stops the trace.
+                self.running = None;
+                self.status = "Stopped".to_string();
+            }
+            Message::Trace(id, event) => {
+                // Ignore events from traces that were stopped.
+                if self.running.as_ref().map(|running| running.id) != Some(id) {
+                    return Command::none();
+                }
+
+                match event {
+                    TraceEvent::Hop(hop) => self.hops.push(hop),
+                    TraceEvent::Finished => {
+                        self.running = None;
+                        let is_reached = self.hops.last().map_or(false, |hop| hop.is_destination);
+                        self.status = if is_reached {
+                            format!("Done, {} hop(s)", self.hops.len())
+                        } else {
+                            "Done, the target was not reached".to_string()
+                        };
+                    }
+                }
+            }
+            Message::ExportFormatSelected(export_format) => {
+                self.export_format = export_format;
+            }
+            Message::CopyExport => match self.export() {
+                Ok(data) => {
+                    clipboard.write(data);
+                    self.export_status = format!("Copied {} to clipboard", self.export_format);
+                }
+                Err(e) => {
+                    error!("{:?}", e);
+                    self.export_status = format!("Failed to export: {:#}", e);
+                }
+            },
+            Message::SaveExport => {
+                let data = match self.export() {
+                    Ok(data) => data,
+                    Err(e) => {
+                        error!("{:?}", e);
+                        self.export_status = format!("Failed to export: {:#}", e);
+                        return Command::none();
+                    }
+                };
+                let extension = self.export_format.extension();
+                self.export_status = "Saving...".to_string();
+
+                return Command::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            save_export("traceroute", &data, extension)
+                        })
+                        .await
+                        .context("tokio task panicked")?
+                    },
+                    |result| Message::SaveExportComplete(Arc::new(result)),
+                );
+            }
+            Message::SaveExportComplete(result) => match result.as_ref() {
+                Ok(path) => {
+                    info!("Saved export to '{}'", path.display());
+                    self.export_status = format!("Saved to {}", path.display());
+                }
+                Err(e) => {
+                    error!("{:?}", e);
+                    self.export_status = format!("Failed to save export: {:#}", e);
+                }
+            },
+        }
+
+        Command::none()
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        let start_button = if self.running.is_some() {
+            Button::new(&mut self.start_button_state, Text::new("Stop").size(15))
+                .style(ForegroundGreenButtonStyle)
+                .on_press(Message::Stop)
+        } else {
+            Button::new(&mut self.start_button_state, Text::new("Start").size(15))
+                .style(ForegroundGreenButtonStyle)
+                .on_press(Message::Start)
+        };
+
+        let mut column = Column::new()
+            .spacing(10)
+            .push(Text::new("Traceroute").size(36))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(
+                        TextInput::new(
+                            &mut self.target_input_state,
+                            "Enter ipv4 or ipv6 address, like fe80::1%12",
+                            &self.target_input,
+                            Message::UpdateTarget,
+                        )
+                        .on_submit(Message::Start)
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(300)),
+                    )
+                    .push(start_button),
+            )
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Text::new("Max Hops").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.max_hops_input_state,
+                            "",
+                            &self.max_hops_input,
+                            Message::UpdateMaxHops,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(50)),
+                    )
+                    .push(Text::new("Probes per Hop").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.probes_input_state,
+                            "",
+                            &self.probes_input,
+                            Message::UpdateProbes,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(50)),
+                    )
+                    .push(Text::new("Timeout (ms)").size(15))
+                    .push(
+                        TextInput::new(
+                            &mut self.timeout_input_state,
+                            "",
+                            &self.timeout_input,
+                            Message::UpdateTimeout,
+                        )
+                        .style(ForegroundGreenTextInputStyle)
+                        .size(15)
+                        .padding(2)
+                        .width(Length::Units(60)),
+                    )
+                    .push(Checkbox::new(
+                        self.resolve_names,
+                        "Resolve names",
+                        Message::ResolveNamesToggled,
+                    )),
+            )
+            .push(Text::new(&self.status).size(15))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(PickList::new(
+                        &mut self.export_format_state,
+                        ExportFormat::ALL,
+                        Some(self.export_format),
+                        Message::ExportFormatSelected,
+                    ))
+                    .push(
+                        Button::new(&mut self.copy_export_button_state, Text::new("Copy"))
+                            .style(ForegroundGreenButtonStyle)
+                            .on_press(Message::CopyExport),
+                    )
+                    .push(
+                        Button::new(&mut self.save_export_button_state, Text::new("Save"))
+                            .style(ForegroundGreenButtonStyle)
+                            .on_press(Message::SaveExport),
+                    )
+                    .push(Text::new(&self.export_status).size(15)),
+            );
+
+        column = column.push(
+            Row::new()
+                .spacing(10)
+                .push(Text::new("Hop").size(15).width(Length::Units(40)))
+                .push(Text::new("RTTs").size(15).width(Length::Units(200)))
+                .push(Text::new("Address").size(15).width(Length::Units(250)))
+                .push(Text::new("Name").size(15).width(Length::Units(250)))
+                .push(Text::new("MAC").size(15)),
+        );
+        for hop in self.hops.iter() {
+            let rtts = format_rtts(hop.rtts.iter().map(|rtt| rtt.map(|rtt| rtt.as_millis())));
+            let address = match (hop.address, hop.error.as_ref()) {
+                (Some(address), Some(error)) => format!("{} ({})", address, error),
+                (Some(address), None) => address.to_string(),
+                (None, Some(error)) => error.clone(),
+                (None, None) => "*".to_string(),
+            };
+            let hardware_address = match (hop.hardware_address.as_ref(), hop.vendor) {
+                (Some(hardware_address), Some(vendor)) => {
+                    format!("{} ({})", hardware_address, vendor)
+                }
+                (Some(hardware_address), None) => hardware_address.clone(),
+                (None, _) => String::new(),
+            };
+
+            let mut address = Text::new(address).size(15).width(Length::Units(250));
+            if hop.error.is_some() {
+                address = address.color(HIGHLIGHT_TEXT_COLOR);
+            }
+            column = column.push(
+                Row::new()
+                    .spacing(10)
+                    .push(
+                        Text::new(hop.ttl.to_string())
+                            .size(15)
+                            .width(Length::Units(40)),
+                    )
+                    .push(Text::new(rtts).size(15).width(Length::Units(200)))
+                    .push(address)
+                    .push(
+                        Text::new(hop.name.as_deref().unwrap_or(""))
+                            .size(15)
+                            .width(Length::Units(250)),
+                    )
+                    .push(Text::new(hardware_address).size(15)),
+            );
+        }
+
+        Container::new(
+            Scrollable::new(&mut self.scroll_state)
+                .push(Container::new(column).padding(20))
+                .width(Length::Fill),
+        )
+        .style(GreyStyle)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::export::{
+        to_csv,
+        to_json,
+        to_markdown,
+    };
+
+    /// Records covering a full hop, a silent hop and an unreachable hop
+    fn records() -> Vec<HopRecord> {
+        vec![
+            HopRecord {
+                ttl: 1,
+                address: Some("192.168.1.1".to_string()),
+                name: Some("router.home.lan".to_string()),
+                rtts_ms: vec![Some(1), Some(0), Some(2)],
+                hardware_address: Some("00:1A:2B:3C:4D:5E".to_string()),
+                vendor: Some("Example, Inc.".to_string()),
+                error: None,
+            },
+            HopRecord {
+                ttl: 2,
+                address: None,
+                name: None,
+                rtts_ms: vec![None, None, None],
+                hardware_address: None,
+                vendor: None,
+                error: None,
+            },
+            HopRecord {
+                ttl: 3,
+                address: Some("10.0.0.1".to_string()),
+                name: None,
+                rtts_ms: vec![Some(12), None, Some(15)],
+                hardware_address: None,
+                vendor: None,
+                error: Some("destination host unreachable".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn format_rtts_all_timeouts() {
+        assert_eq!(format_rtts([None, None, None].iter().copied()), "*, *, *");
+    }
+
+    #[test]
+    fn format_rtts_mixed() {
+        assert_eq!(
+            format_rtts([Some(1), None, Some(3)].iter().copied()),
+            "1 ms, *, 3 ms"
+        );
+    }
+
+    #[test]
+    fn format_rtts_all_replies() {
+        assert_eq!(
+            format_rtts([Some(0), Some(10), Some(200)].iter().copied()),
+            "0 ms, 10 ms, 200 ms"
+        );
+    }
+
+    #[test]
+    fn json_golden() {
+        assert_eq!(
+            to_json(&records()).unwrap(),
+            include_str!("../tests/golden/hops.json")
+        );
+    }
+
+    #[test]
+    fn csv_golden() {
+        assert_eq!(to_csv(&records()), include_str!("../tests/golden/hops.csv"));
+    }
+
+    #[test]
+    fn markdown_golden() {
+        assert_eq!(
+            to_markdown(&records()),
+            include_str!("../tests/golden/hops.md")
+        );
+    }
+}
@@ -0,0 +1,629 @@
+use crate::{
+    format_mac_address_to_string,
+    magic_packet::{
+        magic_packet,
+        send_magic_packet,
+        SecureOn,
+        DEFAULT_PORT,
+    },
+    resolve_arp::{
+        get_sources,
+        SourceOption,
+    },
+    style::{
+        ForegroundGreenButtonStyle,
+        ForegroundGreenTextInputStyle,
+    },
+    subnet::SubnetInfo,
+    GreyStyle,
+    UserSettings,
+};
+use anyhow::Context;
+use iced::{
+    Align,
+    Button,
+    Clipboard,
+    Column,
+    Command,
+    Container,
+    Element,
+    Length,
+    PickList,
+    Row,
+    Scrollable,
+    Space,
+    Text,
+    TextInput,
+};
+use log::{
+    error,
+    info,
+};
+use macaddr::MacAddr;
+use std::{
+    net::{
+        IpAddr,
+        Ipv4Addr,
+        SocketAddrV4,
+    },
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// The name of the address book file in the data dir
+const ADDRESS_BOOK_FILE_NAME: &str = "wake_on_lan.json";
+
+/// A device that can be woken
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct WakeDevice {
+    pub name: String,
+
+    /// The hardware address, formatted like "00-11-22-33-44-55"
+    pub hardware_address: String,
+
+    pub broadcast: Ipv4Addr,
+    pub port: u16,
+
+    /// The local address to send from, or `None` to let windows pick
+    pub source: Option<Ipv4Addr>,
+
+    /// The SecureOn password, which is sent in the clear anyways
+    pub secure_on: Option<String>,
+}
+
+impl WakeDevice {
+    /// Make a device with the default broadcast address and port.
+    pub fn new(name: String, hardware_address: String) -> Self {
+        Self {
+            name,
+            hardware_address,
+            broadcast: Ipv4Addr::BROADCAST,
+            port: DEFAULT_PORT,
+            source: None,
+            secure_on: None,
+        }
+    }
+
+    /// Build the magic packet for this device.
+    pub fn magic_packet(&self) -> anyhow::Result<Vec<u8>> {
+        let hardware_address = parse_hardware_address(&self.hardware_address)?;
+        let secure_on = self
+            .secure_on
+            .as_deref()
+            .map(SecureOn::parse)
+            .transpose()
+            .context("invalid SecureOn password")?;
+        Ok(magic_packet(hardware_address, secure_on.as_ref()))
+    }
+
+    /// Send the magic packet for this device, blocking the current thread.
+    pub fn wake(&self) -> anyhow::Result<()> {
+        let packet = self.magic_packet()?;
+        send_magic_packet(
+            &packet,
+            SocketAddrV4::new(self.broadcast, self.port),
+            self.source,
+        )
+        .context("failed to send magic packet")
+    }
+}
+
+impl std::fmt::Display for WakeDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} via {}:{}",
+            self.name, self.hardware_address, self.broadcast, self.port
+        )?;
+        if let Some(source) = self.source {
+            write!(f, " from {}", source)?;
+        }
+        if self.secure_on.is_some() {
+            write!(f, " (SecureOn)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a 6 byte hardware address.
+fn parse_hardware_address(input: &str) -> anyhow::Result<[u8; 6]> {
+    match input.trim().parse().context("invalid hardware address")? {
+        MacAddr::V6(hardware_address) => Ok(hardware_address.into_array()),
+        MacAddr::V8(_) => anyhow::bail!("only 6 byte hardware addresses can be woken"),
+    }
+}
+
+/// The persisted list of named devices
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct AddressBook {
+    pub devices: Vec<WakeDevice>,
+}
+
+impl AddressBook {
+    pub fn path() -> anyhow::Result<PathBuf> {
+        Ok(UserSettings::data_dir()?.join(ADDRESS_BOOK_FILE_NAME))
+    }
+
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::path()?;
+        let data = std::fs::read_to_string(path).context("failed to read data")?;
+        serde_json::from_str(&data).context("failed to deserialize data")
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        std::fs::create_dir_all(UserSettings::data_dir()?).context("failed to create data dir")?;
+
+        let path = Self::path()?;
+        let data = serde_json::to_string_pretty(self).context("failed to serialize")?;
+        std::fs::write(path, data).context("failed to write")?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    UpdateName(String),
+    UpdateHardwareAddress(String),
+    UpdateBroadcast(String),
+    UpdatePort(String),
+    UpdateSecureOn(String),
+    SourceSelected(SourceOption),
+
+    Send,
+    SaveDevice,
+    WakeDevice(usize),
+    EditDevice(usize),
+    RemoveDevice(usize),
+
+    SendComplete(Arc<anyhow::Result<String>>),
+    AddressBookSaved(Arc<anyhow::Result<()>>),
+}
+
+pub struct WakeOnLan {
+    name: String,
+    hardware_address: String,
+    broadcast: String,
+    port: String,
+    secure_on: String,
+    sources: Vec<SourceOption>,
+    source: SourceOption,
+
+    address_book: AddressBook,
+    status: String,
+
+    scroll_state: iced::scrollable::State,
+    name_state: iced::text_input::State,
+    hardware_address_state: iced::text_input::State,
+    broadcast_state: iced::text_input::State,
+    port_state: iced::text_input::State,
+    secure_on_state: iced::text_input::State,
+    source_state: iced::pick_list::State<SourceOption>,
+    send_button_state: iced::button::State,
+    save_button_state: iced::button::State,
+    device_states: Vec<DeviceState>,
+}
+
+impl WakeOnLan {
+    pub fn new() -> Self {
+        let address_book = match AddressBook::load() {
+            Ok(address_book) => address_book,
+            Err(e) => {
+                info!("Not using a Wake-on-LAN address book: {:?}", e);
+                AddressBook::default()
+            }
+        };
+
+        Self {
+            name: String::new(),
+            hardware_address: String::new(),
+            broadcast: Ipv4Addr::BROADCAST.to_string(),
+            port: DEFAULT_PORT.to_string(),
+            secure_on: String::new(),
+            sources: get_sources(),
+            source: SourceOption::Automatic,
+
+            address_book,
+            status: String::new(),
+
+            scroll_state: iced::scrollable::State::new(),
+            name_state: iced::text_input::State::new(),
+            hardware_address_state: iced::text_input::State::new(),
+            broadcast_state: iced::text_input::State::new(),
+            port_state: iced::text_input::State::new(),
+            secure_on_state: iced::text_input::State::new(),
+            source_state: iced::pick_list::State::default(),
+            send_button_state: iced::button::State::new(),
+            save_button_state: iced::button::State::new(),
+            device_states: Vec::new(),
+        }
+    }
+
+    /// Reload the local addresses that can be sent from.
+    pub fn refresh_sources(&mut self) {
+        self.sources = get_sources();
+
+        // Fall back to automatic if the picked address is gone.
+        if !self.sources.contains(&self.source) {
+            self.source = SourceOption::Automatic;
+        }
+    }
+
+    /// Add a device to the address book, unless its hardware address is already in it.
+    pub fn add_device(&mut self, device: WakeDevice) -> Command<Message> {
+        if let Some(existing) = self
+            .address_book
+            .devices
+            .iter()
+            .find(|existing| existing.hardware_address == device.hardware_address)
+        {
+            self.status = format!(
+                "{} is already in the address book as \"{}\"",
+                device.hardware_address, existing.name
+            );
+            return Command::none();
+        }
+
+        self.status = format!("Added \"{}\" to the address book", device.name);
+        self.address_book.devices.push(device);
+        self.save_address_book_command()
+    }
+
+    /// Validate the inputs.
+    fn parse_device(&self) -> anyhow::Result<WakeDevice> {
+        let hardware_address = parse_hardware_address(&self.hardware_address)?;
+        let broadcast: Ipv4Addr = self
+            .broadcast
+            .trim()
+            .parse()
+            .context("invalid broadcast address")?;
+        let port: u16 = self.port.trim().parse().context("invalid port")?;
+        let secure_on = self.secure_on.trim();
+        let secure_on = if secure_on.is_empty() {
+            None
+        } else {
+            SecureOn::parse(secure_on).context("invalid SecureOn password")?;
+            Some(secure_on.to_string())
+        };
+
+        Ok(WakeDevice {
+            name: self.name.trim().to_string(),
+            hardware_address: format_mac_address_to_string(&hardware_address)
+                .trim_end()
+                .to_string(),
+            broadcast,
+            port,
+            source: match &self.source {
+                SourceOption::Automatic => None,
+                SourceOption::Address { address, .. } => Some(*address),
+            },
+            secure_on,
+        })
+    }
+
+    fn wake_command(&mut self, device: WakeDevice) -> Command<Message> {
+        self.status = "Sending...".to_string();
+        Command::perform(
+            async move {
+                let description = device.to_string();
+                tokio::task::spawn_blocking(move || device.wake())
+                    .await
+                    .context("tokio task failed to join")??;
+                Ok(description)
+            },
+            |res| Message::SendComplete(Arc::new(res)),
+        )
+    }
+
+    fn save_address_book_command(&self) -> Command<Message> {
+        let address_book = AddressBook {
+            devices: self.address_book.devices.clone(),
+        };
+
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    address_book
+                        .save()
+                        .context("failed to save Wake-on-LAN address book")
+                })
+                .await
+                .context("tokio task panicked")??;
+                Ok(())
+            },
+            |r| Message::AddressBookSaved(Arc::new(r)),
+        )
+    }
+
+    pub fn update(&mut self, message: Message, _clipboard: &mut Clipboard) -> Command<Message> {
+        match message {
+            Message::UpdateName(name) => {
+                self.name = name;
+                Command::none()
+            }
+            Message::UpdateHardwareAddress(hardware_address) => {
+                self.hardware_address = hardware_address;
+                Command::none()
+            }
+            Message::UpdateBroadcast(broadcast) => {
+                self.broadcast = broadcast;
+                Command::none()
+            }
+            Message::UpdatePort(port) => {
+                self.port = port;
+                Command::none()
+            }
+            Message::UpdateSecureOn(secure_on) => {
+                self.secure_on = secure_on;
+                Command::none()
+            }
+            Message::SourceSelected(source) => {
+                // Broadcast on the subnet of the source.
+                if let SourceOption::Address {
+                    address,
+                    prefix_length,
+                    ..
+                } = &source
+                {
+                    if let Some(broadcast) = SubnetInfo::new(IpAddr::V4(*address), *prefix_length)
+                        .and_then(|info| info.broadcast)
+                    {
+                        self.broadcast = broadcast.to_string();
+                    }
+                }
+                self.source = source;
+                Command::none()
+            }
+            Message::Send => match self.parse_device() {
+                Ok(device) => self.wake_command(device),
+                Err(e) => {
+                    self.status = format!("{:#}", e);
+                    Command::none()
+                }
+            },
+            Message::SaveDevice => {
+                let device = match self.parse_device() {
+                    Ok(device) if device.name.is_empty() => {
+                        self.status = "name is empty".to_string();
+                        return Command::none();
+                    }
+                    Ok(device) => device,
+                    Err(e) => {
+                        self.status = format!("{:#}", e);
+                        return Command::none();
+                    }
+                };
+
+                // Saving with an existing name updates that device.
+                match self
+                    .address_book
+                    .devices
+                    .iter_mut()
+                    .find(|existing| existing.name == device.name)
+                {
+                    Some(existing) => {
+                        self.status = format!("Updated \"{}\"", device.name);
+                        *existing = device;
+                    }
+                    None => {
+                        self.status = format!("Saved \"{}\"", device.name);
+                        self.address_book.devices.push(device);
+                    }
+                }
+                self.save_address_book_command()
+            }
+            Message::WakeDevice(i) => match self.address_book.devices.get(i) {
+                Some(device) => {
+                    let device = device.clone();
+                    self.wake_command(device)
+                }
+                None => Command::none(),
+            },
+            Message::EditDevice(i) => {
+                if let Some(device) = self.address_book.devices.get(i) {
+                    self.name = device.name.clone();
+                    self.hardware_address = device.hardware_address.clone();
+                    self.broadcast = device.broadcast.to_string();
+                    self.port = device.port.to_string();
+                    self.secure_on = device.secure_on.clone().unwrap_or_default();
+                    self.source = self
+                        .sources
+                        .iter()
+                        .find(|source| match source {
+                            SourceOption::Automatic => device.source.is_none(),
+                            SourceOption::Address { address, .. } => {
+                                device.source == Some(*address)
+                            }
+                        })
+                        .cloned()
+                        .unwrap_or(SourceOption::Automatic);
+                }
+                Command::none()
+            }
+            Message::RemoveDevice(i) => {
+                if i >= self.address_book.devices.len() {
+                    return Command::none();
+                }
+                let device = self.address_book.devices.remove(i);
+                self.status = format!("Removed \"{}\"", device.name);
+                self.save_address_book_command()
+            }
+            Message::SendComplete(res) => {
+                match res.as_ref() {
+                    Ok(description) => {
+                        info!("Sent magic packet to {}", description);
+                        self.status = "Sent".to_string();
+                    }
+                    Err(e) => {
+                        error!("{:?}", e);
+                        self.status = format!("{:#}", e);
+                    }
+                }
+                Command::none()
+            }
+            Message::AddressBookSaved(res) => {
+                if let Err(e) = res.as_ref() {
+                    error!("{:?}", e);
+                    self.status = format!("{:#}", e);
+                }
+                Command::none()
+            }
+        }
+    }
+
+    pub fn view(&mut self) -> Element<Message> {
+        let mut column = Column::new()
+            .spacing(10)
+            .push(Text::new("Wake-on-LAN").size(36))
+            .push(input_row(
+                "Name",
+                TextInput::new(
+                    &mut self.name_state,
+                    "Name (to save)",
+                    &self.name,
+                    Message::UpdateName,
+                ),
+            ))
+            .push(input_row(
+                "MAC Address",
+                TextInput::new(
+                    &mut self.hardware_address_state,
+                    "00-11-22-33-44-55",
+                    &self.hardware_address,
+                    Message::UpdateHardwareAddress,
+                )
+                .on_submit(Message::Send),
+            ))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(Align::Center)
+                    .push(Text::new("Source").size(15).width(Length::Units(150)))
+                    .push(PickList::new(
+                        &mut self.source_state,
+                        &self.sources[..],
+                        Some(self.source.clone()),
+                        Message::SourceSelected,
+                    )),
+            )
+            .push(input_row(
+                "Broadcast Address",
+                TextInput::new(
+                    &mut self.broadcast_state,
+                    "255.255.255.255",
+                    &self.broadcast,
+                    Message::UpdateBroadcast,
+                ),
+            ))
+            .push(input_row(
+                "Port",
+                TextInput::new(&mut self.port_state, "9", &self.port, Message::UpdatePort),
+            ))
+            .push(input_row(
+                "SecureOn Password",
+                TextInput::new(
+                    &mut self.secure_on_state,
+                    "Optional",
+                    &self.secure_on,
+                    Message::UpdateSecureOn,
+                ),
+            ))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(
+                        Button::new(&mut self.send_button_state, Text::new("Send").size(15))
+                            .style(ForegroundGreenButtonStyle)
+                            .on_press(Message::Send),
+                    )
+                    .push(
+                        Button::new(&mut self.save_button_state, Text::new("Save").size(15))
+                            .style(ForegroundGreenButtonStyle)
+                            .on_press(Message::SaveDevice),
+                    ),
+            )
+            .push(Text::new(&self.status).size(15))
+            .push(Text::new("Address Book").size(24));
+
+        if self.address_book.devices.is_empty() {
+            column = column.push(Text::new("No saved devices").size(15));
+        }
+
+        self.device_states
+            .resize_with(self.address_book.devices.len(), DeviceState::new);
+        for (i, (device, state)) in self
+            .address_book
+            .devices
+            .iter()
+            .zip(self.device_states.iter_mut())
+            .enumerate()
+        {
+            column = column.push(state.view(i, device));
+        }
+
+        Container::new(
+            Scrollable::new(&mut self.scroll_state)
+                .push(Container::new(column).padding(20))
+                .width(Length::Fill),
+        )
+        .style(GreyStyle)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
+    }
+}
+
+/// Make a labeled input of the Wake-on-LAN form.
+fn input_row<'a>(label: &str, input: TextInput<'a, Message>) -> Row<'a, Message> {
+    Row::new()
+        .spacing(10)
+        .align_items(Align::Center)
+        .push(Text::new(label).size(15).width(Length::Units(150)))
+        .push(
+            input
+                .style(ForegroundGreenTextInputStyle)
+                .size(15)
+                .padding(2)
+                .width(Length::Units(200)),
+        )
+}
+
+/// The widget states of a device in the address book
+struct DeviceState {
+    wake_button_state: iced::button::State,
+    edit_button_state: iced::button::State,
+    remove_button_state: iced::button::State,
+}
+
+impl DeviceState {
+    fn new() -> Self {
+        Self {
+            wake_button_state: iced::button::State::new(),
+            edit_button_state: iced::button::State::new(),
+            remove_button_state: iced::button::State::new(),
+        }
+    }
+
+    fn view(&mut self, i: usize, device: &WakeDevice) -> Element<Message> {
+        Row::new()
+            .spacing(10)
+            .align_items(Align::Center)
+            .push(Space::new(Length::Units(20), Length::Shrink))
+            .push(Text::new(device.to_string()).size(15).width(Length::Fill))
+            .push(
+                Button::new(&mut self.wake_button_state, Text::new("Wake").size(15))
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::WakeDevice(i)),
+            )
+            .push(
+                Button::new(&mut self.edit_button_state, Text::new("Edit").size(15))
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::EditDevice(i)),
+            )
+            .push(
+                Button::new(&mut self.remove_button_state, Text::new("Remove").size(15))
+                    .style(ForegroundGreenButtonStyle)
+                    .on_press(Message::RemoveDevice(i)),
+            )
+            .into()
+    }
+}